@@ -1,7 +1,6 @@
 use std::{
     error::Error,
     fmt::Display,
-    sync::Arc,
     time::{Duration, Instant},
 };
 
@@ -12,29 +11,35 @@ use ratatui::{
     layout::{Constraint, Direction, Layout, Position},
     Frame, Terminal,
 };
-use reqwest::cookie::Jar;
 use tokio::{sync::mpsc, task::AbortHandle};
 
 #[cfg(feature = "captcha")]
 use crate::widget::captcha::CaptchaPopup;
 
 use crate::{
-    client::{Client, DownloadClientResult, SingleDownloadResult},
+    cache::{CacheHit, ResultCache},
+    client::{Client, DownloadClientResult, DownloadError, SingleDownloadResult},
     clip::ClipboardManager,
     config::{Config, ConfigManager},
-    results::Results,
+    history::{HistoryEntry, SearchHistory},
+    results::{filter::ResultFilter, ResultResponse, ResultTable, Results},
     source::{
-        nyaa_html::NyaaHtmlSource, request_client, Item, Source, SourceInfo, SourceResults, Sources,
+        command, cookies,
+        pages::{self, AllPagesConfig},
+        request_client, Item, SourceError, SourceExtraConfig, SourceInfo, SourceResults, Sources,
     },
-    sync::{EventSync, ReloadType, SearchQuery},
+    sync::{DetailsResult, EventSync, ReloadType, SearchQuery},
     theme::{self, Theme},
     util::conv::key_to_string,
     widget::{
         batch::BatchWidget,
+        bookmarks::{Bookmarks, BookmarksPopup},
         category::CategoryPopup,
         clients::ClientsPopup,
+        details::DetailsPopup,
         filter::FilterPopup,
         help::HelpPopup,
+        local_filter::LocalFilterPopup,
         notifications::{Notification, NotificationWidget},
         page::PagePopup,
         results::ResultsWidget,
@@ -61,16 +66,24 @@ pub static APP_NAME: &str = "nyaa";
 // To ensure that other events will get a chance to be received
 static ANIMATE_SLEEP_MILLIS: u64 = 5;
 
+// Relative dates are shown down to the second
+static RELATIVE_DATE_REFRESH_MILLIS: u64 = 1000;
+
 #[derive(PartialEq, Clone)]
 pub enum LoadType {
     Sourcing,
     Searching,
+    Reloading,
     SolvingCaptcha(String),
     Sorting,
     Filtering,
     Categorizing,
     Batching,
     Downloading,
+    Details,
+    LocalFiltering,
+    AllPages(AllPagesConfig),
+    Appending,
 }
 
 #[derive(PartialEq, Clone)]
@@ -83,9 +96,12 @@ pub enum Mode {
     Sort(SortDir),
     Batch,
     Filter,
+    LocalFilter,
     Theme,
     Sources,
     Clients,
+    Bookmarks,
+    Details,
     Page,
     User,
     Help,
@@ -103,9 +119,12 @@ widgets! {
         category: [Mode::Category]  => CategoryPopup,
         sort: [Mode::Sort(_)]  => SortPopup,
         filter: [Mode::Filter]  => FilterPopup,
+        local_filter: [Mode::LocalFilter]  => LocalFilterPopup,
         theme: [Mode::Theme]  => ThemePopup,
         sources: [Mode::Sources]  => SourcesPopup,
         clients: [Mode::Clients]  => ClientsPopup,
+        bookmarks: [Mode::Bookmarks]  => BookmarksPopup,
+        details: [Mode::Details]  => DetailsPopup,
         page: [Mode::Page]  => PagePopup,
         user: [Mode::User] => UserPopup,
         help: [Mode::Help] => HelpPopup,
@@ -119,12 +138,17 @@ impl Display for LoadType {
         let s = match self {
             LoadType::Sourcing => "Sourcing",
             LoadType::Searching => "Searching",
+            LoadType::Reloading => "Reloading",
             LoadType::SolvingCaptcha(_) => "Solving",
             LoadType::Sorting => "Sorting",
             LoadType::Filtering => "Filtering",
             LoadType::Categorizing => "Categorizing",
             LoadType::Batching => "Downloading Batch",
             LoadType::Downloading => "Downloading",
+            LoadType::Details => "Loading details",
+            LoadType::LocalFiltering => "Filtering results",
+            LoadType::AllPages(_) => "Loading all pages",
+            LoadType::Appending => "Loading next page",
         };
         write!(f, "{}", s)
     }
@@ -139,9 +163,12 @@ impl Display for Mode {
             Mode::Category => "Category",
             Mode::Sort(_) => "Sort",
             Mode::Filter => "Filter",
+            Mode::LocalFilter => "Local Filter",
             Mode::Theme => "Theme",
             Mode::Sources => "Sources",
             Mode::Clients => "Clients",
+            Mode::Bookmarks => "Bookmarks",
+            Mode::Details => "Details",
             Mode::Loading(_) => "Loading",
             Mode::Page => "Page",
             Mode::User => "User",
@@ -163,6 +190,8 @@ pub struct App {
 pub struct Context {
     pub mode: Mode,
     pub load_type: Option<LoadType>,
+    /// Retries of the current load, shown alongside the loading message
+    pub load_progress: Option<String>,
     pub themes: IndexMap<String, Theme>,
     pub src_info: SourceInfo,
     pub theme: Theme,
@@ -174,6 +203,11 @@ pub struct Context {
     pub batch: Vec<Item>,
     pub last_key: String,
     pub results: Results,
+    pub history: SearchHistory,
+    pub bookmarks: Bookmarks,
+    pub local_filter: Option<ResultFilter>,
+    /// Page of the last captcha which must be solved in a browser, opened with `O`
+    pub captcha_url: Option<String>,
     pub deltatime: f64,
     //errors: Vec<String>,
     notifications: Vec<Notification>,
@@ -181,6 +215,8 @@ pub struct Context {
     should_quit: bool,
     should_dismiss_notifications: bool,
     should_save_config: bool,
+    should_save_bookmarks: bool,
+    should_save_cookies: bool,
     skip_reload: bool,
 }
 
@@ -205,6 +241,35 @@ impl Context {
         self.notifications.push(notif);
     }
 
+    /// Notify about a failed load along with what can be done about it
+    pub fn notify_source_error(&mut self, e: SourceError) {
+        let hint = match &e {
+            e if e.is_transient() => Some("Press r to retry".to_owned()),
+            SourceError::Captcha { url } => {
+                self.captcha_url = Some(url.to_owned());
+                Some("Press O to solve the captcha in your browser".to_owned())
+            }
+            SourceError::Auth { key, .. } | SourceError::Config { key, .. } => {
+                Some(format!("Check \"{}\" in your config", key))
+            }
+            _ => None,
+        };
+        match hint {
+            Some(hint) => self.notify_error(format!("{}\n\n{}", e, hint)),
+            None => self.notify_error(e),
+        }
+    }
+
+    /// Notify about a failed download, pointing at the config when it is the cause
+    pub fn notify_download_error(&mut self, e: DownloadError) {
+        match &e {
+            DownloadError::Auth { key, .. } | DownloadError::Config { key, .. } => {
+                self.notify_error(format!("{}\n\nCheck \"{}\" in your config", e, key))
+            }
+            _ => self.notify_error(e),
+        }
+    }
+
     pub fn dismiss_notifications(&mut self) {
         self.should_dismiss_notifications = true;
     }
@@ -215,6 +280,66 @@ impl Context {
         Ok(())
     }
 
+    pub fn save_bookmarks(&mut self) {
+        self.should_save_bookmarks = true;
+    }
+
+    /// Hide loaded results not matching the local filter, rebuilding the results table
+    pub fn apply_local_filter(&mut self) {
+        if self.results.all.is_some() {
+            // Every page is loaded, so all of them are filtered before showing the current one
+            let page_size = self.config.all_pages.page_size;
+            self.results
+                .paginate(self.page, page_size, self.local_filter.as_ref());
+            if self.page > self.results.response.last_page {
+                self.page = self.results.response.last_page;
+                self.results
+                    .paginate(self.page, page_size, self.local_filter.as_ref());
+            }
+            self.format_results();
+            return;
+        }
+        if self.local_filter.is_none() && self.results.unfiltered.is_none() {
+            return;
+        }
+        let items = match self.results.unfiltered.take() {
+            Some(items) => items,
+            None => std::mem::take(&mut self.results.response.items),
+        };
+        match &self.local_filter {
+            Some(filter) => {
+                self.results.response.items = items
+                    .iter()
+                    .filter(|i| filter.matches(i))
+                    .cloned()
+                    .collect();
+                self.results.unfiltered = Some(items);
+            }
+            None => self.results.response.items = items,
+        }
+        self.format_results();
+    }
+
+    /// Show the next page after the loaded results, e.g. when scrolling past the last one
+    pub fn append_results(&mut self, response: ResultResponse) {
+        self.results.append(response);
+        match self.results.unfiltered.is_some() {
+            true => self.apply_local_filter(),
+            false => self.format_results(),
+        }
+    }
+
+    /// Rebuild the results table from the loaded results, e.g. to update relative dates
+    pub fn format_results(&mut self) {
+        self.results.table = self.src.format_table(
+            &self.results.response.items,
+            &self.results.search,
+            &self.config.sources,
+            &SourceExtraConfig::from(&self.config),
+            &self.theme,
+        );
+    }
+
     pub fn quit(&mut self) {
         self.should_quit = true;
     }
@@ -225,8 +350,9 @@ impl Default for Context {
         Context {
             mode: Mode::Loading(LoadType::Searching),
             load_type: None,
+            load_progress: None,
             themes: theme::default_themes(),
-            src_info: NyaaHtmlSource::info(),
+            src_info: Sources::Nyaa.info(),
             theme: Theme::default(),
             config: Config::default(),
             notifications: Vec::new(),
@@ -237,11 +363,17 @@ impl Default for Context {
             batch: vec![],
             last_key: "".to_owned(),
             results: Results::default(),
+            history: SearchHistory::default(),
+            bookmarks: Bookmarks::default(),
+            local_filter: None,
+            captcha_url: None,
             deltatime: 0.0,
             failed_config_load: true,
             should_quit: false,
             should_dismiss_notifications: false,
             should_save_config: false,
+            should_save_bookmarks: false,
+            should_save_cookies: false,
             skip_reload: false,
         }
     }
@@ -258,12 +390,15 @@ impl App {
 
         let timer = tokio::time::sleep(Duration::from_millis(ANIMATE_SLEEP_MILLIS));
         tokio::pin!(timer);
+        let date_timer = tokio::time::sleep(Duration::from_millis(RELATIVE_DATE_REFRESH_MILLIS));
+        tokio::pin!(date_timer);
 
-        let (tx_res, mut rx_res) =
-            mpsc::channel::<Result<SourceResults, Box<dyn Error + Send + Sync>>>(32);
+        let (tx_res, mut rx_res) = mpsc::channel::<Result<SourceResults, SourceError>>(32);
         let (tx_evt, mut rx_evt) = mpsc::channel::<Event>(100);
         let (tx_dl, mut rx_dl) = mpsc::channel::<DownloadClientResult>(100);
         let (tx_cfg, mut rx_cfg) = mpsc::channel::<ReloadType>(1);
+        let (tx_details, mut rx_details) = mpsc::channel::<DetailsResult>(32);
+        let (tx_next, mut rx_next) = mpsc::channel::<Result<SourceResults, SourceError>>(32);
 
         tokio::task::spawn(sync.clone().read_event_loop(tx_evt));
         tokio::task::spawn(sync.clone().watch_config_loop(tx_cfg));
@@ -287,12 +422,36 @@ impl App {
             }
         }
 
-        let jar = Arc::new(Jar::default());
+        if ctx.config.search_history_size > 0 {
+            match config_manager.load_history() {
+                Ok(history) => ctx.history = history,
+                Err(e) => ctx.notify_error(format!("Failed to load search history:\n{}", e)),
+            }
+        }
+
+        match config_manager.load_bookmarks() {
+            Ok(bookmarks) => ctx.bookmarks = bookmarks,
+            Err(e) => ctx.notify_error(format!("Failed to load bookmarks:\n{}", e)),
+        }
+
+        // Cookies are kept between sessions so captchas don't need to be solved again
+        let cookies_path = config_manager.cookies_path();
+        let (jar, errors) = cookies::open(cookies_path.as_ref(), &ctx.config.sources);
+        errors.into_iter().for_each(|e| ctx.notify_error(e));
         let source_rqclient =
             request_client(&jar, ctx.config.timeout, ctx.config.request_proxy.clone())?;
         // Don't use proxy for clients
         let client_rqclient = request_client(&jar, ctx.config.timeout, None)?;
         let mut last_load_abort: Option<AbortHandle> = None;
+        let mut cache = ResultCache::new(config_manager.cache_path(), ctx.config.cache_ttl);
+        cache.configure(&ctx.config.sources);
+        // Whether the current load is refreshing results shown from the cache
+        let mut refreshing = false;
+        // Next page loading in the background with infinite scroll, and the page once loaded
+        let mut prefetch: Option<(Sources, SearchQuery, AbortHandle)> = None;
+        let mut prefetched: Option<(Sources, Results)> = None;
+        // Background page which failed, only loaded again when appending it explicitly
+        let mut prefetch_failed: Option<(Sources, SearchQuery)> = None;
         let mut last_time: Option<Instant> = None;
 
         let (clipboard, err) = &mut if TEST {
@@ -311,6 +470,20 @@ impl App {
                 }
                 ctx.should_save_config = false;
             }
+            if ctx.should_save_bookmarks {
+                if let Err(e) = config_manager.store_bookmarks(&ctx.bookmarks) {
+                    ctx.notify_error(format!("Failed to save bookmarks:\n{}", e));
+                }
+                ctx.should_save_bookmarks = false;
+            }
+            if ctx.should_save_cookies {
+                if let Some(path) = cookies_path.as_ref() {
+                    if let Err(e) = jar.save(path) {
+                        ctx.notify_error(format!("Failed to save cookies:\n{}", e));
+                    }
+                }
+                ctx.should_save_cookies = false;
+            }
             if !ctx.notifications.is_empty() {
                 ctx.notifications
                     .clone()
@@ -335,6 +508,65 @@ impl App {
             if ctx.mode == Mode::Batch && ctx.batch.is_empty() {
                 ctx.mode = Mode::Normal;
             }
+            // Command sources are described in the background
+            if command::take_described().iter().any(|n| n == ctx.src.id()) {
+                ctx.src.apply_info(ctx, &mut self.widgets);
+            }
+
+            // Load the next page once the cursor nears the last result, so it can be appended right away
+            let appending = ctx.load_type == Some(LoadType::Appending);
+            let rows = match ctx.load_type {
+                Some(LoadType::Appending) => 1,
+                None if !matches!(ctx.mode, Mode::Loading(_)) => ctx.config.prefetch_rows,
+                _ => 0,
+            };
+            if let Some(next) = self
+                .widgets
+                .results
+                .next_page(ctx, rows)
+                .filter(|_| ctx.config.infinite_scroll)
+            {
+                let is_next = |src: &Sources, search: &SearchQuery| {
+                    *src == ctx.src && search.page == next.page && search.same_query(&next)
+                };
+                let ready = prefetched
+                    .as_ref()
+                    .is_some_and(|(src, rt)| is_next(src, &rt.search));
+                let loading = prefetch
+                    .as_ref()
+                    .is_some_and(|(src, search, _)| is_next(src, search));
+                let failed = prefetch_failed
+                    .as_ref()
+                    .is_some_and(|(src, search)| is_next(src, search));
+                if !ready && !loading && (!failed || appending) {
+                    match cache.get(ctx.src, &next) {
+                        Some(CacheHit::Fresh(res)) => {
+                            prefetched =
+                                Some((ctx.src, Results::new(next, res, ResultTable::default())));
+                        }
+                        _ => {
+                            if let Some((_, _, handle)) = prefetch.take() {
+                                handle.abort();
+                            }
+                            let task = tokio::spawn(sync.clone().load_results(
+                                tx_next.clone(),
+                                LoadType::Searching,
+                                ctx.src,
+                                source_rqclient.clone(),
+                                next.clone(),
+                                ctx.config.sources.clone(),
+                                ctx.theme.clone(),
+                                ctx.config.clone().into(),
+                            ));
+                            prefetch = Some((ctx.src, next, task.abort_handle()));
+                            prefetch_failed = None;
+                        }
+                    }
+                }
+            }
+            if appending && self.append_next_page(ctx, &mut prefetched) {
+                ctx.load_type = None;
+            }
 
             self.get_help(ctx);
             terminal.draw(|f| self.draw(ctx, f))?;
@@ -377,6 +609,48 @@ impl App {
                         ));
                         continue;
                     }
+                    LoadType::Details => {
+                        if let Some(item) = self
+                            .widgets
+                            .results
+                            .table
+                            .selected()
+                            .and_then(|i| ctx.results.response.items.get(i))
+                        {
+                            // Merged results are keyed by the source each item is from
+                            let src = ctx.src.of_item(item);
+                            if !self
+                                .widgets
+                                .details
+                                .cache
+                                .contains_key(&(src, item.id.to_owned()))
+                            {
+                                tokio::spawn(sync.clone().load_details(
+                                    tx_details.clone(),
+                                    ctx.src,
+                                    source_rqclient.clone(),
+                                    item.to_owned(),
+                                    ctx.config.sources.clone(),
+                                ));
+                            }
+                            self.widgets.details.show(src, item.to_owned());
+                            ctx.mode = Mode::Details;
+                        }
+                        continue;
+                    }
+                    LoadType::LocalFiltering => {
+                        ctx.apply_local_filter();
+                        self.widgets.results.reset();
+                        continue;
+                    }
+                    LoadType::Appending => {
+                        // Wait for the next page if it isn't loaded yet
+                        if !self.append_next_page(ctx, &mut prefetched) {
+                            ctx.load_type = Some(LoadType::Appending);
+                            ctx.load_progress = None;
+                        }
+                        continue;
+                    }
                     LoadType::Sourcing => {
                         // On sourcing, update info, reset things like category, etc.
                         ctx.src.apply(ctx, &mut self.widgets);
@@ -384,12 +658,31 @@ impl App {
                     _ => {}
                 }
 
-                ctx.load_type = Some(load_type.clone());
-
-                if let Some(handle) = last_load_abort.as_ref() {
-                    handle.abort();
+                // Restore the options of a query recalled from the search history
+                if let Some(entry) = self
+                    .widgets
+                    .search
+                    .take_recalled()
+                    .and_then(|i| ctx.history.entries.get(i).cloned())
+                {
+                    // Keep the query, it may have been edited after recalling it
+                    HistoryEntry {
+                        query: self.widgets.search.input.input.clone(),
+                        ..entry
+                    }
+                    .apply(ctx, &mut self.widgets);
+                }
+                if let Some(bookmark) = self
+                    .widgets
+                    .bookmarks
+                    .take_launched()
+                    .and_then(|i| ctx.bookmarks.bookmarks.get(i).cloned())
+                {
+                    bookmark.search.apply(ctx, &mut self.widgets);
                 }
 
+                // Only record queries submitted from the search input, not page flips
+                let submitted = self.widgets.search.take_submitted();
                 let search = SearchQuery {
                     query: self.widgets.search.input.input.clone(),
                     page: ctx.page,
@@ -399,6 +692,75 @@ impl App {
                     user: ctx.user.clone(),
                 };
 
+                // Results with every page loaded are sorted and paginated locally
+                let load_type = match load_type {
+                    LoadType::Searching | LoadType::Sorting
+                        if ctx.results.all.is_some() && ctx.results.search.same_query(&search) =>
+                    {
+                        if let (Some(all), Some(sort)) = (
+                            ctx.results.all.as_mut(),
+                            ctx.src_info.sorts.get(search.sort.sort),
+                        ) {
+                            pages::sort_items(all, sort, search.sort.dir);
+                        }
+                        ctx.results.search = search;
+                        ctx.apply_local_filter();
+                        self.widgets.results.reset();
+                        continue;
+                    }
+                    LoadType::Reloading if ctx.results.all.is_some() => {
+                        LoadType::AllPages(ctx.config.all_pages)
+                    }
+                    load_type => load_type,
+                };
+
+                // Pages which failed in the background may load along with the new results
+                prefetch_failed = None;
+                if let Some(handle) = last_load_abort.as_ref() {
+                    handle.abort();
+                }
+
+                if submitted && ctx.config.search_history_size > 0 && !search.query.is_empty() {
+                    ctx.history.push(
+                        HistoryEntry::new(&search, ctx.src),
+                        ctx.config.search_history_size,
+                    );
+                    if let Err(e) = config_manager.store_history(&ctx.history) {
+                        ctx.notify_error(format!("Failed to save search history:\n{}", e));
+                    }
+                }
+
+                // Show cached results right away, only fetching again if they are stale
+                let cached = match load_type {
+                    LoadType::Reloading | LoadType::SolvingCaptcha(_) | LoadType::AllPages(_) => {
+                        None
+                    }
+                    _ => cache.get(ctx.src, &search),
+                };
+                let fresh = matches!(cached, Some(CacheHit::Fresh(_)));
+                refreshing = cached.is_some() && !fresh;
+                match cached {
+                    Some(CacheHit::Fresh(res) | CacheHit::Stale(res)) => {
+                        let table = ctx.src.format_table(
+                            &res.items,
+                            &search,
+                            &ctx.config.sources,
+                            &SourceExtraConfig::from(&ctx.config),
+                            &ctx.theme,
+                        );
+                        ctx.results = Results::new(search.clone(), res, table);
+                        ctx.apply_local_filter();
+                        ctx.load_type = None;
+                        self.widgets.results.reset();
+                    }
+                    None => ctx.load_type = Some(load_type.clone()),
+                }
+                ctx.load_progress = None;
+                if fresh {
+                    last_load_abort = None;
+                    continue;
+                }
+
                 let task = tokio::spawn(sync.clone().load_results(
                     tx_res.clone(),
                     load_type.clone(),
@@ -438,11 +800,49 @@ impl App {
                             break;
                         }
                     },
+                    () = &mut date_timer, if ctx.config.relative_date.unwrap_or(false) && ctx.load_type.is_none() => {
+                        date_timer.as_mut().reset(tokio::time::Instant::now() + Duration::from_millis(RELATIVE_DATE_REFRESH_MILLIS));
+                        ctx.format_results();
+                        break;
+                    },
                     Some(rt) = rx_res.recv() => {
                         match rt {
-                            Ok(SourceResults::Results(rt)) => {
-                                self.widgets.results.reset();
+                            Ok(SourceResults::Progress(progress)) => {
+                                // Still loading, only redraw the loading message
+                                ctx.load_progress = Some(progress);
+                                break;
+                            }
+                            Ok(SourceResults::Results(mut rt)) => {
+                                // Keep the cookies given for a solved captcha even if the app is killed
+                                if matches!(ctx.load_type, Some(LoadType::SolvingCaptcha(_))) {
+                                    ctx.should_save_cookies = true;
+                                }
+                                // Sources which failed while others loaded
+                                for e in rt.response.errors.drain(..) {
+                                    ctx.notify_error(e);
+                                }
+                                // Taken so pages from the cache don't warn again
+                                if let Some(report) = rt.response.report.take().filter(|r| r.is_broken()) {
+                                    ctx.notify_warn(report.warning(&ctx.src.to_string(), config_manager.debug_path()));
+                                }
+                                // Only single pages are cached
+                                if rt.all.is_none() {
+                                    if let Err(e) = cache.insert(ctx.src, &rt.search, &rt.response) {
+                                        ctx.notify_error(format!("Failed to cache results:\n{}", e));
+                                    }
+                                }
+                                if refreshing {
+                                    // Keep the selection when refreshing results from the cache
+                                    let len = rt.response.items.len();
+                                    if self.widgets.results.table.selected().is_some_and(|s| s >= len) {
+                                        self.widgets.results.table.select(len.saturating_sub(1));
+                                    }
+                                } else {
+                                    self.widgets.results.reset();
+                                }
                                 ctx.results = rt;
+                                ctx.captcha_url = None;
+                                ctx.apply_local_filter();
                             }
                             #[cfg(feature = "captcha")]
                             Ok(SourceResults::Captcha(c)) => {
@@ -452,13 +852,79 @@ impl App {
                                 self.widgets.captcha.input.clear();
                             }
                             Err(e) => {
-                                // Clear results on error
-                                ctx.results = Results::default();
-                                ctx.notify_error(e);
+                                // Clear results on error, unless they are from the cache
+                                if !refreshing {
+                                    ctx.results = Results::default();
+                                }
+                                ctx.notify_source_error(e);
                             },
                         }
                         ctx.load_type = None;
+                        ctx.load_progress = None;
                         last_load_abort = None;
+                        refreshing = false;
+                        break;
+                    },
+                    Some(rt) = rx_next.recv() => {
+                        let appending = ctx.load_type == Some(LoadType::Appending);
+                        match rt {
+                            Ok(SourceResults::Progress(progress)) => {
+                                if appending {
+                                    ctx.load_progress = Some(progress);
+                                }
+                            }
+                            Ok(SourceResults::Results(rt)) => {
+                                // Pages of an earlier prefetch may still arrive after it was replaced
+                                if !prefetch.as_ref().is_some_and(|(_, search, _)| {
+                                    search.page == rt.search.page && search.same_query(&rt.search)
+                                }) {
+                                    continue;
+                                }
+                                let Some((src, _, _)) = prefetch.take() else {
+                                    continue;
+                                };
+                                if let Err(e) = cache.insert(src, &rt.search, &rt.response) {
+                                    ctx.notify_error(format!("Failed to cache results:\n{}", e));
+                                }
+                                prefetched = Some((src, rt));
+                                if appending && self.append_next_page(ctx, &mut prefetched) {
+                                    ctx.load_type = None;
+                                    ctx.load_progress = None;
+                                }
+                            }
+                            #[cfg(feature = "captcha")]
+                            Ok(SourceResults::Captcha(_)) => {
+                                prefetch_failed = prefetch.take().map(|(src, search, _)| (src, search));
+                                if appending {
+                                    ctx.load_type = None;
+                                    ctx.notify_warn("A captcha must be solved, reload the page to solve it".to_owned());
+                                }
+                            }
+                            Err(e) => {
+                                prefetch_failed = prefetch.take().map(|(src, search, _)| (src, search));
+                                // Pages loaded in the background fail silently until they are shown
+                                if appending {
+                                    ctx.load_type = None;
+                                    ctx.load_progress = None;
+                                    ctx.notify_source_error(e);
+                                }
+                            }
+                        }
+                        break;
+                    },
+                    Some(((src, id), details)) = rx_details.recv() => {
+                        match details {
+                            Ok(details) => {
+                                self.widgets.details.cache.insert((src, id), details);
+                            }
+                            Err(e) => {
+                                // Close the popup if it is waiting on the failed item
+                                if ctx.mode == Mode::Details && self.widgets.details.is_showing(src, &id) {
+                                    ctx.mode = Mode::Normal;
+                                }
+                                ctx.notify_error(e);
+                            }
+                        }
                         break;
                     },
                     Some(dl) = rx_dl.recv() => {
@@ -469,7 +935,7 @@ impl App {
                                         ctx.notify(suc.msg);
                                     },
                                     SingleDownloadResult::Error(err) => {
-                                        ctx.notify(err.msg);
+                                        ctx.notify_download_error(err.error);
                                     },
                                 };
                             }
@@ -492,7 +958,10 @@ impl App {
                                 match config_manager.load() {
                                     Ok(config) => {
                                         match config.partial_apply(ctx, &mut self.widgets) {
-                                            Ok(()) => ctx.notify_info("Reloaded config".to_owned()),
+                                            Ok(()) => {
+                                                cache.configure(&ctx.config.sources);
+                                                ctx.notify_info("Reloaded config".to_owned())
+                                            }
                                             Err(e) => ctx.notify_error(e),
                                         }
                                     }
@@ -516,6 +985,10 @@ impl App {
                 last_time = None;
             }
         }
+        // Other cookies are only saved on exit, since they may change with every request
+        if let Some(path) = cookies_path.as_ref() {
+            jar.save(path)?;
+        }
         Ok(())
     }
 
@@ -546,6 +1019,34 @@ impl App {
         self.widgets.notification.draw(f, ctx, f.area());
     }
 
+    /// Append the prefetched page if it follows the loaded results, moving the cursor onto it.
+    /// Returns whether it was appended, a page for other results is dropped.
+    fn append_next_page(
+        &mut self,
+        ctx: &mut Context,
+        prefetched: &mut Option<(Sources, Results)>,
+    ) -> bool {
+        let Some((src, rt)) = prefetched.take() else {
+            return false;
+        };
+        if src != ctx.src
+            || rt.search.page != ctx.page + 1
+            || !rt.search.same_query(&ctx.results.search)
+        {
+            return false;
+        }
+        let len = ctx.results.response.items.len();
+        ctx.page += 1;
+        ctx.append_results(rt.response);
+        if self.widgets.results.table.selected().unwrap_or(0) + 1 >= len {
+            self.widgets
+                .results
+                .table
+                .next(ctx.results.response.items.len(), 1);
+        }
+        true
+    }
+
     fn on<B: Backend, const TEST: bool>(
         &mut self,
         evt: &Event,
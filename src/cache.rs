@@ -0,0 +1,189 @@
+use std::{
+    collections::HashMap,
+    error::Error,
+    fs,
+    path::PathBuf,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    results::ResultResponse,
+    source::{SourceConfig, Sources},
+    sync::SearchQuery,
+};
+
+/// Maximum number of pages kept in memory, the oldest are dropped first
+static MAX_ENTRIES: usize = 200;
+
+/// Cached pages on disk older than this are removed on startup
+static MAX_AGE: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
+#[derive(Serialize, Deserialize, Clone)]
+struct CacheEntry {
+    /// Key the page was stored under, checked on read in case two keys share a file
+    #[serde(default)]
+    key: String,
+    /// Seconds since the unix epoch when the page was fetched
+    time: u64,
+    response: ResultResponse,
+}
+
+/// A cached page. Only stale pages are fetched again in the background, fresh pages are shown
+/// as is until they reach the TTL or are reloaded.
+pub enum CacheHit {
+    /// Younger than the TTL, no need to fetch again
+    Fresh(ResultResponse),
+    /// Older than the TTL, should be shown while fetching again
+    Stale(ResultResponse),
+}
+
+pub struct ResultCache {
+    dir: Option<PathBuf>,
+    ttl: u64,
+    entries: HashMap<String, CacheEntry>,
+    /// Hash of the source config, since options like `rss` or `base_url` change what a page holds
+    config: u64,
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+/// 64-bit FNV-1a, which unlike `DefaultHasher` stays the same across Rust releases so files
+/// cached on disk can still be found after upgrading
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, b| {
+        (hash ^ *b as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+fn cache_key(src: Sources, config: u64, search: &SearchQuery) -> String {
+    format!(
+        "{}|{:016x}|{}|{}|{}|{}|{}|{}|{}",
+        src,
+        config,
+        search.query,
+        search.page,
+        search.category,
+        search.filter,
+        search.sort.sort,
+        search.sort.dir.to_url(),
+        search.user.as_deref().unwrap_or_default()
+    )
+}
+
+impl ResultCache {
+    /// Cache pages for `ttl` seconds, keeping them on disk in `dir` if given.
+    /// A `ttl` of 0 disables the cache.
+    pub fn new(dir: Option<PathBuf>, ttl: u64) -> Self {
+        if let Some(dir) = dir.as_ref().filter(|_| ttl > 0) {
+            prune(dir);
+        }
+        Self {
+            dir,
+            ttl,
+            entries: HashMap::new(),
+            config: 0,
+        }
+    }
+
+    /// Key pages by the source config they were loaded with, so changing it doesn't show pages
+    /// loaded with the old config
+    pub fn configure(&mut self, config: &SourceConfig) {
+        // Through a json value so the tables of other sources are hashed with sorted keys
+        let config = serde_json::to_value(config)
+            .map(|v| v.to_string())
+            .unwrap_or_default();
+        self.config = fnv1a(config.as_bytes());
+    }
+
+    fn file(&self, key: &str) -> Option<PathBuf> {
+        self.dir
+            .as_ref()
+            .map(|d| d.join(format!("{:016x}.json", fnv1a(key.as_bytes()))))
+    }
+
+    pub fn get(&mut self, src: Sources, search: &SearchQuery) -> Option<CacheHit> {
+        if self.ttl == 0 {
+            return None;
+        }
+        let key = cache_key(src, self.config, search);
+        if !self.entries.contains_key(&key) {
+            let entry = fs::read(self.file(&key)?)
+                .ok()
+                .and_then(|f| serde_json::from_slice::<CacheEntry>(&f).ok())
+                .filter(|e| e.key == key)?;
+            self.entries.insert(key.clone(), entry);
+        }
+        let entry = self.entries.get(&key)?;
+        let mut response = entry.response.clone();
+
+        // Icons are not stored, and merged results each have their own source
+        for item in response.items.iter_mut() {
+            item.icon = src.of_item(item).info().entry_from_id(item.category).icon;
+        }
+        Some(match now().saturating_sub(entry.time) < self.ttl {
+            true => CacheHit::Fresh(response),
+            false => CacheHit::Stale(response),
+        })
+    }
+
+    pub fn insert(
+        &mut self,
+        src: Sources,
+        search: &SearchQuery,
+        response: &ResultResponse,
+    ) -> Result<(), Box<dyn Error>> {
+        if self.ttl == 0 {
+            return Ok(());
+        }
+        if self.entries.len() >= MAX_ENTRIES {
+            if let Some(oldest) = self
+                .entries
+                .iter()
+                .min_by_key(|(_, e)| e.time)
+                .map(|(k, _)| k.to_owned())
+            {
+                self.entries.remove(&oldest);
+            }
+        }
+        let key = cache_key(src, self.config, search);
+        let entry = CacheEntry {
+            key: key.clone(),
+            time: now(),
+            response: response.clone(),
+        };
+        if let Some(file) = self.file(&key) {
+            if let Some(dir) = file.parent() {
+                fs::create_dir_all(dir)?;
+            }
+            fs::write(&file, serde_json::to_vec(&entry)?)
+                .map_err(|e| format!("{:?}\nUnable to write cache file:\n{}", file, e))?;
+        }
+        self.entries.insert(key, entry);
+        Ok(())
+    }
+}
+
+/// Remove cached pages which have not been written to in a long time
+fn prune(dir: &PathBuf) {
+    let Ok(files) = fs::read_dir(dir) else {
+        return;
+    };
+    for file in files.filter_map(Result::ok) {
+        let old = file
+            .metadata()
+            .and_then(|m| m.modified())
+            .ok()
+            .and_then(|m| m.elapsed().ok())
+            .is_some_and(|age| age > MAX_AGE);
+        if old {
+            let _ = fs::remove_file(file.path());
+        }
+    }
+}
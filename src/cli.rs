@@ -0,0 +1,191 @@
+use std::{error::Error, fmt::Display, path::PathBuf, str::FromStr, sync::Arc};
+
+use lexopt::{Parser, ValueExt as _};
+
+use crate::{
+    config::{Config, ConfigManager},
+    source::{
+        cookies::{self, CookieJar},
+        custom, Sources,
+    },
+};
+
+use self::{download::DownloadArgs, search::SearchArgs, watch::WatchArgs};
+
+pub mod download;
+pub mod search;
+pub mod watch;
+
+pub static USAGE: &str =
+    "Usage: nyaa [-v|-V|--version] [-c|--config=/path/to/config/folder] [COMMAND]
+
+Commands:
+  search <QUERY>       Search a source and print the results to stdout
+  download <TARGET>... Send torrents to the configured download client
+  watch                Poll saved queries and download new matches";
+
+pub enum Command {
+    Search(SearchArgs),
+    Download(DownloadArgs),
+    Watch(WatchArgs),
+    /// Usage of a command, asked for with `-h/--help`
+    Help(&'static str),
+}
+
+/// Returned while parsing the arguments of a command which was asked for its usage
+#[derive(Debug)]
+pub struct Help(pub &'static str);
+
+impl Display for Help {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl Error for Help {}
+
+impl Command {
+    pub fn parse(
+        name: &str,
+        parser: &mut Parser,
+        config_path: &mut Option<String>,
+    ) -> Result<Self, Box<dyn Error>> {
+        let command = match name {
+            "search" => SearchArgs::parse(parser, config_path).map(Command::Search),
+            "download" => DownloadArgs::parse(parser, config_path).map(Command::Download),
+            "watch" => WatchArgs::parse(parser, config_path).map(Command::Watch),
+            _ => Err(format!("Unknown command \"{}\"\n\n{}", name, USAGE).into()),
+        };
+        command.or_else(|e| match e.downcast::<Help>() {
+            Ok(help) => Ok(Command::Help(help.0)),
+            Err(e) => Err(e),
+        })
+    }
+
+    pub async fn run(self, config: impl ConfigManager) -> Result<(), Box<dyn Error>> {
+        match self {
+            Command::Search(args) => search::run(args, config).await,
+            Command::Download(args) => download::run(args, config).await,
+            Command::Watch(args) => watch::run(args, config).await,
+            Command::Help(usage) => {
+                println!("{}", usage);
+                Ok(())
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy, Default)]
+pub enum OutputFormat {
+    #[default]
+    Json,
+    Tsv,
+    Csv,
+}
+
+impl FromStr for OutputFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "json" => Ok(OutputFormat::Json),
+            "tsv" => Ok(OutputFormat::Tsv),
+            "csv" => Ok(OutputFormat::Csv),
+            _ => Err(format!(
+                "Unknown format \"{}\", expected one of: json, tsv, csv",
+                s
+            )),
+        }
+    }
+}
+
+/// An owned command line argument, so the parser can be borrowed again
+/// while matching on it.
+pub enum Opt {
+    Flag(String),
+    Value(String),
+}
+
+pub fn next_opt(parser: &mut Parser) -> Result<Option<Opt>, Box<dyn Error>> {
+    use lexopt::prelude::*;
+
+    Ok(match parser.next()? {
+        Some(Short(c)) => Some(Opt::Flag(format!("-{}", c))),
+        Some(Long(l)) => Some(Opt::Flag(format!("--{}", l))),
+        Some(Value(v)) => Some(Opt::Value(v.string()?)),
+        None => None,
+    })
+}
+
+/// Handle `-c/--config` given after a command, returning false if `opt` is not
+/// the config option.
+pub fn parse_config_opt(
+    opt: &str,
+    parser: &mut Parser,
+    config_path: &mut Option<String>,
+) -> Result<bool, Box<dyn Error>> {
+    if opt != "-c" && opt != "--config" {
+        return Ok(false);
+    }
+    *config_path = Some(shellexpand::full(&parser.value()?.string()?)?.to_string());
+    Ok(true)
+}
+
+/// Lowercase and strip separators so "Trusted Only", "trusted-only" and
+/// "trustedonly" all compare equal.
+pub fn normalize(s: &str) -> String {
+    s.chars()
+        .filter(|c| !matches!(c, ' ' | '-' | '_'))
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+pub fn parse_source(s: &str) -> Result<Sources, String> {
+    match normalize(s).as_str() {
+        "nyaa" => Ok(Sources::Nyaa),
+        "sukebei" | "sukebeinyaa" => Ok(Sources::SukebeiNyaa),
+        "torrentgalaxy" | "tgx" => Ok(Sources::TorrentGalaxy),
+        "torznab" | "jackett" => Ok(Sources::Torznab),
+        "all" | "allsources" => Ok(Sources::All),
+        // Checked against the registered sources once the config is loaded
+        _ => Ok(Sources::new(s.trim())),
+    }
+}
+
+/// Find a source given on the command line among the registered sources, which include the
+/// sources declared in the config once it is loaded
+pub fn registered_source(src: Sources, config: &Config) -> Result<Sources, String> {
+    custom::register(&config.sources);
+    let all = Sources::all();
+    all.iter()
+        .find(|s| normalize(s.id()) == normalize(src.id()))
+        .copied()
+        .ok_or_else(|| {
+            let names: Vec<&str> = all.iter().map(|s| s.id()).collect();
+            format!(
+                "Unknown source \"{}\", expected one of: {}",
+                src.id(),
+                names.join(", ")
+            )
+        })
+}
+
+/// Cookies saved by the TUI along with the configured `cookies.txt` files, printing files
+/// which can't be read. Returns where to save the jar once done.
+pub fn open_cookies(
+    config: &impl ConfigManager,
+    cfg: &Config,
+) -> (Arc<CookieJar>, Option<PathBuf>) {
+    let path = config.cookies_path();
+    let (jar, errors) = cookies::open(path.as_ref(), &cfg.sources);
+    for e in errors {
+        eprintln!("nyaa: {}", e);
+    }
+    (jar, path)
+}
+
+pub fn save_cookies(jar: &CookieJar, path: Option<&PathBuf>) {
+    if let Some(Err(e)) = path.map(|p| jar.save(p)) {
+        eprintln!("nyaa: Failed to save cookies:\n{}", e);
+    }
+}
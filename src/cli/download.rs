@@ -0,0 +1,263 @@
+use std::{error::Error, sync::Arc};
+
+use lexopt::{Parser, ValueExt as _};
+use reqwest::Url;
+
+use crate::{
+    client::{Client, SingleDownloadResult},
+    config::{Config, ConfigManager},
+    source::{
+        cookies::CookieJar, mirror::Mirrors, nyaa_html::view_item, request, request_client,
+        InfoHash, Item, SourceConfig, Sources,
+    },
+};
+
+use super::{
+    next_opt, normalize, open_cookies, parse_config_opt, save_cookies,
+    search::{self, SearchArgs},
+    Help, Opt,
+};
+
+pub static DOWNLOAD_USAGE: &str = "Usage: nyaa download [OPTIONS] [TARGET]...
+
+Targets can be a magnet link, a torrent URL, a nyaa/sukebei view URL or a
+view id (e.g. 1234567, nyaa-1234567 or sukebei-1234567).
+
+Options:
+  -d, --client <CLIENT>      Download client to use (default: download_client)
+  -n, --from-search <N>      Download the top N results of a search. Accepts the
+                             same options as `nyaa search`, with the remaining
+                             arguments used as the query
+  -c, --config <PATH>        Override the path to the config folder";
+
+#[derive(Clone, Default)]
+pub struct DownloadArgs {
+    pub targets: Vec<String>,
+    pub client: Option<Client>,
+    pub from_search: Option<usize>,
+    pub search: SearchArgs,
+}
+
+impl DownloadArgs {
+    pub fn parse(
+        parser: &mut Parser,
+        config_path: &mut Option<String>,
+    ) -> Result<Self, Box<dyn Error>> {
+        let mut args = DownloadArgs::default();
+        while let Some(opt) = next_opt(parser)? {
+            match opt {
+                Opt::Value(v) => args.targets.push(v),
+                Opt::Flag(f) => match f.as_str() {
+                    "-h" | "--help" => return Err(Help(DOWNLOAD_USAGE).into()),
+                    "-d" | "--client" => {
+                        args.client = Some(parse_client(&parser.value()?.string()?)?);
+                    }
+                    "-n" | "--from-search" => args.from_search = Some(parser.value()?.parse()?),
+                    f => {
+                        if !parse_config_opt(f, parser, config_path)?
+                            && !args.search.parse_opt(f, parser)?
+                        {
+                            return Err(format!("invalid option '{}'", f).into());
+                        }
+                    }
+                },
+            }
+        }
+        if args.from_search.is_some() {
+            // Positional arguments are the search query instead of targets
+            if !args.targets.is_empty() {
+                args.search.query = Some(args.targets.join(" "));
+                args.targets.clear();
+            }
+        } else if args.targets.is_empty() {
+            return Err(format!("Nothing to download\n\n{}", DOWNLOAD_USAGE).into());
+        }
+        Ok(args)
+    }
+}
+
+pub fn parse_client(s: &str) -> Result<Client, String> {
+    Client::all()
+        .into_iter()
+        .find(|c| normalize(&c.to_string()) == normalize(s) || normalize(c.id()) == normalize(s))
+        .ok_or(format!(
+            "Unknown client \"{}\", expected one of: {}",
+            s,
+            Client::all()
+                .iter()
+                .map(|c| normalize(&c.to_string()))
+                .collect::<Vec<String>>()
+                .join(", ")
+        ))
+}
+
+/// Parse a view id such as `1234567`, `nyaa-1234567` or `sukebei-1234567`
+fn parse_view_id(target: &str, default: Sources) -> Option<(Sources, usize)> {
+    let (src, id) = match target.split_once('-') {
+        Some(("nyaa", id)) => (Sources::Nyaa, id),
+        Some(("sukebei", id)) => (Sources::SukebeiNyaa, id),
+        Some(_) => return None,
+        None => (default, target),
+    };
+    Some((src, id.parse().ok()?))
+}
+
+fn magnet_item(magnet: &str) -> Item {
+    let hash = InfoHash::from_magnet(magnet);
+    let hex = hash.map(|h| h.to_string()).unwrap_or_default();
+    let title = magnet
+        .split(['?', '&'])
+        .find_map(|p| p.strip_prefix("dn="))
+        .and_then(|dn| {
+            urlencoding::decode(&dn.replace('+', " "))
+                .map(|dn| dn.into_owned())
+                .ok()
+        })
+        .unwrap_or(hex.clone());
+    Item {
+        id: hex.clone(),
+        title,
+        magnet_link: magnet.to_owned(),
+        hash,
+        file_name: format!("{}.torrent", hex),
+        ..Default::default()
+    }
+}
+
+async fn resolve_target(
+    target: &str,
+    client: &reqwest::Client,
+    config: &mut SourceConfig,
+    default: Sources,
+) -> Result<Item, Box<dyn Error + Send + Sync>> {
+    if target.starts_with("magnet:") {
+        return Ok(magnet_item(target));
+    }
+
+    // View id for nyaa/sukebei, using the configured base url
+    let default = match default {
+        Sources::SukebeiNyaa => Sources::SukebeiNyaa,
+        _ => Sources::Nyaa,
+    };
+    if let Some((src, id)) = parse_view_id(target, default) {
+        src.load_config(config);
+        return match src {
+            Sources::SukebeiNyaa => {
+                let sukebei = config.sukebei.to_owned().unwrap_or_default();
+                view_item(client, &sukebei.mirrors(), sukebei.timeout, "sukebei", id).await
+            }
+            _ => {
+                let nyaa = config.nyaa.to_owned().unwrap_or_default();
+                view_item(client, &nyaa.mirrors(), nyaa.timeout, "nyaa", id).await
+            }
+        };
+    }
+
+    let url =
+        Url::parse(target).map_err(|e| format!("{}\nInvalid download target: {}", target, e))?;
+    if !matches!(url.scheme(), "http" | "https") {
+        return Err(format!("{}\nInvalid download target", target).into());
+    }
+
+    // View url for nyaa/sukebei, using the host given in the url
+    let segments = url
+        .path_segments()
+        .map(|s| s.collect::<Vec<&str>>())
+        .unwrap_or_default();
+    if let ["view", id] = segments.as_slice() {
+        if let Ok(id) = id.parse::<usize>() {
+            let host = url.host_str().unwrap_or_default();
+            let prefix = match host.contains("sukebei") {
+                true => "sukebei",
+                false => "nyaa",
+            };
+            let base_url = format!("{}://{}/", url.scheme(), host);
+            let mirrors = Mirrors::new("url", &base_url, &[]);
+            return view_item(client, &mirrors, None, prefix, id).await;
+        }
+    }
+
+    // Anything else is treated as a link to a torrent file
+    let file_name = url
+        .path_segments()
+        .and_then(|mut s| s.next_back())
+        .filter(|s| !s.is_empty())
+        .unwrap_or("download.torrent")
+        .to_owned();
+    Ok(Item {
+        id: target.to_owned(),
+        title: file_name.clone(),
+        torrent_link: target.to_owned(),
+        file_name,
+        ..Default::default()
+    })
+}
+
+async fn collect_items(
+    args: &DownloadArgs,
+    config: &mut Config,
+    jar: &Arc<CookieJar>,
+) -> Result<Vec<Item>, Box<dyn Error>> {
+    let mut items = vec![];
+    if let Some(n) = args.from_search {
+        let res = search::fetch(&args.search, config, jar).await?;
+        items.extend(res.items.into_iter().take(n));
+        if items.is_empty() {
+            return Err("Search returned no results".into());
+        }
+    }
+
+    let client = request_client(jar, config.timeout, config.request_proxy.clone())?;
+    let default = args.search.source.unwrap_or(config.source);
+    for target in args.targets.iter() {
+        let item = resolve_target(target, &client, &mut config.sources, default)
+            .await
+            .map_err(|e| e as Box<dyn Error>)?;
+        items.push(item);
+    }
+    Ok(items)
+}
+
+pub async fn run(args: DownloadArgs, config: impl ConfigManager) -> Result<(), Box<dyn Error>> {
+    let mut cfg = config.load()?;
+    request::configure(cfg.retry);
+    let (jar, cookies_path) = open_cookies(&config, &cfg);
+    let items = collect_items(&args, &mut cfg, &jar).await;
+    save_cookies(&jar, cookies_path.as_ref());
+    let items = items?;
+
+    let client = args.client.unwrap_or(cfg.download_client);
+    client.load_config(&mut cfg.client);
+
+    // Download clients do not use the request proxy
+    let rq_client = request_client(&jar, cfg.timeout, None)?;
+
+    if items.len() == 1 {
+        return match client
+            .download(items[0].clone(), cfg.client, rq_client)
+            .await
+        {
+            SingleDownloadResult::Success(s) => {
+                println!("{}\t{}", s.id, s.msg.content);
+                Ok(())
+            }
+            SingleDownloadResult::Error(e) => Err(e.msg.content.into()),
+        };
+    }
+
+    let total = items.len();
+    let res = client.batch_download(items, cfg.client, rq_client).await;
+    for id in res.ids.iter() {
+        println!("{}", id);
+    }
+    for err in res.errors.iter() {
+        eprintln!("nyaa: {}", err.content);
+    }
+    match res.errors.is_empty() {
+        true => {
+            println!("{}", res.msg.content);
+            Ok(())
+        }
+        false => Err(format!("{} of {} downloads failed", res.errors.len(), total).into()),
+    }
+}
@@ -0,0 +1,321 @@
+use std::{collections::HashMap, error::Error, io::Write as _, sync::Arc};
+
+use chrono::{DateTime, Utc};
+use lexopt::{Parser, ValueExt as _};
+use serde::Serialize;
+
+use crate::{
+    app::LoadType,
+    config::{Config, ConfigManager},
+    results::ResultResponse,
+    source::{
+        command, cookies::CookieJar, request, request_client, InfoHash, Item, SourceExtraConfig,
+        SourceResponse, Sources,
+    },
+    sync::SearchQuery,
+    util::conv::to_size,
+    widget::sort::{SelectedSort, SortDir},
+};
+
+use super::{
+    next_opt, normalize, open_cookies, parse_config_opt, parse_source, registered_source,
+    save_cookies, Help, Opt, OutputFormat,
+};
+
+pub static SEARCH_USAGE: &str = "Usage: nyaa search [OPTIONS] <QUERY>
+
+Options:
+  -s, --source <SOURCE>      nyaa, sukebei, torrentgalaxy, torznab, all, or the name of
+                             a custom or command source (default: default_source)
+      --category <CATEGORY>  Category config name, e.g. AnimeEnglishTranslated
+      --sort <SORT>          Sort name, e.g. date, seeders, size
+      --sort-dir <DIR>       asc or desc
+      --filter <FILTER>      Filter name, e.g. NoFilter, TrustedOnly
+  -p, --page <PAGE>          Page number (default: 1)
+  -u, --user <USER>          Only show results from this user
+  -f, --format <FORMAT>      json, tsv or csv (default: json)
+  -c, --config <PATH>        Override the path to the config folder";
+
+#[derive(Clone, Default)]
+pub struct SearchArgs {
+    pub query: Option<String>,
+    pub source: Option<Sources>,
+    pub category: Option<String>,
+    pub sort: Option<String>,
+    pub sort_dir: Option<SortDir>,
+    pub filter: Option<String>,
+    pub page: Option<usize>,
+    pub user: Option<String>,
+    pub format: OutputFormat,
+}
+
+impl SearchArgs {
+    pub fn parse(
+        parser: &mut Parser,
+        config_path: &mut Option<String>,
+    ) -> Result<Self, Box<dyn Error>> {
+        let mut args = SearchArgs::default();
+        let mut words: Vec<String> = vec![];
+        while let Some(opt) = next_opt(parser)? {
+            match opt {
+                Opt::Value(v) => words.push(v),
+                Opt::Flag(f) if f == "-h" || f == "--help" => return Err(Help(SEARCH_USAGE).into()),
+                Opt::Flag(f) => {
+                    if !parse_config_opt(&f, parser, config_path)? && !args.parse_opt(&f, parser)? {
+                        return Err(format!("invalid option '{}'", f).into());
+                    }
+                }
+            }
+        }
+        if !words.is_empty() {
+            args.query = Some(words.join(" "));
+        }
+        Ok(args)
+    }
+
+    /// Parse a single search option, returning false if `opt` is not one.
+    /// Shared with other commands which run a search first.
+    pub fn parse_opt(&mut self, opt: &str, parser: &mut Parser) -> Result<bool, Box<dyn Error>> {
+        match opt {
+            "-s" | "--source" => self.source = Some(parse_source(&parser.value()?.string()?)?),
+            "--category" => self.category = Some(parser.value()?.string()?),
+            "--sort" => self.sort = Some(parser.value()?.string()?),
+            "--sort-dir" => {
+                self.sort_dir = Some(match normalize(&parser.value()?.string()?).as_str() {
+                    "asc" | "ascending" => SortDir::Asc,
+                    "desc" | "descending" => SortDir::Desc,
+                    s => return Err(format!("Unknown sort direction \"{}\"", s).into()),
+                });
+            }
+            "--filter" => self.filter = Some(parser.value()?.string()?),
+            "-p" | "--page" => self.page = Some(parser.value()?.parse()?),
+            "-u" | "--user" => self.user = Some(parser.value()?.string()?),
+            "-f" | "--format" => self.format = parser.value()?.string()?.parse()?,
+            _ => return Ok(false),
+        }
+        Ok(true)
+    }
+
+    /// Build a `SearchQuery` for `src`, falling back to the source defaults
+    /// from the config for anything not given on the command line.
+    pub fn to_query(&self, src: Sources, config: &Config) -> Result<SearchQuery, String> {
+        let info = src.info();
+        let sources = &config.sources;
+
+        let category = match &self.category {
+            Some(cat) => info
+                .cats
+                .iter()
+                .flat_map(|c| c.entries.iter())
+                .find(|e| normalize(&e.cfg) == normalize(cat))
+                .map(|e| e.id)
+                .ok_or(format!("Unknown category \"{}\" for {}", cat, src))?,
+            None => src.default_category(sources),
+        };
+        let filter = match &self.filter {
+            Some(filter) => find_name(&info.filters, filter)
+                .ok_or(format!("Unknown filter \"{}\" for {}", filter, src))?,
+            None => src.default_filter(sources),
+        };
+        let mut sort = src.default_sort(sources);
+        if let Some(name) = &self.sort {
+            sort = SelectedSort {
+                sort: find_name(&info.sorts, name)
+                    .ok_or(format!("Unknown sort \"{}\" for {}", name, src))?,
+                dir: sort.dir,
+            };
+        }
+        if let Some(dir) = self.sort_dir {
+            sort.dir = dir;
+        }
+
+        Ok(SearchQuery {
+            query: self
+                .query
+                .clone()
+                .unwrap_or_else(|| src.default_search(sources)),
+            page: self.page.unwrap_or(1).max(1),
+            category,
+            filter,
+            sort,
+            user: self.user.clone(),
+        })
+    }
+}
+
+fn find_name(names: &[String], name: &str) -> Option<usize> {
+    names.iter().position(|n| normalize(n) == normalize(name))
+}
+
+/// Run a single search against the configured source, without the TUI.
+pub async fn fetch(
+    args: &SearchArgs,
+    config: &mut Config,
+    jar: &Arc<CookieJar>,
+) -> Result<ResultResponse, Box<dyn Error>> {
+    let src = match args.source {
+        Some(src) => registered_source(src, config)?,
+        None => config.source,
+    };
+    src.load_config(&mut config.sources);
+    // Categories and sorts of command sources are only known once they are described
+    let name = src.id();
+    tokio::task::spawn_blocking(move || command::wait_described(name)).await?;
+    let search = args.to_query(src, config)?;
+
+    let client = request_client(jar, config.timeout, config.request_proxy.clone())?;
+    let extra = SourceExtraConfig::from(config.clone());
+
+    // Sorting is a superset of searching, it additionally sorts RSS results locally
+    let res = src
+        .load(LoadType::Sorting, &client, &search, &config.sources, &extra)
+        .await
+        .map_err(|e| Box::new(e) as Box<dyn Error>)?;
+    match res {
+        SourceResponse::Results(res) => Ok(res),
+        #[cfg(feature = "captcha")]
+        SourceResponse::Captcha(_) => Err(format!(
+            "{} requires a captcha to be solved, run nyaa without a command to solve it",
+            src
+        )
+        .into()),
+    }
+}
+
+pub async fn run(args: SearchArgs, config: impl ConfigManager) -> Result<(), Box<dyn Error>> {
+    let mut cfg = config.load()?;
+    request::configure(cfg.retry);
+    let (jar, cookies_path) = open_cookies(&config, &cfg);
+    let res = fetch(&args, &mut cfg, &jar).await?;
+    save_cookies(&jar, cookies_path.as_ref());
+    if let Some(report) = res.report.as_ref().filter(|r| r.is_broken()) {
+        let src = args.source.unwrap_or(cfg.source);
+        eprintln!(
+            "nyaa: {}",
+            report.warning(&src.to_string(), config.debug_path())
+        );
+    }
+    print_items(&res.items, args.format)
+}
+
+#[derive(Serialize)]
+struct ItemOutput<'a> {
+    id: &'a str,
+    title: &'a str,
+    date: Option<DateTime<Utc>>,
+    size: String,
+    bytes: u64,
+    seeders: u32,
+    leechers: u32,
+    downloads: u32,
+    category: usize,
+    hash: Option<InfoHash>,
+    magnet_link: &'a str,
+    torrent_link: &'a str,
+    post_link: &'a str,
+    file_name: &'a str,
+    extra: &'a HashMap<String, String>,
+}
+
+impl<'a> From<&'a Item> for ItemOutput<'a> {
+    fn from(item: &'a Item) -> Self {
+        Self {
+            id: &item.id,
+            title: &item.title,
+            date: Some(item.date).filter(|d| *d != DateTime::<Utc>::default()),
+            size: to_size(item.size),
+            bytes: item.size,
+            seeders: item.seeders,
+            leechers: item.leechers,
+            downloads: item.downloads,
+            category: item.category,
+            hash: item.hash,
+            magnet_link: &item.magnet_link,
+            torrent_link: &item.torrent_link,
+            post_link: &item.post_link,
+            file_name: &item.file_name,
+            extra: &item.extra,
+        }
+    }
+}
+
+static COLUMNS: [&str; 13] = [
+    "id",
+    "title",
+    "date",
+    "size",
+    "bytes",
+    "seeders",
+    "leechers",
+    "downloads",
+    "hash",
+    "magnet_link",
+    "torrent_link",
+    "post_link",
+    "extra",
+];
+
+fn row(item: &Item) -> [String; 13] {
+    let mut extra = item
+        .extra
+        .iter()
+        .map(|(k, v)| format!("{}={}", k, v))
+        .collect::<Vec<String>>();
+    extra.sort();
+    [
+        item.id.clone(),
+        item.title.clone(),
+        ItemOutput::from(item)
+            .date
+            .map(|d| d.to_rfc3339())
+            .unwrap_or_default(),
+        to_size(item.size),
+        item.size.to_string(),
+        item.seeders.to_string(),
+        item.leechers.to_string(),
+        item.downloads.to_string(),
+        item.hash.map(|h| h.to_string()).unwrap_or_default(),
+        item.magnet_link.clone(),
+        item.torrent_link.clone(),
+        item.post_link.clone(),
+        extra.join(";"),
+    ]
+}
+
+fn escape_csv(s: &str) -> String {
+    if s.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_owned()
+    }
+}
+
+fn escape_tsv(s: &str) -> String {
+    s.replace(['\t', '\n', '\r'], " ")
+}
+
+pub fn format_items(items: &[Item], format: OutputFormat) -> Result<String, Box<dyn Error>> {
+    let (sep, escape): (&str, fn(&str) -> String) = match format {
+        OutputFormat::Json => {
+            let out = items.iter().map(ItemOutput::from).collect::<Vec<_>>();
+            return Ok(serde_json::to_string_pretty(&out)?);
+        }
+        OutputFormat::Tsv => ("\t", escape_tsv),
+        OutputFormat::Csv => (",", escape_csv),
+    };
+    let mut lines = vec![COLUMNS.join(sep)];
+    lines.extend(items.iter().map(|item| {
+        row(item)
+            .iter()
+            .map(|c| escape(c))
+            .collect::<Vec<String>>()
+            .join(sep)
+    }));
+    Ok(lines.join("\n"))
+}
+
+pub fn print_items(items: &[Item], format: OutputFormat) -> Result<(), Box<dyn Error>> {
+    let mut stdout = std::io::stdout().lock();
+    writeln!(stdout, "{}", format_items(items, format)?)?;
+    Ok(())
+}
@@ -0,0 +1,341 @@
+use std::{error::Error, path::PathBuf, sync::Arc, time::Duration};
+
+use indexmap::IndexSet;
+use lexopt::{Parser, ValueExt as _};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    client::{Client, SingleDownloadResult},
+    config::{self, Config, ConfigManager},
+    source::{cookies::CookieJar, request, request_client, Item, ItemType, Sources},
+    widget::sort::SortDir,
+};
+
+use super::{
+    download::parse_client,
+    next_opt, open_cookies, parse_config_opt, save_cookies,
+    search::{self, SearchArgs},
+    Help, Opt,
+};
+
+pub static WATCH_FILE: &str = "watch.toml";
+pub static WATCH_SEEN_FILE: &str = "watch_seen.toml";
+
+/// Maximum number of ids kept in the seen file, oldest are dropped first
+static MAX_SEEN: usize = 10000;
+
+pub static WATCH_USAGE: &str = "Usage: nyaa watch [OPTIONS]
+
+Poll the queries in watch.toml and send new matches to the download client.
+
+Options:
+  -i, --interval <MINUTES>   Minutes between polls (default: interval in watch.toml)
+      --once                 Poll once and exit, for use with cron
+      --dry-run              Print matches without downloading or marking them seen
+  -d, --client <CLIENT>      Download client to use (default: download_client)
+  -c, --config <PATH>        Override the path to the config folder";
+
+#[derive(Clone, Default)]
+pub struct WatchArgs {
+    pub interval: Option<u64>,
+    pub once: bool,
+    pub dry_run: bool,
+    pub client: Option<Client>,
+}
+
+impl WatchArgs {
+    pub fn parse(
+        parser: &mut Parser,
+        config_path: &mut Option<String>,
+    ) -> Result<Self, Box<dyn Error>> {
+        let mut args = WatchArgs::default();
+        while let Some(opt) = next_opt(parser)? {
+            match opt {
+                Opt::Value(v) => return Err(format!("unexpected argument '{}'", v).into()),
+                Opt::Flag(f) => match f.as_str() {
+                    "-h" | "--help" => return Err(Help(WATCH_USAGE).into()),
+                    "-i" | "--interval" => args.interval = Some(parser.value()?.parse()?),
+                    "--once" => args.once = true,
+                    "--dry-run" => args.dry_run = true,
+                    "-d" | "--client" => {
+                        args.client = Some(parse_client(&parser.value()?.string()?)?);
+                    }
+                    f => {
+                        if !parse_config_opt(f, parser, config_path)? {
+                            return Err(format!("invalid option '{}'", f).into());
+                        }
+                    }
+                },
+            }
+        }
+        Ok(args)
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct WatchConfig {
+    /// Minutes between polls
+    pub interval: u64,
+    #[serde(rename = "query", skip_serializing_if = "Vec::is_empty")]
+    pub queries: Vec<WatchQuery>,
+}
+
+impl Default for WatchConfig {
+    fn default() -> Self {
+        Self {
+            interval: 15,
+            queries: vec![],
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct WatchQuery {
+    pub name: String,
+    pub source: Option<Sources>,
+    pub query: String,
+    pub category: Option<String>,
+    pub filter: Option<String>,
+    pub user: Option<String>,
+
+    /// Regex the title must match
+    pub title: Option<String>,
+    /// Uploader the item must be from, searched for by Nyaa and Sukebei and checked against the
+    /// uploader listed by TorrentGalaxy. Other sources can't check it.
+    pub uploader: Option<String>,
+    pub min_seeders: Option<u32>,
+    pub trusted_only: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct WatchSeen {
+    pub seen: Vec<String>,
+}
+
+pub struct WatchRule {
+    title: Option<Regex>,
+    uploader: Option<String>,
+    min_seeders: Option<u32>,
+    trusted_only: bool,
+}
+
+impl WatchRule {
+    /// Rule for the items of `query` searched on `src`
+    pub fn new(query: &WatchQuery, src: Sources) -> Result<Self, String> {
+        let title = match &query.title {
+            Some(t) => Some(
+                Regex::new(t)
+                    .map_err(|e| format!("{}\nInvalid title regex in \"{}\"", e, query.name))?,
+            ),
+            None => None,
+        };
+        // Nyaa and Sukebei only return items of the uploader, TorrentGalaxy lists it per item
+        let uploader = match &query.uploader {
+            Some(_) if src == Sources::Nyaa || src == Sources::SukebeiNyaa => None,
+            Some(u) if src == Sources::TorrentGalaxy => Some(u.to_owned()),
+            Some(_) => {
+                return Err(format!(
+                    "{} does not list uploaders, remove \"uploader\" from \"{}\"",
+                    src, query.name
+                ))
+            }
+            None => None,
+        };
+        Ok(Self {
+            title,
+            uploader,
+            min_seeders: query.min_seeders,
+            trusted_only: query.trusted_only,
+        })
+    }
+
+    pub fn matches(&self, item: &Item) -> bool {
+        if let Some(title) = &self.title {
+            if !title.is_match(&item.title) {
+                return false;
+            }
+        }
+        if let Some(uploader) = &self.uploader {
+            if !item
+                .extra
+                .get("uploader")
+                .is_some_and(|u| uploader.eq_ignore_ascii_case(u))
+            {
+                return false;
+            }
+        }
+        if self.min_seeders.is_some_and(|min| item.seeders < min) {
+            return false;
+        }
+        if self.trusted_only && !matches!(item.item_type, ItemType::Trusted) {
+            return false;
+        }
+        true
+    }
+}
+
+impl WatchQuery {
+    fn search_args(&self) -> SearchArgs {
+        SearchArgs {
+            query: Some(self.query.to_owned()),
+            source: self.source,
+            category: self.category.to_owned(),
+            filter: self.filter.to_owned(),
+            // Nyaa filters by uploader on the server
+            user: self.user.to_owned().or(self.uploader.to_owned()),
+            sort: Some("Date".to_owned()),
+            sort_dir: Some(SortDir::Desc),
+            page: Some(1),
+            ..Default::default()
+        }
+    }
+}
+
+struct Watcher {
+    args: WatchArgs,
+    config: Config,
+    queries: Vec<(WatchQuery, WatchRule)>,
+    seen_path: PathBuf,
+    /// Ids of the items already downloaded, oldest first
+    seen: IndexSet<String>,
+    jar: Arc<CookieJar>,
+    cookies_path: Option<PathBuf>,
+}
+
+impl Watcher {
+    /// Poll every query once, returning the number of errors
+    async fn poll(&mut self) -> Result<usize, Box<dyn Error>> {
+        let mut errors = 0;
+        for (query, rule) in self.queries.iter() {
+            let res = match search::fetch(&query.search_args(), &mut self.config, &self.jar).await {
+                Ok(res) => res,
+                Err(e) => {
+                    eprintln!("nyaa: {}: {}", query.name, e);
+                    errors += 1;
+                    continue;
+                }
+            };
+            let matches = res
+                .items
+                .into_iter()
+                .filter(|i| !self.seen.contains(&i.id) && rule.matches(i))
+                .collect::<Vec<Item>>();
+            if matches.is_empty() {
+                continue;
+            }
+            if self.args.dry_run {
+                for item in matches.iter() {
+                    println!("{}\t{}\t{}", query.name, item.id, item.title);
+                }
+                continue;
+            }
+
+            let ids = self.download(query, &matches, &mut errors).await;
+            for item in matches.iter().filter(|i| ids.contains(&i.id)) {
+                println!("{}\t{}\t{}", query.name, item.id, item.title);
+                self.seen.insert(item.id.to_owned());
+            }
+        }
+        save_cookies(&self.jar, self.cookies_path.as_ref());
+        if !self.args.dry_run {
+            let len = self.seen.len();
+            if len > MAX_SEEN {
+                self.seen.drain(..len - MAX_SEEN);
+            }
+            let seen = WatchSeen {
+                seen: self.seen.iter().cloned().collect(),
+            };
+            config::store_path(&self.seen_path, &seen)?;
+        }
+        Ok(errors)
+    }
+
+    async fn download(
+        &self,
+        query: &WatchQuery,
+        items: &[Item],
+        errors: &mut usize,
+    ) -> Vec<String> {
+        let client = self.args.client.unwrap_or(self.config.download_client);
+        let mut conf = self.config.client.clone();
+        client.load_config(&mut conf);
+
+        // Download clients do not use the request proxy
+        let rq_client = match request_client(&self.jar, self.config.timeout, None) {
+            Ok(c) => c,
+            Err(e) => {
+                eprintln!("nyaa: {}: {}", query.name, e);
+                *errors += 1;
+                return vec![];
+            }
+        };
+
+        if items.len() == 1 {
+            return match client.download(items[0].clone(), conf, rq_client).await {
+                SingleDownloadResult::Success(s) => vec![s.id],
+                SingleDownloadResult::Error(e) => {
+                    eprintln!("nyaa: {}: {}", query.name, e.msg.content);
+                    *errors += 1;
+                    vec![]
+                }
+            };
+        }
+        let res = client.batch_download(items.to_vec(), conf, rq_client).await;
+        for err in res.errors.iter() {
+            eprintln!("nyaa: {}: {}", query.name, err.content);
+        }
+        *errors += res.errors.len();
+        res.ids
+    }
+}
+
+pub async fn run(args: WatchArgs, config: impl ConfigManager) -> Result<(), Box<dyn Error>> {
+    let mut cfg = config.load()?;
+    request::configure(cfg.retry);
+    // The RSS feed lists the newest uploads along with trusted/remake status
+    cfg.sources.nyaa.get_or_insert_with(Default::default).rss = true;
+    cfg.sources.sukebei.get_or_insert_with(Default::default).rss = true;
+    let watch: WatchConfig = config::load_path(config.path().join(WATCH_FILE))?;
+    if watch.queries.is_empty() {
+        return Err(format!(
+            "No queries to watch, add them to {:?}",
+            config.path().join(WATCH_FILE)
+        )
+        .into());
+    }
+    let queries = watch
+        .queries
+        .iter()
+        .map(|q| WatchRule::new(q, q.source.unwrap_or(cfg.source)).map(|r| (q.clone(), r)))
+        .collect::<Result<Vec<_>, String>>()?;
+
+    let seen_path = config.path().join(WATCH_SEEN_FILE);
+    let seen: WatchSeen = config::load_path(&seen_path)?;
+    let interval = args.interval.unwrap_or(watch.interval).max(1);
+    let once = args.once;
+    let (jar, cookies_path) = open_cookies(&config, &cfg);
+
+    let mut watcher = Watcher {
+        args,
+        config: cfg,
+        queries,
+        seen_path,
+        seen: seen.seen.into_iter().collect(),
+        jar,
+        cookies_path,
+    };
+    loop {
+        let errors = watcher.poll().await?;
+        if once {
+            return match errors {
+                0 => Ok(()),
+                e => Err(format!("{} errors while polling", e).into()),
+            };
+        }
+        tokio::time::sleep(Duration::from_secs(interval * 60)).await;
+    }
+}
@@ -1,10 +1,21 @@
-use std::fmt::Display;
+use std::{
+    collections::HashMap,
+    error::Error,
+    fmt::Display,
+    future::Future,
+    pin::Pin,
+    sync::{Arc, OnceLock, RwLock},
+};
 
-use serde::{Deserialize, Serialize};
-use strum::{Display, VariantArray};
-use tokio::task::JoinSet;
+use indexmap::IndexMap;
+use serde::{de::DeserializeOwned, Deserialize, Deserializer, Serialize, Serializer};
+use tokio::task::{JoinError, JoinSet};
 
-use crate::{client::cmd::CmdClient, source::Item, widget::notifications::Notification};
+use crate::{
+    client::cmd::CmdClient,
+    source::{intern, Item},
+    widget::notifications::Notification,
+};
 
 use self::{
     cmd::CmdConfig,
@@ -22,37 +33,126 @@ pub mod qbit;
 pub mod rqbit;
 pub mod transmission;
 
-pub struct DownloadError(String);
+/// Why a download failed, so the app and embedders can react to each case
+#[derive(Debug)]
+pub enum DownloadError {
+    /// The client responded with an unexpected status code
+    Http {
+        status: u16,
+        message: String,
+    },
+    /// The client did not respond in time
+    Timeout {
+        message: String,
+    },
+    /// The client could not be reached
+    Connect {
+        message: String,
+    },
+    /// The credentials in the config were rejected
+    Auth {
+        key: String,
+        message: String,
+    },
+    /// The client's config is missing or invalid
+    Config {
+        key: String,
+        message: String,
+    },
+    Other(String),
+}
+
+impl DownloadError {
+    pub fn config<K: Into<String>, M: Display>(key: K, message: M) -> Self {
+        Self::Config {
+            key: key.into(),
+            message: message.to_string(),
+        }
+    }
+
+    /// Error of a request sent to a client, prefixed with `context`
+    pub fn request<C: Display>(context: C, e: Box<dyn Error + Send + Sync>) -> Self {
+        let message = format!("{}\n{}", context, e);
+        match e.downcast_ref::<reqwest::Error>() {
+            Some(e) if e.is_timeout() => Self::Timeout { message },
+            Some(e) if e.is_connect() => Self::Connect { message },
+            Some(e) if e.status().is_some() => Self::Http {
+                status: e.status().map(|s| s.as_u16()).unwrap_or_default(),
+                message,
+            },
+            _ => Self::Other(message),
+        }
+    }
+
+    /// Whether downloading again may succeed without changing anything
+    pub fn is_transient(&self) -> bool {
+        match self {
+            Self::Http { status, .. } => *status == 429 || *status >= 500,
+            Self::Timeout { .. } | Self::Connect { .. } => true,
+            _ => false,
+        }
+    }
+}
+
+impl Error for DownloadError {}
 
 impl From<String> for DownloadError {
     fn from(value: String) -> Self {
-        Self(value)
+        Self::Other(value)
     }
 }
 
 impl From<&str> for DownloadError {
     fn from(value: &str) -> Self {
-        Self(value.to_owned())
+        Self::Other(value.to_owned())
     }
 }
 
-pub trait DownloadClient {
+impl From<Box<dyn Error + Send + Sync>> for DownloadError {
+    fn from(value: Box<dyn Error + Send + Sync>) -> Self {
+        match value.downcast::<DownloadError>() {
+            Ok(e) => *e,
+            Err(e) => Self::Other(e.to_string()),
+        }
+    }
+}
+
+impl From<JoinError> for DownloadError {
+    fn from(value: JoinError) -> Self {
+        Self::Other(value.to_string())
+    }
+}
+
+pub type DownloadFuture<T> = Pin<Box<dyn Future<Output = T> + Send + 'static>>;
+
+pub trait DownloadClient: Send + Sync {
+    /// Name shown to the user
+    fn name(&self) -> String;
     fn download(
+        &self,
         item: Item,
         conf: ClientConfig,
         client: reqwest::Client,
-    ) -> impl std::future::Future<Output = SingleDownloadResult> + std::marker::Send + 'static;
+    ) -> DownloadFuture<SingleDownloadResult>;
     fn batch_download(
+        &self,
         items: Vec<Item>,
         conf: ClientConfig,
         client: reqwest::Client,
-    ) -> impl std::future::Future<Output = BatchDownloadResult> + std::marker::Send + 'static;
-    fn load_config(cfg: &mut ClientConfig);
+    ) -> DownloadFuture<BatchDownloadResult>;
+    fn load_config(&self, cfg: &mut ClientConfig);
 }
 
 impl Display for DownloadError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", self.0)
+        match self {
+            Self::Http { message, .. }
+            | Self::Timeout { message }
+            | Self::Connect { message }
+            | Self::Auth { message, .. }
+            | Self::Config { message, .. }
+            | Self::Other(message) => f.write_str(message),
+        }
     }
 }
 
@@ -63,6 +163,7 @@ pub struct DownloadSuccessResult {
 
 pub struct DownloadErrorResult {
     pub msg: Notification,
+    pub error: DownloadError,
 }
 
 pub enum SingleDownloadResult {
@@ -89,9 +190,11 @@ impl SingleDownloadResult {
         })
     }
 
-    pub fn error<S: Display>(msg: S) -> Self {
+    pub fn error<E: Into<DownloadError>>(error: E) -> Self {
+        let error = error.into();
         Self::Error(DownloadErrorResult {
-            msg: Notification::error(msg),
+            msg: Notification::error(&error),
+            error,
         })
     }
 
@@ -104,31 +207,36 @@ impl SingleDownloadResult {
     }
 }
 
-#[derive(Serialize, Deserialize, Display, Clone, Copy, VariantArray, PartialEq, Eq)]
-pub enum Client {
-    #[serde(rename = "qBittorrent")]
-    #[strum(serialize = "qBittorrent")]
-    Qbit = 0,
-
-    #[serde(rename = "Transmission")]
-    #[strum(serialize = "Transmission")]
-    Transmission = 1,
+/// Handle to a download client in the `ClientRegistry`, identified by the name it was registered with
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Client(&'static str);
 
-    #[serde(rename = "rqbit")]
-    #[strum(serialize = "rqbit")]
-    Rqbit = 2,
+#[allow(non_upper_case_globals)]
+impl Client {
+    pub const Qbit: Client = Client("qBittorrent");
+    pub const Transmission: Client = Client("Transmission");
+    pub const Rqbit: Client = Client("rqbit");
+    pub const DefaultApp: Client = Client("DefaultApp");
+    pub const Download: Client = Client("DownloadTorrentFile");
+    pub const Cmd: Client = Client("RunCommand");
+}
 
-    #[serde(rename = "DefaultApp")]
-    #[strum(serialize = "Default App")]
-    DefaultApp = 3,
+impl Display for Client {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.client().name())
+    }
+}
 
-    #[serde(rename = "DownloadTorrentFile")]
-    #[strum(serialize = "Download Torrent File")]
-    Download = 4,
+impl Serialize for Client {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.id())
+    }
+}
 
-    #[serde(rename = "RunCommand")]
-    #[strum(serialize = "Run Command")]
-    Cmd = 5,
+impl<'de> Deserialize<'de> for Client {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(Client::new(&String::deserialize(deserializer)?))
+    }
 }
 
 #[derive(Default, Clone, Deserialize, Serialize)]
@@ -145,54 +253,186 @@ pub struct ClientConfig {
     pub download: Option<DownloadConfig>,
     #[serde(rename = "rqbit")]
     pub rqbit: Option<RqbitConfig>,
+    /// Tables of clients registered by other crates, like `[client.myclient]`
+    #[serde(flatten)]
+    pub other: HashMap<String, toml::Value>,
 }
 
-pub async fn multidownload<C: DownloadClient, F>(
+impl ClientConfig {
+    /// Config table of a client registered by another crate
+    pub fn get<T: DeserializeOwned>(&self, key: &str) -> Option<T> {
+        self.other.get(key).and_then(|v| v.clone().try_into().ok())
+    }
+
+    pub fn set<T: Serialize>(&mut self, key: &str, value: &T) -> Result<(), toml::ser::Error> {
+        self.other
+            .insert(key.to_owned(), toml::Value::try_from(value)?);
+        Ok(())
+    }
+}
+
+/// Download each item on its own with `dl`, collecting the results into one
+pub fn multidownload<F>(
+    dl: &dyn DownloadClient,
     success_msg: F,
     items: &[Item],
     conf: &ClientConfig,
     client: &reqwest::Client,
-) -> BatchDownloadResult
+) -> DownloadFuture<BatchDownloadResult>
 where
-    F: Fn(usize) -> String,
+    F: Fn(usize) -> String + Send + 'static,
 {
-    let mut set = JoinSet::new();
-    for item in items.iter() {
-        let item = item.to_owned();
-        set.spawn(C::download(item.clone(), conf.clone(), client.clone()));
-    }
-
-    let mut success_ids: Vec<String> = vec![];
-    let mut errors: Vec<Notification> = vec![];
-    while let Some(res) = set.join_next().await {
-        match res.unwrap_or_else(SingleDownloadResult::error) {
-            SingleDownloadResult::Success(sr) => success_ids.push(sr.id),
-            SingleDownloadResult::Error(er) => errors.push(er.msg),
+    let downloads: Vec<DownloadFuture<SingleDownloadResult>> = items
+        .iter()
+        .map(|item| dl.download(item.to_owned(), conf.clone(), client.clone()))
+        .collect();
+    Box::pin(async move {
+        let mut set = JoinSet::new();
+        for download in downloads {
+            set.spawn(download);
         }
+
+        let mut success_ids: Vec<String> = vec![];
+        let mut errors: Vec<Notification> = vec![];
+        while let Some(res) = set.join_next().await {
+            match res.unwrap_or_else(SingleDownloadResult::error) {
+                SingleDownloadResult::Success(sr) => success_ids.push(sr.id),
+                SingleDownloadResult::Error(er) => errors.push(er.msg),
+            }
+        }
+
+        BatchDownloadResult {
+            msg: Notification::success(success_msg(success_ids.len())),
+            errors,
+            ids: success_ids,
+        }
+    })
+}
+
+/// Stands in for a client which is not registered
+struct UnknownClient(&'static str);
+
+impl DownloadClient for UnknownClient {
+    fn name(&self) -> String {
+        self.0.to_owned()
+    }
+
+    fn download(
+        &self,
+        _item: Item,
+        _conf: ClientConfig,
+        _client: reqwest::Client,
+    ) -> DownloadFuture<SingleDownloadResult> {
+        let error = DownloadError::config(
+            "download_client",
+            format!("Unknown download client \"{}\"", self.0),
+        );
+        Box::pin(async move { SingleDownloadResult::error(error) })
     }
 
-    BatchDownloadResult {
-        msg: Notification::success(success_msg(success_ids.len())),
-        errors,
-        ids: success_ids,
+    fn batch_download(
+        &self,
+        _items: Vec<Item>,
+        _conf: ClientConfig,
+        _client: reqwest::Client,
+    ) -> DownloadFuture<BatchDownloadResult> {
+        let msg = format!("Unknown download client \"{}\"", self.0);
+        Box::pin(async move {
+            BatchDownloadResult {
+                msg: Notification::error(msg),
+                errors: vec![],
+                ids: vec![],
+            }
+        })
     }
+
+    fn load_config(&self, _cfg: &mut ClientConfig) {}
+}
+
+/// Every download client which can be selected, keyed by name in the order they were registered
+pub struct ClientRegistry {
+    clients: IndexMap<&'static str, Arc<dyn DownloadClient>>,
+}
+
+impl Default for ClientRegistry {
+    fn default() -> Self {
+        let mut registry = ClientRegistry {
+            clients: IndexMap::new(),
+        };
+        registry.register("qBittorrent", QbitClient);
+        registry.register("Transmission", TransmissionClient);
+        registry.register("rqbit", RqbitClient);
+        registry.register("DefaultApp", DefaultAppClient);
+        registry.register("DownloadTorrentFile", DownloadFileClient);
+        registry.register("RunCommand", CmdClient);
+        registry
+    }
+}
+
+impl ClientRegistry {
+    /// Register a client under `name`, replacing any client already registered with that name
+    pub fn register(&mut self, name: &str, client: impl DownloadClient + 'static) -> Client {
+        self.register_shared(name, Arc::new(client))
+    }
+
+    pub fn register_shared(&mut self, name: &str, client: Arc<dyn DownloadClient>) -> Client {
+        let c = Client::new(name);
+        self.clients.insert(c.0, client);
+        c
+    }
+
+    pub fn unregister(&mut self, c: Client) {
+        self.clients.shift_remove(c.0);
+    }
+
+    pub fn get(&self, c: Client) -> Option<Arc<dyn DownloadClient>> {
+        self.clients.get(c.0).cloned()
+    }
+
+    pub fn clients(&self) -> Vec<Client> {
+        self.clients.keys().map(|k| Client(k)).collect()
+    }
+}
+
+/// The registry shared by the app, which starts out with the built-in clients
+pub fn registry() -> &'static RwLock<ClientRegistry> {
+    static REGISTRY: OnceLock<RwLock<ClientRegistry>> = OnceLock::new();
+    REGISTRY.get_or_init(|| RwLock::new(ClientRegistry::default()))
 }
 
 impl Client {
+    pub fn new(name: &str) -> Client {
+        Client(intern(name))
+    }
+
+    /// Every registered client
+    pub fn all() -> Vec<Client> {
+        registry()
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .clients()
+    }
+
+    /// Name the client is registered and stored in the config with
+    pub fn id(&self) -> &'static str {
+        self.0
+    }
+
+    pub fn client(self) -> Arc<dyn DownloadClient> {
+        registry()
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(self)
+            .unwrap_or_else(|| Arc::new(UnknownClient(self.0)))
+    }
+
     pub async fn download(
         self,
         item: Item,
         conf: ClientConfig,
         client: reqwest::Client,
     ) -> SingleDownloadResult {
-        match self {
-            Self::Cmd => CmdClient::download(item, conf, client).await,
-            Self::DefaultApp => DefaultAppClient::download(item, conf, client).await,
-            Self::Download => DownloadFileClient::download(item, conf, client).await,
-            Self::Qbit => QbitClient::download(item, conf, client).await,
-            Self::Rqbit => RqbitClient::download(item, conf, client).await,
-            Self::Transmission => TransmissionClient::download(item, conf, client).await,
-        }
+        self.client().download(item, conf, client).await
     }
 
     pub async fn batch_download(
@@ -201,24 +441,10 @@ impl Client {
         conf: ClientConfig,
         client: reqwest::Client,
     ) -> BatchDownloadResult {
-        match self {
-            Self::Cmd => CmdClient::batch_download(items, conf, client).await,
-            Self::DefaultApp => DefaultAppClient::batch_download(items, conf, client).await,
-            Self::Download => DownloadFileClient::batch_download(items, conf, client).await,
-            Self::Qbit => QbitClient::batch_download(items, conf, client).await,
-            Self::Rqbit => RqbitClient::batch_download(items, conf, client).await,
-            Self::Transmission => TransmissionClient::batch_download(items, conf, client).await,
-        }
+        self.client().batch_download(items, conf, client).await
     }
 
     pub fn load_config(self, cfg: &mut ClientConfig) {
-        match self {
-            Self::Cmd => CmdClient::load_config(cfg),
-            Self::DefaultApp => DefaultAppClient::load_config(cfg),
-            Self::Download => DownloadFileClient::load_config(cfg),
-            Self::Rqbit => RqbitClient::load_config(cfg),
-            Self::Qbit => QbitClient::load_config(cfg),
-            Self::Transmission => TransmissionClient::load_config(cfg),
-        };
+        self.client().load_config(cfg);
     }
 }
@@ -3,7 +3,8 @@ use serde::{Deserialize, Serialize};
 use crate::{source::Item, util::cmd::CommandBuilder};
 
 use super::{
-    multidownload, BatchDownloadResult, ClientConfig, DownloadClient, SingleDownloadResult,
+    multidownload, BatchDownloadResult, ClientConfig, DownloadClient, DownloadError,
+    DownloadFuture, SingleDownloadResult,
 };
 
 #[derive(Serialize, Deserialize, Clone)]
@@ -29,42 +30,57 @@ impl Default for CmdConfig {
 }
 
 impl DownloadClient for CmdClient {
-    async fn download(item: Item, conf: ClientConfig, _: reqwest::Client) -> SingleDownloadResult {
-        let cmd = match conf.cmd.to_owned() {
-            Some(c) => c,
-            None => {
-                return SingleDownloadResult::error("Failed to get cmd config");
-            }
-        };
-        let res = CommandBuilder::new(cmd.cmd)
-            .sub("{magnet}", &item.magnet_link)
-            .sub("{torrent}", &item.torrent_link)
-            .sub("{title}", &item.title)
-            .sub("{file}", &item.file_name)
-            .run(cmd.shell_cmd)
-            .map_err(|e| e.to_string());
+    fn name(&self) -> String {
+        "Run Command".to_owned()
+    }
 
-        match res {
-            Ok(()) => SingleDownloadResult::success("Successfully ran command", item.id),
-            Err(e) => SingleDownloadResult::error(e),
-        }
+    fn download(
+        &self,
+        item: Item,
+        conf: ClientConfig,
+        _: reqwest::Client,
+    ) -> DownloadFuture<SingleDownloadResult> {
+        Box::pin(async move {
+            let cmd = match conf.cmd.to_owned() {
+                Some(c) => c,
+                None => {
+                    return SingleDownloadResult::error(DownloadError::config(
+                        "client.command",
+                        "Failed to get cmd config",
+                    ));
+                }
+            };
+            let res = CommandBuilder::new(cmd.cmd)
+                .sub("{magnet}", &item.magnet_link)
+                .sub("{torrent}", &item.torrent_link)
+                .sub("{title}", &item.title)
+                .sub("{file}", &item.file_name)
+                .run(cmd.shell_cmd)
+                .map_err(|e| e.to_string());
+
+            match res {
+                Ok(()) => SingleDownloadResult::success("Successfully ran command", item.id),
+                Err(e) => SingleDownloadResult::error(e),
+            }
+        })
     }
 
-    async fn batch_download(
+    fn batch_download(
+        &self,
         items: Vec<Item>,
         conf: ClientConfig,
         client: reqwest::Client,
-    ) -> BatchDownloadResult {
-        multidownload::<CmdClient, _>(
+    ) -> DownloadFuture<BatchDownloadResult> {
+        multidownload(
+            self,
             |s| format!("Successfully ran command on {} torrents", s),
             &items,
             &conf,
             &client,
         )
-        .await
     }
 
-    fn load_config(cfg: &mut ClientConfig) {
+    fn load_config(&self, cfg: &mut ClientConfig) {
         if cfg.cmd.is_none() {
             cfg.cmd = Some(CmdConfig::default());
         }
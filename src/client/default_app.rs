@@ -3,7 +3,8 @@ use serde::{Deserialize, Serialize};
 use crate::source::Item;
 
 use super::{
-    multidownload, BatchDownloadResult, ClientConfig, DownloadClient, SingleDownloadResult,
+    multidownload, BatchDownloadResult, ClientConfig, DownloadClient, DownloadError,
+    DownloadFuture, SingleDownloadResult,
 };
 
 #[derive(Serialize, Deserialize, Clone, Default)]
@@ -15,40 +16,56 @@ pub struct DefaultAppConfig {
 pub struct DefaultAppClient;
 
 impl DownloadClient for DefaultAppClient {
-    async fn download(item: Item, conf: ClientConfig, _: reqwest::Client) -> SingleDownloadResult {
-        let conf = match conf.default_app.to_owned() {
-            Some(c) => c,
-            None => {
-                return SingleDownloadResult::error("Failed to get default app config");
-            }
-        };
-        let link = match conf.use_magnet {
-            true => item.magnet_link.to_owned(),
-            false => item.torrent_link.to_owned(),
-        };
-        match open::that_detached(link).map_err(|e| e.to_string()) {
-            Ok(()) => {
-                SingleDownloadResult::success("Successfully opened link in default app", item.id)
+    fn name(&self) -> String {
+        "Default App".to_owned()
+    }
+
+    fn download(
+        &self,
+        item: Item,
+        conf: ClientConfig,
+        _: reqwest::Client,
+    ) -> DownloadFuture<SingleDownloadResult> {
+        Box::pin(async move {
+            let conf = match conf.default_app.to_owned() {
+                Some(c) => c,
+                None => {
+                    return SingleDownloadResult::error(DownloadError::config(
+                        "client.default_app",
+                        "Failed to get default app config",
+                    ));
+                }
+            };
+            let link = match conf.use_magnet {
+                true => item.magnet_link.to_owned(),
+                false => item.torrent_link.to_owned(),
+            };
+            match open::that_detached(link).map_err(|e| e.to_string()) {
+                Ok(()) => SingleDownloadResult::success(
+                    "Successfully opened link in default app",
+                    item.id,
+                ),
+                Err(e) => SingleDownloadResult::error(e),
             }
-            Err(e) => SingleDownloadResult::error(e),
-        }
+        })
     }
 
-    async fn batch_download(
+    fn batch_download(
+        &self,
         items: Vec<Item>,
         conf: ClientConfig,
         client: reqwest::Client,
-    ) -> BatchDownloadResult {
-        multidownload::<DefaultAppClient, _>(
+    ) -> DownloadFuture<BatchDownloadResult> {
+        multidownload(
+            self,
             |s| format!("Successfully opened {} links in default app", s),
             &items,
             &conf,
             &client,
         )
-        .await
     }
 
-    fn load_config(cfg: &mut ClientConfig) {
+    fn load_config(&self, cfg: &mut ClientConfig) {
         if cfg.default_app.is_none() {
             let def = DefaultAppConfig::default();
             cfg.default_app = Some(def);
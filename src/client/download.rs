@@ -3,10 +3,11 @@ use std::{error::Error, fs, path::PathBuf};
 use reqwest::StatusCode;
 use serde::{Deserialize, Serialize};
 
-use crate::{source::Item, util::conv::get_hash};
+use crate::source::Item;
 
 use super::{
-    multidownload, BatchDownloadResult, ClientConfig, DownloadClient, SingleDownloadResult,
+    multidownload, BatchDownloadResult, ClientConfig, DownloadClient, DownloadError,
+    DownloadFuture, SingleDownloadResult,
 };
 
 #[derive(Serialize, Deserialize, Clone)]
@@ -70,70 +71,86 @@ async fn download_torrent(
 }
 
 impl DownloadClient for DownloadFileClient {
-    async fn download(
+    fn name(&self) -> String {
+        "Download Torrent File".to_owned()
+    }
+
+    fn download(
+        &self,
         item: Item,
         conf: ClientConfig,
         client: reqwest::Client,
-    ) -> SingleDownloadResult {
-        let conf = match conf.download.to_owned() {
-            Some(c) => c,
-            None => {
-                return SingleDownloadResult::error("Failed to get download config");
-            }
-        };
+    ) -> DownloadFuture<SingleDownloadResult> {
+        Box::pin(async move {
+            let conf = match conf.download.to_owned() {
+                Some(c) => c,
+                None => {
+                    return SingleDownloadResult::error(DownloadError::config(
+                        "client.download",
+                        "Failed to get download config",
+                    ));
+                }
+            };
 
-        let filename = conf
-            .filename
-            .map(|f| {
-                f.replace("{file}", &item.file_name)
-                    .replace(
-                        "{basename}",
-                        item.file_name
-                            .split_once(".torrent")
-                            .map(|f| f.0)
-                            .unwrap_or(&item.file_name),
-                    )
-                    .replace(
-                        "{hash}",
-                        &get_hash(item.magnet_link).unwrap_or("NO_HASH_FOUND".to_string()),
-                    )
-            })
-            .unwrap_or(item.file_name.to_owned());
-        match download_torrent(
-            item.torrent_link.to_owned(),
-            filename,
-            conf.save_dir.clone(),
-            conf.create_root_folder,
-            conf.overwrite,
-            client,
-        )
-        .await
-        {
-            Ok(path) => SingleDownloadResult::success(format!("Saved to \"{}\"", path), item.id),
-            Err(e) => SingleDownloadResult::error(format!(
-                "Failed to download torrent to {}:\n{}",
-                conf.save_dir.to_owned(),
-                e
-            )),
-        }
+            let filename = conf
+                .filename
+                .map(|f| {
+                    f.replace("{file}", &item.file_name)
+                        .replace(
+                            "{basename}",
+                            item.file_name
+                                .split_once(".torrent")
+                                .map(|f| f.0)
+                                .unwrap_or(&item.file_name),
+                        )
+                        .replace(
+                            "{hash}",
+                            &item
+                                .hash
+                                .map(|h| h.to_string())
+                                .unwrap_or("NO_HASH_FOUND".to_string()),
+                        )
+                })
+                .unwrap_or(item.file_name.to_owned());
+            match download_torrent(
+                item.torrent_link.to_owned(),
+                filename,
+                conf.save_dir.clone(),
+                conf.create_root_folder,
+                conf.overwrite,
+                client,
+            )
+            .await
+            {
+                Ok(path) => {
+                    SingleDownloadResult::success(format!("Saved to \"{}\"", path), item.id)
+                }
+                Err(e) => SingleDownloadResult::error(format!(
+                    "Failed to download torrent to {}:\n{}",
+                    conf.save_dir.to_owned(),
+                    e
+                )),
+            }
+        })
     }
 
-    async fn batch_download(
+    fn batch_download(
+        &self,
         items: Vec<Item>,
         conf: ClientConfig,
         client: reqwest::Client,
-    ) -> BatchDownloadResult {
+    ) -> DownloadFuture<BatchDownloadResult> {
         let save_dir = conf.download.clone().unwrap_or_default().save_dir.clone();
-        multidownload::<DownloadFileClient, _>(
-            |s| format!("Saved {} torrents to folder {}", s, save_dir),
+        multidownload(
+            self,
+            move |s| format!("Saved {} torrents to folder {}", s, save_dir),
             &items,
             &conf,
             &client,
         )
-        .await
     }
 
-    fn load_config(cfg: &mut ClientConfig) {
+    fn load_config(&self, cfg: &mut ClientConfig) {
         if cfg.download.is_none() {
             cfg.download = Some(DownloadConfig::default());
         }
@@ -5,7 +5,10 @@ use serde::{Deserialize, Serialize};
 
 use crate::{source::Item, util::conv::add_protocol, widget::notifications::Notification};
 
-use super::{BatchDownloadResult, ClientConfig, DownloadClient, SingleDownloadResult};
+use super::{
+    BatchDownloadResult, ClientConfig, DownloadClient, DownloadError, DownloadFuture,
+    SingleDownloadResult,
+};
 
 #[derive(Serialize, Deserialize, Clone)]
 #[serde(default)]
@@ -164,21 +167,24 @@ async fn download_some(
     items: Vec<Item>,
     conf: ClientConfig,
     client: reqwest::Client,
-) -> Result<(), String> {
+) -> Result<(), DownloadError> {
     let Some(qbit) = conf.qbit.to_owned() else {
-        return Err("Failed to get qBittorrent config".to_owned());
+        return Err(DownloadError::config(
+            "client.qBittorrent",
+            "Failed to get qBittorrent config",
+        ));
     };
     if let Some(labels) = qbit.tags.clone() {
         if let Some(bad) = labels.iter().find(|l| l.contains(',')) {
             let bad = format!("\"{}\"", bad);
-            return Err(format!(
-                "qBittorrent tags must not contain commas:\n{}",
-                bad
+            return Err(DownloadError::config(
+                "client.qBittorrent.tags",
+                format!("qBittorrent tags must not contain commas:\n{}", bad),
             ));
         }
     }
     if let Err(e) = login(&qbit, &client).await {
-        return Err(format!("Failed to get SID:\n{}", e));
+        return Err(DownloadError::request("Failed to get SID:", e));
     }
     let links = match qbit.use_magnet.unwrap_or(true) {
         true => items
@@ -194,18 +200,24 @@ async fn download_some(
     };
     let res = match add_torrent(&qbit, links, &client).await {
         Ok(res) => res,
-        Err(e) => return Err(format!("Failed to get response:\n{}", e)),
+        Err(e) => return Err(DownloadError::request("Failed to get response:", e)),
     };
     if res.status() != StatusCode::OK {
-        let mut msg = format!(
+        let message = format!(
             "qBittorrent returned status code {} {}",
             res.status().as_u16(),
             res.status().canonical_reason().unwrap_or("")
         );
         if res.status() == StatusCode::FORBIDDEN {
-            msg.push_str("\n\nLikely incorrect username/password");
+            return Err(DownloadError::Auth {
+                key: "client.qBittorrent.password".to_owned(),
+                message: format!("{}\n\nLikely incorrect username/password", message),
+            });
         }
-        return Err(msg);
+        return Err(DownloadError::Http {
+            status: res.status().as_u16(),
+            message,
+        });
     }
 
     let _ = logout(&qbit, &client).await;
@@ -213,43 +225,55 @@ async fn download_some(
 }
 
 impl DownloadClient for QbitClient {
-    async fn download(
+    fn name(&self) -> String {
+        "qBittorrent".to_owned()
+    }
+
+    fn download(
+        &self,
         item: Item,
         conf: ClientConfig,
         client: reqwest::Client,
-    ) -> SingleDownloadResult {
-        let id = item.id.clone();
-        match download_some(vec![item], conf, client).await {
-            Ok(()) => SingleDownloadResult::success("Successfully sent torrent to qBittorrent", id),
-            Err(e) => SingleDownloadResult::error(e),
-        }
+    ) -> DownloadFuture<SingleDownloadResult> {
+        Box::pin(async move {
+            let id = item.id.clone();
+            match download_some(vec![item], conf, client).await {
+                Ok(()) => {
+                    SingleDownloadResult::success("Successfully sent torrent to qBittorrent", id)
+                }
+                Err(e) => SingleDownloadResult::error(e),
+            }
+        })
     }
 
-    async fn batch_download(
+    fn batch_download(
+        &self,
         items: Vec<Item>,
         conf: ClientConfig,
         client: reqwest::Client,
-    ) -> BatchDownloadResult {
-        let ids = items.iter().map(|i| i.id.clone()).collect();
-        let num_items = items.len();
-        match download_some(items, conf, client).await {
-            Ok(()) => BatchDownloadResult {
-                msg: Notification::success("Successfully sent {} torrents to qBittorrent"),
-                ids,
-                errors: vec![],
-            },
-            Err(e) => BatchDownloadResult {
-                msg: Notification::error(format!(
-                    "Failed to send {} torrents to qBittorrent",
-                    num_items
-                )),
-                errors: vec![Notification::error(e)],
-                ids: vec![],
-            },
-        }
+    ) -> DownloadFuture<BatchDownloadResult> {
+        Box::pin(async move {
+            let ids = items.iter().map(|i| i.id.clone()).collect();
+            let num_items = items.len();
+            match download_some(items, conf, client).await {
+                Ok(()) => BatchDownloadResult {
+                    msg: Notification::success("Successfully sent {} torrents to qBittorrent"),
+                    ids,
+                    errors: vec![],
+                },
+                Err(e) => BatchDownloadResult {
+                    msg: Notification::error(format!(
+                        "Failed to send {} torrents to qBittorrent",
+                        num_items
+                    )),
+                    errors: vec![Notification::error(e)],
+                    ids: vec![],
+                },
+            }
+        })
     }
 
-    fn load_config(cfg: &mut ClientConfig) {
+    fn load_config(&self, cfg: &mut ClientConfig) {
         if cfg.qbit.is_none() {
             cfg.qbit = Some(QbitConfig::default());
         }
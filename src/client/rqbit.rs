@@ -8,7 +8,7 @@ use crate::{source::Item, util::conv::add_protocol};
 
 use super::{
     multidownload, BatchDownloadResult, ClientConfig, DownloadClient, DownloadError,
-    SingleDownloadResult,
+    DownloadFuture, SingleDownloadResult,
 };
 
 #[derive(Serialize, Deserialize, Clone)]
@@ -65,55 +65,66 @@ async fn add_torrent(
 }
 
 impl DownloadClient for RqbitClient {
-    async fn download(
+    fn name(&self) -> String {
+        "rqbit".to_owned()
+    }
+
+    fn download(
+        &self,
         item: Item,
         conf: ClientConfig,
         client: reqwest::Client,
-    ) -> SingleDownloadResult {
-        let conf = match conf.rqbit.clone() {
-            Some(q) => q,
-            None => {
-                return SingleDownloadResult::error("Failed to get rqbit config");
+    ) -> DownloadFuture<SingleDownloadResult> {
+        Box::pin(async move {
+            let conf = match conf.rqbit.clone() {
+                Some(q) => q,
+                None => {
+                    return SingleDownloadResult::error(DownloadError::config(
+                        "client.rqbit",
+                        "Failed to get rqbit config",
+                    ));
+                }
+            };
+            let link = match conf.use_magnet.unwrap_or(true) {
+                true => item.magnet_link.to_owned(),
+                false => item.torrent_link.to_owned(),
+            };
+            let res = match add_torrent(&conf, link, &client).await {
+                Ok(r) => r,
+                Err(e) => {
+                    return SingleDownloadResult::error(DownloadError::request(
+                        "Failed to get response from rqbit",
+                        e,
+                    ));
+                }
+            };
+            if res.status() != StatusCode::OK {
+                return SingleDownloadResult::error(DownloadError::Http {
+                    status: res.status().as_u16(),
+                    message: format!("rqbit returned status code {}", res.status().as_u16()),
+                });
             }
-        };
-        let link = match conf.use_magnet.unwrap_or(true) {
-            true => item.magnet_link.to_owned(),
-            false => item.torrent_link.to_owned(),
-        };
-        let res = match add_torrent(&conf, link, &client).await {
-            Ok(r) => r,
-            Err(e) => {
-                return SingleDownloadResult::error(DownloadError(format!(
-                    "Failed to get response from rqbit\n{}",
-                    e
-                )));
-            }
-        };
-        if res.status() != StatusCode::OK {
-            return SingleDownloadResult::error(DownloadError(format!(
-                "rqbit returned status code {}",
-                res.status().as_u16()
-            )));
-        }
 
-        SingleDownloadResult::success("Successfully sent torrent to rqbit".to_owned(), item.id)
+            SingleDownloadResult::success("Successfully sent torrent to rqbit".to_owned(), item.id)
+        })
     }
 
-    async fn batch_download(
+    fn batch_download(
+        &self,
         items: Vec<Item>,
         conf: ClientConfig,
         client: reqwest::Client,
-    ) -> BatchDownloadResult {
-        multidownload::<RqbitClient, _>(
+    ) -> DownloadFuture<BatchDownloadResult> {
+        multidownload(
+            self,
             |s| format!("Successfully sent {} torrents to rqbit", s),
             &items,
             &conf,
             &client,
         )
-        .await
     }
 
-    fn load_config(cfg: &mut ClientConfig) {
+    fn load_config(&self, cfg: &mut ClientConfig) {
         if cfg.rqbit.is_none() {
             cfg.rqbit = Some(RqbitConfig::default());
         }
@@ -9,7 +9,8 @@ use transmission_rpc::{
 use crate::{source::Item, util::conv::add_protocol};
 
 use super::{
-    multidownload, BatchDownloadResult, ClientConfig, DownloadClient, SingleDownloadResult,
+    multidownload, BatchDownloadResult, ClientConfig, DownloadClient, DownloadError,
+    DownloadFuture, SingleDownloadResult,
 };
 
 #[derive(Serialize, Deserialize, Clone)]
@@ -89,55 +90,66 @@ async fn add_torrent(
     client
         .torrent_add(add)
         .await
-        .map_err(|e| format!("Failed to add torrent:\n{}", e))?;
+        .map_err(|e| DownloadError::request("Failed to add torrent:", e))?;
     Ok(())
 }
 
 impl DownloadClient for TransmissionClient {
-    async fn download(
+    fn name(&self) -> String {
+        "Transmission".to_owned()
+    }
+
+    fn download(
+        &self,
         item: Item,
         conf: ClientConfig,
         client: reqwest::Client,
-    ) -> SingleDownloadResult {
-        let Some(conf) = conf.transmission.clone() else {
-            return SingleDownloadResult::error("Failed to get configuration for transmission");
-        };
-
-        if let Some(labels) = conf.labels.clone() {
-            if let Some(bad) = labels.iter().find(|l| l.contains(',')) {
-                let bad = format!("\"{}\"", bad);
-                return SingleDownloadResult::error(format!(
-                    "Transmission labels must not contain commas:\n{}",
-                    bad
+    ) -> DownloadFuture<SingleDownloadResult> {
+        Box::pin(async move {
+            let Some(conf) = conf.transmission.clone() else {
+                return SingleDownloadResult::error(DownloadError::config(
+                    "client.transmission",
+                    "Failed to get configuration for transmission",
                 ));
+            };
+
+            if let Some(labels) = conf.labels.clone() {
+                if let Some(bad) = labels.iter().find(|l| l.contains(',')) {
+                    let bad = format!("\"{}\"", bad);
+                    return SingleDownloadResult::error(format!(
+                        "Transmission labels must not contain commas:\n{}",
+                        bad
+                    ));
+                }
             }
-        }
 
-        let link = match conf.use_magnet {
-            None | Some(true) => item.magnet_link.to_owned(),
-            Some(false) => item.torrent_link.to_owned(),
-        };
-        if let Err(e) = add_torrent(conf, link, client).await {
-            return SingleDownloadResult::error(e);
-        }
-        SingleDownloadResult::success("Successfully sent torrent to Transmission", item.id)
+            let link = match conf.use_magnet {
+                None | Some(true) => item.magnet_link.to_owned(),
+                Some(false) => item.torrent_link.to_owned(),
+            };
+            if let Err(e) = add_torrent(conf, link, client).await {
+                return SingleDownloadResult::error(e);
+            }
+            SingleDownloadResult::success("Successfully sent torrent to Transmission", item.id)
+        })
     }
 
-    async fn batch_download(
+    fn batch_download(
+        &self,
         items: Vec<Item>,
         conf: ClientConfig,
         client: reqwest::Client,
-    ) -> BatchDownloadResult {
-        multidownload::<TransmissionClient, _>(
+    ) -> DownloadFuture<BatchDownloadResult> {
+        multidownload(
+            self,
             |s| format!("Successfully sent {} torrents to Transmission", s),
             &items,
             &conf,
             &client,
         )
-        .await
     }
 
-    fn load_config(cfg: &mut ClientConfig) {
+    fn load_config(&self, cfg: &mut ClientConfig) {
         if cfg.transmission.is_none() {
             cfg.transmission = Some(TransmissionConfig::default());
         }
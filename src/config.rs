@@ -10,9 +10,18 @@ use crate::{
     app::{Context, Widgets, APP_NAME},
     client::{Client, ClientConfig},
     clip::ClipboardConfig,
-    source::{SourceConfig, Sources},
+    history::{SearchHistory, HISTORY_FILE},
+    source::{
+        cookies::COOKIES_FILE,
+        pages::AllPagesConfig,
+        request::{self, RetryConfig},
+        SourceConfig, Sources,
+    },
     theme::{self, Theme},
-    widget::notifications::NotificationConfig,
+    widget::{
+        bookmarks::{Bookmarks, BOOKMARKS_FILE},
+        notifications::NotificationConfig,
+    },
 };
 use directories::ProjectDirs;
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
@@ -23,6 +32,32 @@ pub trait ConfigManager {
     fn load(&self) -> Result<Config, Box<dyn Error>>;
     fn store(&self, cfg: &Config) -> Result<(), Box<dyn Error>>;
     fn path(&self) -> PathBuf;
+    /// Searches kept between sessions, empty unless overridden
+    fn load_history(&self) -> Result<SearchHistory, Box<dyn Error>> {
+        Ok(SearchHistory::default())
+    }
+    fn store_history(&self, _history: &SearchHistory) -> Result<(), Box<dyn Error>> {
+        Ok(())
+    }
+    /// Saved searches, empty unless overridden
+    fn load_bookmarks(&self) -> Result<Bookmarks, Box<dyn Error>> {
+        Ok(Bookmarks::default())
+    }
+    fn store_bookmarks(&self, _bookmarks: &Bookmarks) -> Result<(), Box<dyn Error>> {
+        Ok(())
+    }
+    /// Folder where result pages are cached between sessions
+    fn cache_path(&self) -> Option<PathBuf> {
+        None
+    }
+    /// Folder where pages which could not be scraped are saved for bug reports
+    fn debug_path(&self) -> Option<PathBuf> {
+        None
+    }
+    /// File where cookies are saved between sessions
+    fn cookies_path(&self) -> Option<PathBuf> {
+        None
+    }
 }
 
 pub struct AppConfig {
@@ -59,6 +94,16 @@ pub struct Config {
     pub cursor_padding: usize,
     pub save_config_on_change: bool,
     pub hot_reload_config: bool,
+    pub search_history_size: usize,
+    pub cache_ttl: u64,
+    /// Append the next page when moving past the last result, instead of paging
+    pub infinite_scroll: bool,
+    /// Load the next page in the background once the cursor is this close to the last result
+    pub prefetch_rows: usize,
+    /// Retries of source requests which failed with 429, 5xx or a timeout
+    pub retry: RetryConfig,
+    /// Loading every page of a search to sort and paginate the results locally
+    pub all_pages: AllPagesConfig,
 
     #[serde(rename = "notifications")]
     pub notifications: Option<NotificationConfig>,
@@ -85,6 +130,12 @@ impl Default for Config {
             cursor_padding: 4,
             save_config_on_change: true,
             hot_reload_config: true,
+            search_history_size: 100,
+            cache_ttl: 300,
+            infinite_scroll: false,
+            prefetch_rows: 10,
+            retry: RetryConfig::default(),
+            all_pages: AllPagesConfig::default(),
 
             notifications: None,
             clipboard: None,
@@ -101,9 +152,30 @@ impl ConfigManager for AppConfig {
     fn store(&self, cfg: &Config) -> Result<(), Box<dyn Error>> {
         store_path(self.config_path.join(CONFIG_FILE), cfg)
     }
+    fn load_history(&self) -> Result<SearchHistory, Box<dyn Error>> {
+        load_path(self.config_path.join(HISTORY_FILE))
+    }
+    fn store_history(&self, history: &SearchHistory) -> Result<(), Box<dyn Error>> {
+        store_path(self.config_path.join(HISTORY_FILE), history)
+    }
+    fn load_bookmarks(&self) -> Result<Bookmarks, Box<dyn Error>> {
+        load_path(self.config_path.join(BOOKMARKS_FILE))
+    }
+    fn store_bookmarks(&self, bookmarks: &Bookmarks) -> Result<(), Box<dyn Error>> {
+        store_path(self.config_path.join(BOOKMARKS_FILE), bookmarks)
+    }
     fn path(&self) -> PathBuf {
         self.config_path.clone()
     }
+    fn cache_path(&self) -> Option<PathBuf> {
+        get_cache_folder(APP_NAME).ok().map(|p| p.join("results"))
+    }
+    fn debug_path(&self) -> Option<PathBuf> {
+        get_cache_folder(APP_NAME).ok().map(|p| p.join("debug"))
+    }
+    fn cookies_path(&self) -> Option<PathBuf> {
+        Some(self.config_path.join(COOKIES_FILE))
+    }
 }
 
 impl Config {
@@ -130,7 +202,9 @@ impl Config {
             w.notification.load_config(&conf);
         }
 
-        w.clients.table.select(ctx.client as usize);
+        if let Some(i) = w.clients.table.items.iter().position(|c| *c == ctx.client) {
+            w.clients.table.select(i);
+        }
 
         // Load defaults for default source
         Ok(())
@@ -146,12 +220,26 @@ impl Config {
             ctx.theme = theme.clone();
         }
 
+        request::configure(self.retry);
+
         // Load download client config
         ctx.client.load_config(&mut ctx.config.client);
 
         // Load current source config
         ctx.src.load_config(&mut ctx.config.sources);
 
+        // List custom sources declared in the config
+        w.sources.table.items = Sources::all();
+        if let Some(i) = w.sources.table.items.iter().position(|s| *s == ctx.src) {
+            w.sources.table.select(i);
+        }
+
+        // List clients registered since the popup was created
+        w.clients.table.items = Client::all();
+        if let Some(i) = w.clients.table.items.iter().position(|c| *c == ctx.client) {
+            w.clients.table.select(i);
+        }
+
         Ok(())
     }
 }
@@ -182,7 +270,7 @@ pub fn load_path<T: Serialize + DeserializeOwned + Default>(
     }
 }
 
-fn store_path(path: impl AsRef<Path>, cfg: impl Serialize) -> Result<(), Box<dyn Error>> {
+pub fn store_path(path: impl AsRef<Path>, cfg: impl Serialize) -> Result<(), Box<dyn Error>> {
     let path = path.as_ref();
     let config_dir = path
         .parent()
@@ -210,6 +298,12 @@ pub fn get_configuration_file_path<'a>(
     Ok(path)
 }
 
+pub fn get_cache_folder(app_name: &str) -> Result<PathBuf, Box<dyn Error>> {
+    let project = ProjectDirs::from("rs", "", app_name)
+        .ok_or("could not determine home directory path".to_string())?;
+    Ok(project.cache_dir().to_path_buf())
+}
+
 pub fn get_configuration_folder(app_name: &str) -> Result<PathBuf, Box<dyn Error>> {
     let project = ProjectDirs::from("rs", "", app_name)
         .ok_or("could not determine home directory path".to_string())?;
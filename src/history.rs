@@ -0,0 +1,105 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    app::{Context, Widgets},
+    source::Sources,
+    sync::SearchQuery,
+    widget::sort::{SelectedSort, SortDir},
+};
+
+pub static HISTORY_FILE: &str = "history.toml";
+
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+pub struct HistoryEntry {
+    pub query: String,
+    pub source: Sources,
+    pub category: usize,
+    pub filter: usize,
+    pub sort: usize,
+    pub sort_dir: SortDir,
+    pub user: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct SearchHistory {
+    #[serde(rename = "entry", skip_serializing_if = "Vec::is_empty")]
+    pub entries: Vec<HistoryEntry>,
+}
+
+impl HistoryEntry {
+    pub fn new(search: &SearchQuery, source: Sources) -> Self {
+        Self {
+            query: search.query.to_owned(),
+            source,
+            category: search.category,
+            filter: search.filter,
+            sort: search.sort.sort,
+            sort_dir: search.sort.dir,
+            user: search.user.to_owned(),
+        }
+    }
+
+    /// Restore the query along with the source, category, filter, sort and user
+    /// it was searched with
+    pub fn apply(&self, ctx: &mut Context, w: &mut Widgets) {
+        if self.source != ctx.src {
+            ctx.src = self.source;
+            ctx.src.load_config(&mut ctx.config.sources);
+            ctx.src.apply(ctx, w);
+        }
+
+        w.category.selected = self.category;
+        let (major, minor) = ctx.src_info.get_major_minor(self.category);
+        w.category.table.select(major + minor + 1);
+        w.category.major = major;
+        w.category.minor = minor;
+
+        w.sort.selected = SelectedSort {
+            sort: self.sort,
+            dir: self.sort_dir,
+        };
+        w.sort.table.select(self.sort);
+        w.filter.selected = self.filter;
+        w.filter.table.select(self.filter);
+        ctx.user.clone_from(&self.user);
+
+        w.search.input.input.clone_from(&self.query);
+        w.search
+            .input
+            .set_cursor(w.search.input.input.chars().count());
+    }
+}
+
+impl SearchHistory {
+    /// Add an entry as the most recent, removing any older duplicate and
+    /// dropping the oldest entries past `max`
+    pub fn push(&mut self, entry: HistoryEntry, max: usize) {
+        self.entries.retain(|e| e != &entry);
+        self.entries.push(entry);
+        let len = self.entries.len();
+        if len > max {
+            self.entries.drain(..len - max);
+        }
+    }
+
+    /// Find the most recent entry before `before` whose query contains every
+    /// character of `pattern` in order, ignoring case
+    pub fn fuzzy_find(&self, pattern: &str, before: usize) -> Option<usize> {
+        let pattern = pattern.to_lowercase();
+        self.entries[..before.min(self.entries.len())]
+            .iter()
+            .rposition(|e| {
+                let mut chars = e.query.to_lowercase().chars().collect::<Vec<char>>();
+                chars.reverse();
+                pattern.chars().all(|p| {
+                    while let Some(c) = chars.pop() {
+                        if c == p {
+                            return true;
+                        }
+                    }
+                    false
+                })
+            })
+    }
+}
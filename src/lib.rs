@@ -1,7 +1,10 @@
 pub mod app;
+pub mod cache;
+pub mod cli;
 pub mod client;
 pub mod clip;
 pub mod config;
+pub mod history;
 pub mod macros;
 pub mod results;
 pub mod source;
@@ -1,6 +1,7 @@
 use std::{error::Error, io::stdout};
 
 use app::App;
+use cli::Command;
 use config::{AppConfig, ConfigManager};
 use ratatui::{backend::CrosstermBackend, Terminal};
 use sync::AppSync;
@@ -9,9 +10,12 @@ use sync::AppSync;
 use ratatui::termion::raw::IntoRawMode;
 
 pub mod app;
+pub mod cache;
+pub mod cli;
 pub mod client;
 pub mod clip;
 pub mod config;
+pub mod history;
 pub mod macros;
 pub mod results;
 pub mod source;
@@ -22,12 +26,14 @@ pub mod widget;
 
 struct Args {
     config_path: Option<String>,
+    command: Option<Command>,
 }
 
 fn parse_args() -> Result<Args, Box<dyn Error>> {
     use lexopt::prelude::*;
 
     let mut config_path = None;
+    let mut command = None;
     let mut parser = lexopt::Parser::from_env();
     while let Some(arg) = parser.next()? {
         match arg {
@@ -39,14 +45,26 @@ fn parse_args() -> Result<Args, Box<dyn Error>> {
                 std::process::exit(0);
             }
             Long("help") => {
-                println!("Usage: nyaa [-v|-V|--version] [-c|--config=/path/to/config/folder]");
+                println!("{}", cli::USAGE);
                 std::process::exit(0);
             }
+            Value(name) if command.is_none() => {
+                match Command::parse(&name.string()?, &mut parser, &mut config_path)? {
+                    Command::Help(usage) => {
+                        println!("{}", usage);
+                        std::process::exit(0);
+                    }
+                    cmd => command = Some(cmd),
+                }
+            }
             _ => return Err(arg.unexpected().into()),
         }
     }
 
-    Ok(Args { config_path })
+    Ok(Args {
+        config_path,
+        command,
+    })
 }
 
 #[tokio::main()]
@@ -59,7 +77,27 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         std::process::exit(1);
     }));
 
-    let args = parse_args()?;
+    let args = match parse_args() {
+        Ok(args) => args,
+        Err(e) => {
+            eprintln!("nyaa: {}", e);
+            std::process::exit(2);
+        }
+    };
+    let config = match args.config_path {
+        Some(path) => AppConfig::from_path(path),
+        None => AppConfig::new(),
+    }?;
+
+    // Run headless commands without touching the terminal
+    if let Some(command) = args.command {
+        if let Err(e) = command.run(config).await {
+            eprintln!("nyaa: {}", e);
+            std::process::exit(1);
+        }
+        std::process::exit(0);
+    }
+
     util::term::setup_terminal()?;
 
     #[cfg(any(target_os = "linux", target_os = "windows", target_os = "macos"))]
@@ -70,10 +108,6 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let mut terminal = Terminal::new(backend)?;
 
     let mut app = App::default();
-    let config = match args.config_path {
-        Some(path) => AppConfig::from_path(path),
-        None => AppConfig::new(),
-    }?;
     let sync = AppSync::new(config.path());
 
     app.run_app::<_, _, AppConfig, false>(&mut terminal, sync, config)
@@ -1,17 +1,30 @@
+use std::collections::HashSet;
+
 use ratatui::{
     layout::{Alignment, Constraint},
     style::{Style, Stylize},
     text::{Span, Text},
     widgets::Row,
 };
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    source::{scrape::ParseReport, Item},
+    sync::SearchQuery,
+    widget::sort::SortDir,
+};
 
-use crate::{source::Item, sync::SearchQuery, widget::sort::SortDir};
+pub mod filter;
 
 #[derive(Clone, Default)]
 pub struct Results {
     pub search: SearchQuery,
     pub response: ResultResponse,
     pub table: ResultTable,
+    /// All loaded items while a local filter hides some from `response.items`
+    pub unfiltered: Option<Vec<Item>>,
+    /// Every item of a search loaded with all of its pages, which are sorted and paginated locally
+    pub all: Option<Vec<Item>>,
 }
 
 impl Results {
@@ -20,15 +33,81 @@ impl Results {
             search,
             response,
             table,
+            unfiltered: None,
+            all: None,
+        }
+    }
+
+    /// Add the items of the next page after the loaded ones, leaving out items already loaded
+    pub fn append(&mut self, response: ResultResponse) {
+        let items = match self.unfiltered.as_mut() {
+            Some(unfiltered) => unfiltered,
+            None => &mut self.response.items,
+        };
+        let ids: HashSet<String> = items.iter().map(|i| i.id.clone()).collect();
+        items.extend(
+            response
+                .items
+                .into_iter()
+                .filter(|i| i.id.is_empty() || !ids.contains(&i.id)),
+        );
+        self.response.last_page = response.last_page;
+        self.response.total_results = response.total_results;
+    }
+
+    /// Results of a search loaded with all of its pages, shown once paginated
+    pub fn all_pages(search: SearchQuery, mut response: ResultResponse) -> Self {
+        let all = std::mem::take(&mut response.items);
+        Self {
+            all: Some(all),
+            ..Self::new(search, response, ResultTable::default())
         }
     }
+
+    /// Show the items on `page` of the results loaded with all pages, out of those matching `filter`
+    pub fn paginate(
+        &mut self,
+        page: usize,
+        page_size: usize,
+        filter: Option<&filter::ResultFilter>,
+    ) {
+        let Some(all) = &self.all else {
+            return;
+        };
+        let page_size = page_size.max(1);
+        let matching: Vec<&Item> = all
+            .iter()
+            .filter(|i| match filter {
+                Some(filter) => filter.matches(i),
+                None => true,
+            })
+            .collect();
+        self.response.total_results = matching.len();
+        self.response.last_page = matching.len().div_ceil(page_size).max(1);
+        self.response.items = matching
+            .into_iter()
+            .skip(page.saturating_sub(1) * page_size)
+            .take(page_size)
+            .cloned()
+            .collect();
+        self.unfiltered = None;
+    }
 }
 
-#[derive(Default, Clone)]
+#[derive(Serialize, Deserialize, Default, Clone)]
 pub struct ResultResponse {
     pub items: Vec<Item>,
     pub last_page: usize,
     pub total_results: usize,
+    /// Errors from sources which failed while others loaded, when searching several at once
+    #[serde(skip)]
+    pub errors: Vec<String>,
+    /// Host of the mirror which served the page
+    #[serde(skip)]
+    pub mirror: Option<String>,
+    /// How well the rows of a scraped page could be read
+    #[serde(skip)]
+    pub report: Option<ParseReport>,
 }
 
 pub struct ResultHeader<S: PartialEq + Copy> {
@@ -84,7 +163,7 @@ impl<S: PartialEq + Copy> ResultColumn<S> {
                             name.replace_range(
                                 name.char_indices()
                                     .nth(idx + 2)
-                                    .map(|(pos, ch)| (pos..pos + ch.len_utf8()))
+                                    .map(|(pos, ch)| pos..pos + ch.len_utf8())
                                     .unwrap(),
                                 match dir {
                                     SortDir::Asc => "▲",
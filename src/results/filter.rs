@@ -0,0 +1,297 @@
+use std::str::FromStr;
+
+use regex::{Regex, RegexBuilder};
+
+use crate::{
+    source::{
+        release::{self, ReleaseField},
+        Item, ItemType,
+    },
+    util::conv::to_bytes,
+};
+
+/// A filter over loaded results, made of whitespace separated terms which must all match.
+///
+/// - `word`, `"some words"`: title contains the text, ignoring case
+/// - `/regex/`: title matches the regex, ignoring case
+/// - `field:text`, `field:/regex/`: a text field contains the text or matches the regex
+/// - `field>num`, `field>=num`, `field<num`, `field<=num`, `field=num`: compare a numeric field
+/// - `group:`, `series:`, `ep:`, `res:`, `codec:`, `source:`, `crc:`: parts of the release name,
+///   with `ep` and `res` also compared as numbers
+/// - `is:trusted`, `is:remake`: trusted or remake torrents
+/// - `-term`: the term must not match
+#[derive(Clone)]
+pub struct ResultFilter {
+    terms: Vec<Term>,
+}
+
+#[derive(Clone)]
+struct Term {
+    negate: bool,
+    kind: TermKind,
+}
+
+#[derive(Clone)]
+enum TermKind {
+    Text(TextField, Pattern),
+    Compare(NumField, Op, u64),
+    Is(ItemKind),
+}
+
+#[derive(Clone, Copy)]
+enum TextField {
+    Title,
+    Uploader,
+    Id,
+    Release(ReleaseField),
+}
+
+#[derive(Clone, Copy)]
+enum NumField {
+    Seeders,
+    Leechers,
+    Downloads,
+    Size,
+    Comments,
+    Episode,
+    Resolution,
+}
+
+#[derive(Clone, Copy)]
+enum Op {
+    Lt,
+    Le,
+    Eq,
+    Ge,
+    Gt,
+}
+
+#[derive(Clone, Copy)]
+enum ItemKind {
+    Trusted,
+    Remake,
+}
+
+#[derive(Clone)]
+enum Pattern {
+    Contains(String),
+    Regex(Regex),
+}
+
+impl Pattern {
+    fn parse(s: &str) -> Result<Self, String> {
+        match s.strip_prefix('/').and_then(|s| s.strip_suffix('/')) {
+            Some(re) => RegexBuilder::new(re)
+                .case_insensitive(true)
+                .build()
+                .map(Pattern::Regex)
+                .map_err(|e| format!("Invalid regex \"{}\":\n{}", re, e)),
+            None => Ok(Pattern::Contains(s.to_lowercase())),
+        }
+    }
+
+    fn matches(&self, s: &str) -> bool {
+        match self {
+            Pattern::Contains(p) => s.to_lowercase().contains(p),
+            Pattern::Regex(re) => re.is_match(s),
+        }
+    }
+}
+
+impl FromStr for TextField {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "title" | "name" => Ok(TextField::Title),
+            "user" | "uploader" => Ok(TextField::Uploader),
+            "id" => Ok(TextField::Id),
+            _ => s.parse().map(TextField::Release),
+        }
+    }
+}
+
+impl FromStr for NumField {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "seeders" | "seeds" => Ok(NumField::Seeders),
+            "leechers" | "peers" => Ok(NumField::Leechers),
+            "downloads" | "dl" => Ok(NumField::Downloads),
+            "size" => Ok(NumField::Size),
+            "comments" => Ok(NumField::Comments),
+            "episode" | "ep" => Ok(NumField::Episode),
+            "resolution" | "res" => Ok(NumField::Resolution),
+            _ => Err(()),
+        }
+    }
+}
+
+impl NumField {
+    /// Value of the field, or `None` if the release name has no episode or resolution
+    fn value(self, item: &Item) -> Option<u64> {
+        match self {
+            NumField::Seeders => Some(item.seeders as u64),
+            NumField::Leechers => Some(item.leechers as u64),
+            NumField::Downloads => Some(item.downloads as u64),
+            NumField::Size => Some(item.size),
+            NumField::Comments => Some(
+                item.extra
+                    .get("comments")
+                    .and_then(|c| c.parse().ok())
+                    .unwrap_or_default(),
+            ),
+            NumField::Episode => ReleaseField::Episode
+                .get(item)
+                .and_then(|e| release::episode_number(&e)),
+            NumField::Resolution => ReleaseField::Resolution
+                .get(item)
+                .and_then(|r| r.trim_end_matches('p').parse().ok()),
+        }
+    }
+
+    fn parse_value(self, s: &str) -> Result<u64, String> {
+        let invalid = || format!("Invalid number \"{}\"", s);
+        match self {
+            NumField::Size => {
+                // Allow both "2GiB" and "2 GiB"
+                let split = s
+                    .find(|c: char| !c.is_ascii_digit() && c != '.')
+                    .unwrap_or(s.len());
+                let (num, unit) = s.split_at(split);
+                num.parse::<f64>().map_err(|_| invalid())?;
+                Ok(to_bytes(&format!("{} {}", num, unit.trim().to_uppercase())) as u64)
+            }
+            NumField::Episode => release::episode_number(s).ok_or_else(invalid),
+            // Allow both "1080" and "1080p"
+            NumField::Resolution => s
+                .trim_end_matches(['p', 'P'])
+                .parse()
+                .map_err(|_| invalid()),
+            _ => s.parse().map_err(|_| invalid()),
+        }
+    }
+}
+
+impl Op {
+    fn compare(self, a: u64, b: u64) -> bool {
+        match self {
+            Op::Lt => a < b,
+            Op::Le => a <= b,
+            Op::Eq => a == b,
+            Op::Ge => a >= b,
+            Op::Gt => a > b,
+        }
+    }
+}
+
+/// Split on whitespace, keeping text inside double quotes together
+fn split_terms(s: &str) -> Result<Vec<String>, String> {
+    let mut terms = vec![];
+    let mut term = String::new();
+    let mut quoted = false;
+    for c in s.chars() {
+        match c {
+            '"' => quoted = !quoted,
+            c if c.is_whitespace() && !quoted => {
+                if !term.is_empty() {
+                    terms.push(std::mem::take(&mut term));
+                }
+            }
+            c => term.push(c),
+        }
+    }
+    if quoted {
+        return Err("Missing closing quote".to_owned());
+    }
+    if !term.is_empty() {
+        terms.push(term);
+    }
+    Ok(terms)
+}
+
+impl Term {
+    fn parse(s: &str) -> Result<Self, String> {
+        let (negate, s) = match s.strip_prefix('-') {
+            Some(rest) if !rest.is_empty() => (true, rest),
+            _ => (false, s),
+        };
+
+        // Comparisons, checking two character operators first
+        let ops = [
+            (">=", Op::Ge),
+            ("<=", Op::Le),
+            (">", Op::Gt),
+            ("<", Op::Lt),
+            ("=", Op::Eq),
+        ];
+        for (sym, op) in ops {
+            if let Some((field, value)) = s.split_once(sym) {
+                if let Ok(field) = field.to_lowercase().parse::<NumField>() {
+                    let value = field.parse_value(value)?;
+                    return Ok(Term {
+                        negate,
+                        kind: TermKind::Compare(field, op, value),
+                    });
+                }
+            }
+        }
+
+        let kind = match s.split_once(':') {
+            Some((field, value)) if field.eq_ignore_ascii_case("is") => {
+                match value.to_lowercase().as_str() {
+                    "trusted" => TermKind::Is(ItemKind::Trusted),
+                    "remake" => TermKind::Is(ItemKind::Remake),
+                    _ => return Err(format!("Unknown value \"{}\" for \"is:\"", value)),
+                }
+            }
+            Some((field, value)) => match field.to_lowercase().parse::<TextField>() {
+                Ok(field) => TermKind::Text(field, Pattern::parse(value)?),
+                Err(()) => return Err(format!("Unknown field \"{}\"", field)),
+            },
+            None => TermKind::Text(TextField::Title, Pattern::parse(s)?),
+        };
+        Ok(Term { negate, kind })
+    }
+
+    fn matches(&self, item: &Item) -> bool {
+        let res = match &self.kind {
+            TermKind::Text(field, pattern) => match field {
+                TextField::Title => pattern.matches(&item.title),
+                TextField::Uploader => item
+                    .extra
+                    .get("uploader")
+                    .is_some_and(|u| pattern.matches(u)),
+                TextField::Id => pattern.matches(&item.id),
+                TextField::Release(field) => field.get(item).is_some_and(|v| pattern.matches(&v)),
+            },
+            TermKind::Compare(field, op, value) => {
+                field.value(item).is_some_and(|v| op.compare(v, *value))
+            }
+            TermKind::Is(kind) => matches!(
+                (kind, item.item_type),
+                (ItemKind::Trusted, ItemType::Trusted) | (ItemKind::Remake, ItemType::Remake)
+            ),
+        };
+        res != self.negate
+    }
+}
+
+impl FromStr for ResultFilter {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let terms = split_terms(s)?
+            .iter()
+            .map(|t| Term::parse(t))
+            .collect::<Result<Vec<Term>, String>>()?;
+        Ok(ResultFilter { terms })
+    }
+}
+
+impl ResultFilter {
+    pub fn matches(&self, item: &Item) -> bool {
+        self.terms.iter().all(|t| t.matches(item))
+    }
+}
@@ -1,9 +1,19 @@
-use std::{collections::HashMap, error::Error, sync::Arc, time::Duration};
+use std::{
+    collections::HashMap,
+    error::Error,
+    fmt::{Display, Write as _},
+    future::Future,
+    pin::Pin,
+    str::FromStr,
+    sync::{Arc, Mutex, OnceLock, RwLock},
+    time::Duration,
+};
 
+use chrono::{DateTime, Local, Utc};
+use indexmap::IndexMap;
 use nyaa_html::NyaaTheme;
-use reqwest::{cookie::Jar, Proxy};
-use serde::{Deserialize, Serialize};
-use strum::{Display, VariantArray};
+use reqwest::Proxy;
+use serde::{de::DeserializeOwned, Deserialize, Deserializer, Serialize, Serializer};
 use sukebei_nyaa::SukebeiTheme;
 use torrent_galaxy::TgxTheme;
 
@@ -13,7 +23,10 @@ use crate::{
     results::{ResultResponse, ResultTable, Results},
     sync::SearchQuery,
     theme::Theme,
-    util::conv::add_protocol,
+    util::{
+        conv::{add_protocol, to_bytes, to_relative_date},
+        types::Either,
+    },
     widget::{
         category::{CatEntry, CatIcon, CatStruct},
         sort::SelectedSort,
@@ -21,22 +34,41 @@ use crate::{
 };
 
 use self::{
-    nyaa_html::{NyaaConfig, NyaaHtmlSource},
+    all::{AllConfig, AllSource},
+    command::CommandSourceConfig,
+    cookies::CookieJar,
+    custom::CustomSourceConfig,
+    nyaa_html::{nyaa_table, ItemDetails, NyaaConfig, NyaaHtmlSource},
+    release::ReleaseField,
     sukebei_nyaa::{SukebeiHtmlSource, SukebeiNyaaConfig},
     torrent_galaxy::{TgxConfig, TorrentGalaxyHtmlSource},
+    torznab::{TorznabConfig, TorznabSource},
 };
 
 #[cfg(feature = "captcha")]
 use ratatui_image::protocol::StatefulProtocol;
 
+pub mod all;
+pub mod command;
+pub mod cookies;
+pub mod custom;
+pub mod mirror;
 pub mod nyaa_html;
 pub mod nyaa_rss;
+pub mod pages;
+pub mod release;
+pub mod request;
+pub mod scrape;
 pub mod sukebei_nyaa;
 pub mod torrent_galaxy;
+pub mod torznab;
 
 #[derive(Clone)]
+#[allow(clippy::large_enum_variant)]
 pub enum SourceResults {
     Results(Results),
+    /// A request is being retried, while the results are still loading
+    Progress(String),
     #[cfg(feature = "captcha")]
     Captcha(Box<dyn StatefulProtocol>),
 }
@@ -48,6 +80,173 @@ pub enum SourceResponse {
     Captcha(Box<dyn StatefulProtocol>),
 }
 
+/// Why a source failed to load, so the app and embedders can react to each case
+#[derive(Debug)]
+pub enum SourceError {
+    /// The site responded with an unexpected status code
+    Http {
+        status: u16,
+        url: String,
+    },
+    /// The site did not respond in time
+    Timeout {
+        url: String,
+    },
+    /// The site could not be reached
+    Connect {
+        url: String,
+    },
+    /// The response could not be read, optionally because a selector matched nothing
+    Parse {
+        selector: Option<String>,
+        message: String,
+    },
+    /// The site wants a captcha solved before responding
+    Captcha {
+        url: String,
+    },
+    /// The credentials in the config were rejected
+    Auth {
+        key: String,
+        message: String,
+    },
+    /// A value in the config is invalid
+    Config {
+        key: String,
+        message: String,
+    },
+    Other(String),
+}
+
+impl std::fmt::Display for SourceError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Http { status, url } => write!(f, "{}\nInvalid response code: {}", url, status),
+            Self::Timeout { url } => write!(f, "{}\nTimed out", url),
+            Self::Connect { url } => write!(f, "{}\nFailed to connect", url),
+            Self::Parse {
+                selector: Some(selector),
+                message,
+            } => write!(f, "{}\nSelector: {}", message, selector),
+            Self::Parse {
+                selector: None,
+                message,
+            } => write!(f, "{}", message),
+            Self::Captcha { url } => write!(
+                f,
+                "{}\nA captcha must be solved, most likely due to rate limit.\nWait a bit before retrying...",
+                url
+            ),
+            Self::Auth { message, .. } => write!(f, "{}", message),
+            Self::Config { message, .. } => write!(f, "{}", message),
+            Self::Other(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+impl Error for SourceError {}
+
+impl SourceError {
+    pub fn http(status: reqwest::StatusCode, url: &reqwest::Url) -> Self {
+        Self::Http {
+            status: status.as_u16(),
+            url: url.to_string(),
+        }
+    }
+
+    pub fn parse<S: Into<String>>(selector: S, message: &str) -> Self {
+        Self::Parse {
+            selector: Some(selector.into()),
+            message: message.to_owned(),
+        }
+    }
+
+    pub fn config<K: Into<String>, M: Display>(key: K, message: M) -> Self {
+        Self::Config {
+            key: key.into(),
+            message: message.to_string(),
+        }
+    }
+
+    /// Whether loading again may succeed without changing anything
+    pub fn is_transient(&self) -> bool {
+        match self {
+            Self::Http { status, .. } => *status == 429 || *status >= 500,
+            Self::Timeout { .. } | Self::Connect { .. } => true,
+            _ => false,
+        }
+    }
+}
+
+impl From<reqwest::Error> for SourceError {
+    fn from(e: reqwest::Error) -> Self {
+        let url = e.url().map(|u| u.to_string()).unwrap_or_default();
+        match e.status() {
+            Some(status) => Self::Http {
+                status: status.as_u16(),
+                url,
+            },
+            None if e.is_timeout() => Self::Timeout { url },
+            None if e.is_connect() => Self::Connect { url },
+            None if e.is_decode() => Self::Parse {
+                selector: None,
+                message: e.to_string(),
+            },
+            None => Self::Other(e.to_string()),
+        }
+    }
+}
+
+impl From<Box<dyn Error + Send + Sync>> for SourceError {
+    fn from(e: Box<dyn Error + Send + Sync>) -> Self {
+        match e.downcast::<SourceError>() {
+            Ok(e) => *e,
+            Err(e) => match e.downcast::<reqwest::Error>() {
+                Ok(e) => (*e).into(),
+                Err(e) => Self::Other(e.to_string()),
+            },
+        }
+    }
+}
+
+impl From<String> for SourceError {
+    fn from(e: String) -> Self {
+        Self::Other(e)
+    }
+}
+
+impl From<&str> for SourceError {
+    fn from(e: &str) -> Self {
+        Self::Other(e.to_owned())
+    }
+}
+
+macro_rules! source_error_from {
+    ($variant:ident: $($err:ty),+) => {
+        $(
+            impl From<$err> for SourceError {
+                fn from(e: $err) -> Self {
+                    source_error_from!(@$variant e)
+                }
+            }
+        )+
+    };
+    (@Parse $e:ident) => {
+        Self::Parse {
+            selector: None,
+            message: $e.to_string(),
+        }
+    };
+    (@Other $e:ident) => {
+        Self::Other($e.to_string())
+    };
+}
+
+source_error_from!(Parse: std::str::Utf8Error, serde_json::Error, rss::Error);
+source_error_from!(Other: reqwest::header::InvalidHeaderValue, url::ParseError, reqwest::header::ToStrError, std::time::SystemTimeError, tokio::task::JoinError, std::io::Error);
+#[cfg(feature = "captcha")]
+source_error_from!(Parse: image::ImageError);
+
 #[derive(Serialize, Deserialize, Clone, Copy, Default)]
 pub struct SourceTheme {
     #[serde(default)]
@@ -66,24 +265,120 @@ pub struct SourceConfig {
     pub sukebei: Option<SukebeiNyaaConfig>,
     #[serde(rename = "torrentgalaxy")]
     pub tgx: Option<TgxConfig>,
+    pub torznab: Option<TorznabConfig>,
+    pub all: Option<AllConfig>,
+    pub custom: Vec<CustomSourceConfig>,
+    pub command: Vec<CommandSourceConfig>,
+    /// Tables of sources registered by other crates, like `[source.mysource]`
+    #[serde(flatten)]
+    pub other: HashMap<String, toml::Value>,
 }
 
+impl SourceConfig {
+    /// Config table of a source registered by another crate
+    pub fn get<T: DeserializeOwned>(&self, key: &str) -> Option<T> {
+        self.other.get(key).and_then(|v| v.clone().try_into().ok())
+    }
+
+    pub fn set<T: Serialize>(&mut self, key: &str, value: &T) -> Result<(), toml::ser::Error> {
+        self.other
+            .insert(key.to_owned(), toml::Value::try_from(value)?);
+        Ok(())
+    }
+
+    /// Source name, `cookies.txt` path and urls of each source with cookies to import
+    pub fn cookie_files(&self) -> Vec<(String, String, Vec<String>)> {
+        let urls = |base_url: &String, mirrors: &[String]| {
+            std::iter::once(base_url).chain(mirrors).cloned().collect()
+        };
+        let mut files: Vec<(String, Option<String>, Vec<String>)> = vec![];
+        if let Some(c) = &self.nyaa {
+            let urls = urls(&c.base_url, &c.mirrors);
+            files.push((Sources::Nyaa.to_string(), c.cookies.clone(), urls));
+        }
+        if let Some(c) = &self.sukebei {
+            let urls = urls(&c.base_url, &c.mirrors);
+            files.push((Sources::SukebeiNyaa.to_string(), c.cookies.clone(), urls));
+        }
+        if let Some(c) = &self.tgx {
+            let urls = urls(&c.base_url, &c.mirrors);
+            files.push((Sources::TorrentGalaxy.to_string(), c.cookies.clone(), urls));
+        }
+        if let Some(c) = &self.torznab {
+            let urls = urls(&c.base_url, &[]);
+            files.push((Sources::Torznab.to_string(), c.cookies.clone(), urls));
+        }
+        for c in self.custom.iter() {
+            files.push((c.name.clone(), c.cookies.clone(), vec![c.url.clone()]));
+        }
+        files
+            .into_iter()
+            .filter_map(|(name, file, urls)| Some((name, file?, urls)))
+            .collect()
+    }
+}
+
+pub static DEFAULT_DATE_FORMAT: &str = "%Y-%m-%d %H:%M";
+
+#[derive(Clone, Default)]
 pub struct SourceExtraConfig {
     pub date_format: Option<String>,
     pub relative_date: Option<bool>,
     pub relative_date_short: Option<bool>,
 }
 
-impl From<Config> for SourceExtraConfig {
-    fn from(c: Config) -> Self {
+impl SourceExtraConfig {
+    /// Format the publish date of an item in local time, leaving unknown dates empty.
+    /// Relative dates are only correct when formatted, so tables are formatted again as time passes.
+    pub fn format_date(&self, date: DateTime<Utc>) -> String {
+        match self.relative_date.unwrap_or(false) && date != DateTime::<Utc>::default() {
+            true => to_relative_date(
+                date.with_timezone(&Local),
+                self.relative_date_short.unwrap_or(false),
+            ),
+            false => self.format_absolute_date(date),
+        }
+    }
+
+    /// Format the publish date of an item, showing the scraped text if it could not be read
+    pub fn format_item_date(&self, item: &Item) -> String {
+        match item.extra.get("date") {
+            Some(raw) if item.date == DateTime::<Utc>::default() => raw.to_owned(),
+            _ => self.format_date(item.date),
+        }
+    }
+
+    /// Format the publish date of an item using `date_format`, even if `relative_date` is set
+    pub fn format_absolute_date(&self, date: DateTime<Utc>) -> String {
+        if date == DateTime::<Utc>::default() {
+            return String::new();
+        }
+        let date = date.with_timezone(&Local);
+        let date_format = self.date_format.as_deref().unwrap_or(DEFAULT_DATE_FORMAT);
+        let mut newstr = String::new();
+        if write!(newstr, "{}", date.format(date_format)).is_err() {
+            newstr = format!("Invalid format string: `{}`", date_format);
+        }
+        newstr
+    }
+}
+
+impl From<&Config> for SourceExtraConfig {
+    fn from(c: &Config) -> Self {
         SourceExtraConfig {
-            date_format: c.date_format,
+            date_format: c.date_format.clone(),
             relative_date: c.relative_date,
             relative_date_short: c.relative_date_short,
         }
     }
 }
 
+impl From<Config> for SourceExtraConfig {
+    fn from(c: Config) -> Self {
+        SourceExtraConfig::from(&c)
+    }
+}
+
 #[derive(Clone)]
 pub struct SourceInfo {
     pub cats: Vec<CatStruct>,
@@ -129,7 +424,7 @@ impl SourceInfo {
 }
 
 pub fn request_client(
-    jar: &Arc<Jar>,
+    jar: &Arc<CookieJar>,
     timeout: u64,
     proxy_url: Option<String>,
 ) -> Result<reqwest::Client, Box<dyn Error>> {
@@ -145,7 +440,7 @@ pub fn request_client(
     Ok(client.build()?)
 }
 
-#[derive(Default, Clone, Copy)]
+#[derive(Serialize, Deserialize, Default, Clone, Copy)]
 pub enum ItemType {
     #[default]
     None,
@@ -153,85 +448,373 @@ pub enum ItemType {
     Remake,
 }
 
-#[derive(Clone, Default)]
+#[derive(Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
 pub struct Item {
     pub id: String,
-    pub date: String,
+    /// When the torrent was published, the unix epoch if unknown. Scraped dates which could not
+    /// be read are kept in `extra` as "date".
+    #[serde(deserialize_with = "deserialize_date")]
+    pub date: DateTime<Utc>,
     pub seeders: u32,
     pub leechers: u32,
     pub downloads: u32,
-    pub size: String,
-    pub bytes: usize,
+    /// Size in bytes
+    #[serde(deserialize_with = "deserialize_size")]
+    pub size: u64,
     pub title: String,
     pub torrent_link: String,
     pub magnet_link: String,
+    pub hash: Option<InfoHash>,
     pub post_link: String,
     pub file_name: String,
     pub category: usize,
+    /// Not stored, restored from the category of the source
+    #[serde(skip)]
     pub icon: CatIcon,
     pub item_type: ItemType,
     pub extra: HashMap<String, String>,
 }
 
-#[derive(Serialize, Deserialize, Display, Clone, Copy, VariantArray, PartialEq, Eq)]
-pub enum Sources {
-    #[strum(serialize = "Nyaa")]
-    Nyaa = 0,
-    #[strum(serialize = "Sukebei")]
-    SukebeiNyaa = 1,
-    #[strum(serialize = "TorrentGalaxy")]
-    TorrentGalaxy = 2,
+/// Dates are written as RFC 3339, but unix timestamps are also accepted
+fn deserialize_date<'de, D: Deserializer<'de>>(d: D) -> Result<DateTime<Utc>, D::Error> {
+    match Either::<i64, DateTime<Utc>>::deserialize(d)? {
+        Either::Left(t) => Ok(DateTime::from_timestamp(t, 0).unwrap_or_default()),
+        Either::Right(date) => Ok(date),
+    }
 }
 
-pub trait Source {
-    fn search(
-        client: &reqwest::Client,
-        search: &SearchQuery,
-        config: &SourceConfig,
-        extra: &SourceExtraConfig,
-    ) -> impl std::future::Future<Output = Result<SourceResponse, Box<dyn Error + Send + Sync>>> + Send;
-    fn sort(
-        client: &reqwest::Client,
-        search: &SearchQuery,
-        config: &SourceConfig,
-        extra: &SourceExtraConfig,
-    ) -> impl std::future::Future<Output = Result<SourceResponse, Box<dyn Error + Send + Sync>>> + Send;
-    fn filter(
-        client: &reqwest::Client,
-        search: &SearchQuery,
-        config: &SourceConfig,
-        extra: &SourceExtraConfig,
-    ) -> impl std::future::Future<Output = Result<SourceResponse, Box<dyn Error + Send + Sync>>> + Send;
-    fn categorize(
-        client: &reqwest::Client,
-        search: &SearchQuery,
-        config: &SourceConfig,
-        extra: &SourceExtraConfig,
-    ) -> impl std::future::Future<Output = Result<SourceResponse, Box<dyn Error + Send + Sync>>> + Send;
-    fn solve(
-        solution: String,
-        client: &reqwest::Client,
-        search: &SearchQuery,
-        config: &SourceConfig,
-        extra: &SourceExtraConfig,
-    ) -> impl std::future::Future<Output = Result<SourceResponse, Box<dyn Error + Send + Sync>>> + Send;
-    fn info() -> SourceInfo;
-    fn load_config(config: &mut SourceConfig);
+/// Sizes are written in bytes, but sizes like "1.4 GiB" are also accepted
+fn deserialize_size<'de, D: Deserializer<'de>>(d: D) -> Result<u64, D::Error> {
+    match Either::<u64, String>::deserialize(d)? {
+        Either::Left(bytes) => Ok(bytes),
+        Either::Right(size) => Ok(to_bytes(&size)),
+    }
+}
+
+/// SHA-1 info hash identifying a torrent across sources
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct InfoHash(pub [u8; 20]);
+
+impl InfoHash {
+    /// Read the hash from the `xt=urn:btih:` parameter of a magnet link
+    pub fn from_magnet(magnet: &str) -> Option<Self> {
+        magnet
+            .split(['?', '&'])
+            .find_map(|p| p.strip_prefix("xt=urn:btih:"))
+            .and_then(|h| h.parse().ok())
+    }
+}
 
-    fn default_category(config: &SourceConfig) -> usize;
-    fn default_sort(config: &SourceConfig) -> SelectedSort;
-    fn default_filter(config: &SourceConfig) -> usize;
-    fn default_search(config: &SourceConfig) -> String;
+impl FromStr for InfoHash {
+    type Err = String;
+
+    /// Parse a hash written in hex, or in base32 like some magnet links do
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut hash = [0u8; 20];
+        match s.len() {
+            40 => {
+                for (i, byte) in hash.iter_mut().enumerate() {
+                    *byte = u8::from_str_radix(s.get(i * 2..i * 2 + 2).unwrap_or_default(), 16)
+                        .map_err(|_| format!("Invalid info hash \"{}\"", s))?;
+                }
+            }
+            32 => {
+                let mut bits: u64 = 0;
+                let mut len = 0;
+                let mut i = 0;
+                for c in s.chars() {
+                    let v = match c.to_ascii_uppercase() {
+                        c @ 'A'..='Z' => c as u64 - 'A' as u64,
+                        c @ '2'..='7' => c as u64 - '2' as u64 + 26,
+                        _ => return Err(format!("Invalid info hash \"{}\"", s)),
+                    };
+                    bits = (bits << 5) | v;
+                    len += 5;
+                    if len >= 8 {
+                        len -= 8;
+                        hash[i] = (bits >> len) as u8;
+                        i += 1;
+                    }
+                }
+            }
+            _ => return Err(format!("Invalid info hash \"{}\"", s)),
+        }
+        Ok(InfoHash(hash))
+    }
+}
+
+impl Display for InfoHash {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.iter().try_for_each(|b| write!(f, "{:02x}", b))
+    }
+}
+
+impl Serialize for InfoHash {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for InfoHash {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(serde::de::Error::custom)
+    }
+}
 
+/// Handle to a source in the `SourceRegistry`, identified by the name it was registered with
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Sources(&'static str);
+
+#[allow(non_upper_case_globals)]
+impl Sources {
+    pub const Nyaa: Sources = Sources("Nyaa");
+    pub const SukebeiNyaa: Sources = Sources("SukebeiNyaa");
+    pub const TorrentGalaxy: Sources = Sources("TorrentGalaxy");
+    pub const Torznab: Sources = Sources("Torznab");
+    pub const All: Sources = Sources("All");
+}
+
+impl std::fmt::Display for Sources {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.source().name())
+    }
+}
+
+impl Serialize for Sources {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.id())
+    }
+}
+
+impl<'de> Deserialize<'de> for Sources {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(Sources::new(&String::deserialize(deserializer)?))
+    }
+}
+
+pub type SourceFuture<'a, T> = Pin<Box<dyn Future<Output = Result<T, SourceError>> + Send + 'a>>;
+
+/// Box a future returned by a `Source`
+pub fn boxed<'a, T>(
+    future: impl Future<Output = Result<T, SourceError>> + Send + 'a,
+) -> SourceFuture<'a, T> {
+    Box::pin(future)
+}
+
+pub trait Source: Send + Sync {
+    /// Name shown to the user
+    fn name(&self) -> String;
+
+    fn search<'a>(
+        &'a self,
+        client: &'a reqwest::Client,
+        search: &'a SearchQuery,
+        config: &'a SourceConfig,
+        extra: &'a SourceExtraConfig,
+    ) -> SourceFuture<'a, SourceResponse>;
+    fn sort<'a>(
+        &'a self,
+        client: &'a reqwest::Client,
+        search: &'a SearchQuery,
+        config: &'a SourceConfig,
+        extra: &'a SourceExtraConfig,
+    ) -> SourceFuture<'a, SourceResponse> {
+        self.search(client, search, config, extra)
+    }
+    fn filter<'a>(
+        &'a self,
+        client: &'a reqwest::Client,
+        search: &'a SearchQuery,
+        config: &'a SourceConfig,
+        extra: &'a SourceExtraConfig,
+    ) -> SourceFuture<'a, SourceResponse> {
+        self.search(client, search, config, extra)
+    }
+    fn categorize<'a>(
+        &'a self,
+        client: &'a reqwest::Client,
+        search: &'a SearchQuery,
+        config: &'a SourceConfig,
+        extra: &'a SourceExtraConfig,
+    ) -> SourceFuture<'a, SourceResponse> {
+        self.search(client, search, config, extra)
+    }
+    fn solve<'a>(
+        &'a self,
+        _solution: String,
+        client: &'a reqwest::Client,
+        search: &'a SearchQuery,
+        config: &'a SourceConfig,
+        extra: &'a SourceExtraConfig,
+    ) -> SourceFuture<'a, SourceResponse> {
+        self.search(client, search, config, extra)
+    }
+    /// Fetch the post page of an item
+    fn details<'a>(
+        &'a self,
+        _client: &'a reqwest::Client,
+        _item: &'a Item,
+        _config: &'a SourceConfig,
+    ) -> SourceFuture<'a, ItemDetails> {
+        let name = self.name();
+        boxed(async move {
+            Err(SourceError::Other(format!(
+                "Details are not supported for {}",
+                name
+            )))
+        })
+    }
+    fn info(&self) -> SourceInfo;
+    /// Fill in the defaults of the config of the source
+    fn load_config(&self, _config: &mut SourceConfig) {}
+
+    fn default_category(&self, _config: &SourceConfig) -> usize {
+        0
+    }
+    fn default_sort(&self, _config: &SourceConfig) -> SelectedSort {
+        SelectedSort::default()
+    }
+    fn default_filter(&self, _config: &SourceConfig) -> usize {
+        0
+    }
+    fn default_search(&self, _config: &SourceConfig) -> String {
+        String::new()
+    }
+
+    /// Columns of the results, the same as for Nyaa by default
     fn format_table(
+        &self,
         items: &[Item],
-        sort: &SearchQuery,
-        config: &SourceConfig,
+        search: &SearchQuery,
+        _config: &SourceConfig,
+        extra: &SourceExtraConfig,
         theme: &Theme,
-    ) -> ResultTable;
+    ) -> ResultTable {
+        nyaa_table(items.into(), theme, &search.sort, &None, extra)
+    }
+}
+
+/// Stands in for a source which is not registered, like a custom source removed from the config
+struct UnknownSource(&'static str);
+
+impl Source for UnknownSource {
+    fn name(&self) -> String {
+        self.0.to_owned()
+    }
+
+    fn search<'a>(
+        &'a self,
+        _client: &'a reqwest::Client,
+        _search: &'a SearchQuery,
+        _config: &'a SourceConfig,
+        _extra: &'a SourceExtraConfig,
+    ) -> SourceFuture<'a, SourceResponse> {
+        boxed(async move {
+            Err(SourceError::config(
+                "default_source",
+                format!("Unknown source \"{}\"", self.0),
+            ))
+        })
+    }
+
+    fn info(&self) -> SourceInfo {
+        AllSource.info()
+    }
+}
+
+/// Every source which can be selected, keyed by name in the order they were registered
+pub struct SourceRegistry {
+    sources: IndexMap<&'static str, Arc<dyn Source>>,
+}
+
+impl Default for SourceRegistry {
+    fn default() -> Self {
+        let mut registry = SourceRegistry {
+            sources: IndexMap::new(),
+        };
+        registry.register("Nyaa", NyaaHtmlSource);
+        registry.register("SukebeiNyaa", SukebeiHtmlSource);
+        registry.register("TorrentGalaxy", TorrentGalaxyHtmlSource);
+        registry.register("Torznab", TorznabSource);
+        registry.register("All", AllSource);
+        registry
+    }
+}
+
+impl SourceRegistry {
+    /// Register a source under `name`, replacing any source already registered with that name
+    pub fn register(&mut self, name: &str, source: impl Source + 'static) -> Sources {
+        self.register_shared(name, Arc::new(source))
+    }
+
+    pub fn register_shared(&mut self, name: &str, source: Arc<dyn Source>) -> Sources {
+        let src = Sources::new(name);
+        self.sources.insert(src.0, source);
+        src
+    }
+
+    pub fn unregister(&mut self, src: Sources) {
+        self.sources.shift_remove(src.0);
+    }
+
+    pub fn get(&self, src: Sources) -> Option<Arc<dyn Source>> {
+        self.sources.get(src.0).cloned()
+    }
+
+    pub fn sources(&self) -> Vec<Sources> {
+        self.sources.keys().map(|k| Sources(k)).collect()
+    }
+}
+
+/// The registry shared by the app, which starts out with the built-in sources
+pub fn registry() -> &'static RwLock<SourceRegistry> {
+    static REGISTRY: OnceLock<RwLock<SourceRegistry>> = OnceLock::new();
+    REGISTRY.get_or_init(|| RwLock::new(SourceRegistry::default()))
+}
+
+/// Get a static copy of a string, only leaking each distinct string once
+pub fn intern(s: &str) -> &'static str {
+    static INTERNED: Mutex<Vec<&str>> = Mutex::new(Vec::new());
+    let mut interned = INTERNED.lock().unwrap_or_else(|e| e.into_inner());
+    match interned.iter().find(|i| **i == s) {
+        Some(i) => i,
+        None => {
+            let i: &'static str = Box::leak(s.to_owned().into_boxed_str());
+            interned.push(i);
+            i
+        }
+    }
 }
 
 impl Sources {
+    pub fn new(name: &str) -> Sources {
+        Sources(intern(name))
+    }
+
+    /// Every registered source
+    pub fn all() -> Vec<Sources> {
+        registry()
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .sources()
+    }
+
+    /// Name the source is registered and stored in the config with
+    pub fn id(&self) -> &'static str {
+        self.0
+    }
+
+    pub fn source(self) -> Arc<dyn Source> {
+        registry()
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(self)
+            .unwrap_or_else(|| Arc::new(UnknownSource(self.0)))
+    }
+
     pub async fn load(
         &self,
         load_type: LoadType,
@@ -239,60 +822,81 @@ impl Sources {
         search: &SearchQuery,
         config: &SourceConfig,
         extra: &SourceExtraConfig,
-    ) -> Result<SourceResponse, Box<dyn Error + Send + Sync>> {
-        match self {
-            Sources::Nyaa => match load_type {
-                LoadType::Searching | LoadType::Sourcing => {
-                    NyaaHtmlSource::search(client, search, config, extra).await
-                }
-                LoadType::Sorting => NyaaHtmlSource::sort(client, search, config, extra).await,
-                LoadType::Filtering => NyaaHtmlSource::filter(client, search, config, extra).await,
-                LoadType::Categorizing => {
-                    NyaaHtmlSource::categorize(client, search, config, extra).await
-                }
-                LoadType::SolvingCaptcha(solution) => {
-                    NyaaHtmlSource::solve(solution, client, search, config, extra).await
-                }
-                LoadType::Downloading | LoadType::Batching => unreachable!(),
-            },
-            Sources::SukebeiNyaa => match load_type {
-                LoadType::Searching | LoadType::Sourcing => {
-                    SukebeiHtmlSource::search(client, search, config, extra).await
-                }
-                LoadType::Sorting => SukebeiHtmlSource::sort(client, search, config, extra).await,
-                LoadType::Filtering => {
-                    SukebeiHtmlSource::filter(client, search, config, extra).await
-                }
-                LoadType::Categorizing => {
-                    SukebeiHtmlSource::categorize(client, search, config, extra).await
-                }
-                LoadType::SolvingCaptcha(solution) => {
-                    SukebeiHtmlSource::solve(solution, client, search, config, extra).await
-                }
-                LoadType::Downloading | LoadType::Batching => unreachable!(),
-            },
-            Sources::TorrentGalaxy => match load_type {
-                LoadType::Searching | LoadType::Sourcing => {
-                    TorrentGalaxyHtmlSource::search(client, search, config, extra).await
-                }
-                LoadType::Sorting => {
-                    TorrentGalaxyHtmlSource::sort(client, search, config, extra).await
-                }
-                LoadType::Filtering => {
-                    TorrentGalaxyHtmlSource::filter(client, search, config, extra).await
-                }
-                LoadType::Categorizing => {
-                    TorrentGalaxyHtmlSource::categorize(client, search, config, extra).await
-                }
-                LoadType::SolvingCaptcha(solution) => {
-                    TorrentGalaxyHtmlSource::solve(solution, client, search, config, extra).await
+    ) -> Result<SourceResponse, SourceError> {
+        let src = self.source();
+        let mut res = match load_type {
+            LoadType::Searching | LoadType::Sourcing | LoadType::Reloading => {
+                src.search(client, search, config, extra).await
+            }
+            LoadType::Sorting => src.sort(client, search, config, extra).await,
+            LoadType::Filtering => src.filter(client, search, config, extra).await,
+            LoadType::Categorizing => src.categorize(client, search, config, extra).await,
+            LoadType::SolvingCaptcha(solution) => {
+                src.solve(solution, client, search, config, extra).await
+            }
+            LoadType::Downloading
+            | LoadType::Batching
+            | LoadType::Details
+            | LoadType::LocalFiltering
+            | LoadType::AllPages(_)
+            | LoadType::Appending => {
+                unreachable!()
+            }
+        }?;
+        // Release names are parsed for every source, so results can also be sorted by their parts
+        match &mut res {
+            SourceResponse::Results(res) => {
+                release::annotate(&mut res.items);
+                let sort = src.info().sorts.get(search.sort.sort).cloned();
+                if let Some(sort) = sort.filter(|s| s.parse::<ReleaseField>().is_ok()) {
+                    pages::sort_items(&mut res.items, &sort, search.sort.dir);
                 }
-                LoadType::Downloading | LoadType::Batching => unreachable!(),
-            },
+            }
+            #[cfg(feature = "captcha")]
+            SourceResponse::Captcha(_) => {}
         }
+        Ok(res)
+    }
+
+    /// The source an item was loaded from, which differs from `self` for merged results
+    pub fn of_item(self, item: &Item) -> Sources {
+        item.extra
+            .get("source")
+            .and_then(|s| {
+                Sources::all()
+                    .into_iter()
+                    .find(|v| &v.to_string() == s || v.id() == s)
+            })
+            .unwrap_or(self)
+    }
+
+    /// Fetch the post page of an item, if supported by the source it was loaded from
+    pub async fn details(
+        self,
+        client: &reqwest::Client,
+        item: &Item,
+        config: &SourceConfig,
+    ) -> Result<ItemDetails, SourceError> {
+        self.of_item(item)
+            .source()
+            .details(client, item, config)
+            .await
     }
 
     pub fn apply(self, ctx: &mut Context, w: &mut Widgets) {
+        self.apply_info(ctx, w);
+
+        w.search.input.input = self.default_search(&ctx.config.sources);
+        w.search
+            .input
+            .set_cursor(w.search.input.input.chars().count());
+
+        // Go back to first page when changing source
+        ctx.page = 1;
+    }
+
+    /// Show the categories, sorts and filters of the source, selecting its defaults
+    pub fn apply_info(self, ctx: &mut Context, w: &mut Widgets) {
         ctx.src_info = self.info();
         w.category.selected = self.default_category(&ctx.config.sources);
 
@@ -305,62 +909,32 @@ impl Sources {
         w.sort.table.select(w.sort.selected.sort);
         w.filter.selected = self.default_filter(&ctx.config.sources);
         w.filter.table.select(w.filter.selected);
-
-        w.search.input.input = self.default_search(&ctx.config.sources);
-        w.search
-            .input
-            .set_cursor(w.search.input.input.chars().count());
-
-        // Go back to first page when changing source
-        ctx.page = 1;
     }
 
     pub fn info(self) -> SourceInfo {
-        match self {
-            Sources::Nyaa => NyaaHtmlSource::info(),
-            Sources::SukebeiNyaa => SukebeiHtmlSource::info(),
-            Sources::TorrentGalaxy => TorrentGalaxyHtmlSource::info(),
-        }
+        self.source().info()
     }
 
+    /// Load the config of the source, registering the sources declared in the config first
     pub fn load_config(self, config: &mut SourceConfig) {
-        match self {
-            Sources::Nyaa => NyaaHtmlSource::load_config(config),
-            Sources::SukebeiNyaa => SukebeiHtmlSource::load_config(config),
-            Sources::TorrentGalaxy => TorrentGalaxyHtmlSource::load_config(config),
-        };
+        custom::register(config);
+        self.source().load_config(config);
     }
 
     pub fn default_category(self, config: &SourceConfig) -> usize {
-        match self {
-            Sources::Nyaa => NyaaHtmlSource::default_category(config),
-            Sources::SukebeiNyaa => SukebeiHtmlSource::default_category(config),
-            Sources::TorrentGalaxy => TorrentGalaxyHtmlSource::default_category(config),
-        }
+        self.source().default_category(config)
     }
 
     pub fn default_sort(self, config: &SourceConfig) -> SelectedSort {
-        match self {
-            Sources::Nyaa => NyaaHtmlSource::default_sort(config),
-            Sources::SukebeiNyaa => SukebeiHtmlSource::default_sort(config),
-            Sources::TorrentGalaxy => TorrentGalaxyHtmlSource::default_sort(config),
-        }
+        self.source().default_sort(config)
     }
 
     pub fn default_filter(self, config: &SourceConfig) -> usize {
-        match self {
-            Sources::Nyaa => NyaaHtmlSource::default_filter(config),
-            Sources::SukebeiNyaa => SukebeiHtmlSource::default_filter(config),
-            Sources::TorrentGalaxy => TorrentGalaxyHtmlSource::default_filter(config),
-        }
+        self.source().default_filter(config)
     }
 
     pub fn default_search(self, config: &SourceConfig) -> String {
-        match self {
-            Sources::Nyaa => NyaaHtmlSource::default_search(config),
-            Sources::SukebeiNyaa => SukebeiHtmlSource::default_search(config),
-            Sources::TorrentGalaxy => TorrentGalaxyHtmlSource::default_search(config),
-        }
+        self.source().default_search(config)
     }
 
     pub fn format_table(
@@ -368,14 +942,10 @@ impl Sources {
         items: &[Item],
         search: &SearchQuery,
         config: &SourceConfig,
+        extra: &SourceExtraConfig,
         theme: &Theme,
     ) -> ResultTable {
-        match self {
-            Sources::Nyaa => NyaaHtmlSource::format_table(items, search, config, theme),
-            Sources::SukebeiNyaa => SukebeiHtmlSource::format_table(items, search, config, theme),
-            Sources::TorrentGalaxy => {
-                TorrentGalaxyHtmlSource::format_table(items, search, config, theme)
-            }
-        }
+        self.source()
+            .format_table(items, search, config, extra, theme)
     }
 }
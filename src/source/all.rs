@@ -0,0 +1,307 @@
+use std::{cmp::max, cmp::Ordering, collections::HashMap};
+
+use ratatui::{
+    layout::{Alignment, Constraint},
+    style::Stylize as _,
+};
+use serde::{Deserialize, Serialize};
+use strum::VariantArray as _;
+
+use crate::{
+    cats,
+    results::{ResultColumn, ResultHeader, ResultResponse, ResultRow, ResultTable},
+    sync::SearchQuery,
+    theme::Theme,
+    util::conv::{shorten_number, to_size},
+    widget::sort::{SelectedSort, SortDir},
+};
+
+use super::{
+    boxed,
+    nyaa_html::{NyaaHtmlSource, NyaaSort},
+    sukebei_nyaa::SukebeiHtmlSource,
+    torrent_galaxy::{TgxSort, TorrentGalaxyHtmlSource},
+    InfoHash, Item, ItemType, Source, SourceConfig, SourceError, SourceExtraConfig, SourceFuture,
+    SourceInfo, SourceResponse, Sources,
+};
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct AllConfig {
+    /// Also search Sukebei, disabled by default
+    pub sukebei: bool,
+    pub default_sort: NyaaSort,
+    pub default_sort_dir: SortDir,
+    pub default_search: String,
+}
+
+impl Default for AllConfig {
+    fn default() -> Self {
+        Self {
+            sukebei: false,
+            default_sort: NyaaSort::Date,
+            default_sort_dir: SortDir::Desc,
+            default_search: Default::default(),
+        }
+    }
+}
+
+/// Searches Nyaa, TorrentGalaxy and optionally Sukebei at once, merging the results
+pub struct AllSource;
+
+/// Sort merged results locally, since each source sorted only its own results
+pub fn sort_items(items: &mut [Item], sort: SelectedSort) {
+    let f: fn(&Item, &Item) -> Ordering = match NyaaSort::from_repr(sort.sort) {
+        Some(NyaaSort::Downloads) => |a, b| b.downloads.cmp(&a.downloads),
+        Some(NyaaSort::Seeders) => |a, b| b.seeders.cmp(&a.seeders),
+        Some(NyaaSort::Leechers) => |a, b| b.leechers.cmp(&a.leechers),
+        Some(NyaaSort::Size) => |a, b| b.size.cmp(&a.size),
+        _ => |a, b| b.date.cmp(&a.date),
+    };
+    items.sort_by(f);
+    if sort.dir == SortDir::Asc {
+        items.reverse();
+    }
+}
+
+/// Merge the items of each source, keeping the copy with the most seeders when the same torrent
+/// is found more than once
+pub fn merge_items(results: Vec<(Sources, Vec<Item>)>) -> Vec<Item> {
+    let mut items: Vec<Item> = vec![];
+    let mut hashes: HashMap<InfoHash, usize> = HashMap::new();
+    for (src, src_items) in results {
+        for mut item in src_items {
+            item.extra.insert("source".to_owned(), src.to_string());
+            let hash = item.hash;
+            match hash.as_ref().and_then(|h| hashes.get(h)) {
+                Some(&idx) => {
+                    if item.seeders > items[idx].seeders {
+                        items[idx] = item;
+                    }
+                }
+                None => {
+                    if let Some(hash) = hash {
+                        hashes.insert(hash, items.len());
+                    }
+                    items.push(item);
+                }
+            }
+        }
+    }
+    items
+}
+
+/// Short name of the source an item was merged from
+fn source_label(item: &Item) -> &'static str {
+    match Sources::All.of_item(item) {
+        Sources::SukebeiNyaa => "Sukebei",
+        Sources::TorrentGalaxy => "TGX",
+        _ => "Nyaa",
+    }
+}
+
+impl Source for AllSource {
+    fn name(&self) -> String {
+        "All sources".to_owned()
+    }
+
+    fn search<'a>(
+        &'a self,
+        client: &'a reqwest::Client,
+        search: &'a SearchQuery,
+        config: &'a SourceConfig,
+        extra: &'a SourceExtraConfig,
+    ) -> SourceFuture<'a, SourceResponse> {
+        boxed(async move {
+            let all = config.all.to_owned().unwrap_or_default();
+            // Each source is searched in all of its categories without filters
+            let nyaa_search = SearchQuery {
+                category: 0,
+                filter: 0,
+                ..search.to_owned()
+            };
+            // TorrentGalaxy has no download count, fall back to sorting by date
+            let tgx_sort = NyaaSort::from_repr(search.sort.sort)
+                .and_then(|s| {
+                    TgxSort::VARIANTS
+                        .iter()
+                        .find(|t| t.to_string() == s.to_string())
+                })
+                .copied()
+                .unwrap_or(TgxSort::Date);
+            let tgx_search = SearchQuery {
+                sort: SelectedSort {
+                    sort: tgx_sort as usize,
+                    dir: search.sort.dir,
+                },
+                ..nyaa_search.to_owned()
+            };
+
+            let (nyaa, sukebei, tgx) = tokio::join!(
+                NyaaHtmlSource.sort(client, &nyaa_search, config, extra),
+                async {
+                    match all.sukebei {
+                        true => Some(
+                            SukebeiHtmlSource
+                                .sort(client, &nyaa_search, config, extra)
+                                .await,
+                        ),
+                        false => None,
+                    }
+                },
+                TorrentGalaxyHtmlSource.search(client, &tgx_search, config, extra),
+            );
+
+            let mut results = vec![];
+            let mut errors = vec![];
+            let mut last_page = 1;
+            let mut total_results = 0;
+            let responses = [
+                (Sources::Nyaa, Some(nyaa)),
+                (Sources::SukebeiNyaa, sukebei),
+                (Sources::TorrentGalaxy, Some(tgx)),
+            ];
+            for (src, res) in responses {
+                match res {
+                    Some(Ok(SourceResponse::Results(res))) => {
+                        // Select the source on its own to save the broken page
+                        if let Some(report) = res.report.filter(|r| r.is_broken()) {
+                            errors
+                                .push(format!("{} may have changed its layout:\n{}", src, report));
+                        }
+                        last_page = max(last_page, res.last_page);
+                        total_results += res.total_results;
+                        results.push((src, res.items));
+                    }
+                    #[cfg(feature = "captcha")]
+                    Some(Ok(SourceResponse::Captcha(_))) => errors.push(format!(
+                        "{}:\nA captcha must be solved, select it as the source to solve it",
+                        src
+                    )),
+                    Some(Err(e)) => errors.push(format!("{}:\n{}", src, e)),
+                    None => {}
+                }
+            }
+            if results.is_empty() {
+                return Err(SourceError::Other(errors.join("\n\n")));
+            }
+
+            let mut items = merge_items(results);
+            sort_items(&mut items, search.sort);
+            Ok(SourceResponse::Results(ResultResponse {
+                items,
+                last_page,
+                total_results,
+                errors,
+                ..Default::default()
+            }))
+        })
+    }
+
+    fn info(&self) -> SourceInfo {
+        let cats = cats! {
+            "All Categories" => {
+                0 => ("---", "All Categories", "AllCategories", fg);
+            }
+        };
+        SourceInfo {
+            cats,
+            filters: vec!["No Filter".to_owned()],
+            sorts: NyaaSort::VARIANTS.iter().map(ToString::to_string).collect(),
+        }
+    }
+
+    fn load_config(&self, config: &mut SourceConfig) {
+        if config.all.is_none() {
+            config.all = Some(AllConfig::default());
+        }
+    }
+
+    fn default_category(&self, _cfg: &SourceConfig) -> usize {
+        0
+    }
+
+    fn default_sort(&self, cfg: &SourceConfig) -> SelectedSort {
+        cfg.all
+            .as_ref()
+            .map(|c| SelectedSort {
+                sort: c.default_sort as usize,
+                dir: c.default_sort_dir,
+            })
+            .unwrap_or_default()
+    }
+
+    fn default_filter(&self, _cfg: &SourceConfig) -> usize {
+        0
+    }
+
+    fn default_search(&self, cfg: &SourceConfig) -> String {
+        cfg.all
+            .as_ref()
+            .map(|c| c.default_search.to_owned())
+            .unwrap_or_default()
+    }
+
+    fn format_table(
+        &self,
+        items: &[Item],
+        search: &SearchQuery,
+        _config: &SourceConfig,
+        extra: &SourceExtraConfig,
+        theme: &Theme,
+    ) -> ResultTable {
+        let dates: Vec<String> = items.iter().map(|i| extra.format_item_date(i)).collect();
+        let raw_date_width = dates.iter().map(|d| d.len()).max().unwrap_or_default() as u16;
+        let date_width = max(raw_date_width, 6);
+
+        let header = ResultHeader::new([
+            ResultColumn::Normal("Source".to_owned(), Constraint::Length(7)),
+            ResultColumn::Normal("Cat".to_owned(), Constraint::Length(3)),
+            ResultColumn::Normal("Name".to_owned(), Constraint::Min(3)),
+            ResultColumn::Sorted("Size".to_owned(), 9, NyaaSort::Size as u32),
+            ResultColumn::Sorted("Date".to_owned(), date_width, NyaaSort::Date as u32),
+            ResultColumn::Sorted("".to_owned(), 4, NyaaSort::Seeders as u32),
+            ResultColumn::Sorted("".to_owned(), 4, NyaaSort::Leechers as u32),
+            ResultColumn::Sorted("".to_owned(), 5, NyaaSort::Downloads as u32),
+        ]);
+        let binding = header.get_binding();
+        let align = [
+            Alignment::Left,
+            Alignment::Left,
+            Alignment::Left,
+            Alignment::Right,
+            Alignment::Left,
+            Alignment::Right,
+            Alignment::Right,
+            Alignment::Left,
+        ];
+        let rows: Vec<ResultRow> = items
+            .iter()
+            .zip(dates)
+            .map(|(item, date)| {
+                ResultRow::new([
+                    source_label(item).fg(theme.info),
+                    item.icon.label.fg((item.icon.color)(theme)),
+                    item.title.to_owned().fg(match item.item_type {
+                        ItemType::Trusted => theme.success,
+                        ItemType::Remake => theme.error,
+                        ItemType::None => theme.fg,
+                    }),
+                    to_size(item.size).fg(theme.fg),
+                    date.fg(theme.fg),
+                    item.seeders.to_string().fg(theme.success),
+                    item.leechers.to_string().fg(theme.error),
+                    shorten_number(item.downloads).fg(theme.fg),
+                ])
+                .aligned(align)
+                .fg(theme.fg)
+            })
+            .collect();
+
+        ResultTable {
+            headers: header.get_row(search.sort.dir, search.sort.sort as u32),
+            rows,
+            binding,
+        }
+    }
+}
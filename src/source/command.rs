@@ -0,0 +1,384 @@
+use std::{
+    error::Error,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Condvar, Mutex,
+    },
+    thread,
+    time::Duration,
+};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    results::ResultResponse,
+    sync::SearchQuery,
+    theme::Theme,
+    util::cmd::CommandBuilder,
+    widget::{
+        category::{CatEntry, CatStruct},
+        sort::{SelectedSort, SortDir},
+    },
+};
+
+use super::{
+    boxed,
+    custom::category_color,
+    intern,
+    nyaa_html::{nyaa_table, NyaaColumns},
+    InfoHash, Item, ResultTable, Source, SourceConfig, SourceExtraConfig, SourceFuture, SourceInfo,
+    SourceResponse,
+};
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct CommandSourceConfig {
+    pub name: String,
+    /// Reads a search as JSON on stdin and writes the results as JSON to stdout
+    pub cmd: String,
+    pub shell_cmd: String,
+    /// Name of a sort returned by `--describe`
+    pub default_sort: String,
+    pub default_sort_dir: SortDir,
+    /// Name of a filter returned by `--describe`
+    pub default_filter: String,
+    pub default_category: String,
+    pub default_search: String,
+    /// Seconds to wait for the command before killing it
+    pub timeout: u64,
+    pub columns: Option<NyaaColumns>,
+}
+
+impl Default for CommandSourceConfig {
+    fn default() -> Self {
+        Self {
+            name: Default::default(),
+            cmd: Default::default(),
+            shell_cmd: CommandBuilder::default_shell(),
+            default_sort: Default::default(),
+            default_sort_dir: SortDir::Desc,
+            default_filter: Default::default(),
+            default_category: "AllCategories".to_owned(),
+            default_search: Default::default(),
+            timeout: 30,
+            columns: None,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct CategoryDescription {
+    pub id: usize,
+    pub name: String,
+    pub icon: String,
+    pub color: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct CategoryGroupDescription {
+    pub name: String,
+    pub entries: Vec<CategoryDescription>,
+}
+
+/// Output of `cmd --describe`
+#[derive(Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct SourceDescription {
+    pub categories: Vec<CategoryGroupDescription>,
+    pub sorts: Vec<String>,
+    pub filters: Vec<String>,
+}
+
+impl From<SourceDescription> for SourceInfo {
+    fn from(desc: SourceDescription) -> Self {
+        let mut cats: Vec<CatStruct> = desc
+            .categories
+            .into_iter()
+            .map(|group| CatStruct {
+                name: group.name,
+                entries: group
+                    .entries
+                    .into_iter()
+                    .map(|e| {
+                        let icon: String = e.icon.chars().take(3).collect();
+                        CatEntry::new(
+                            &e.name,
+                            &e.name.replace(' ', ""),
+                            e.id,
+                            intern(&format!("{:<3}", icon)),
+                            category_color(&e.color),
+                        )
+                    })
+                    .collect(),
+            })
+            .filter(|group| !group.entries.is_empty())
+            .collect();
+        if cats.is_empty() {
+            cats.push(CatStruct {
+                name: "All Categories".to_owned(),
+                entries: vec![CatEntry::new(
+                    "All Categories",
+                    "AllCategories",
+                    0,
+                    "---",
+                    |t| t.fg,
+                )],
+            });
+        }
+        let or_default = |v: Vec<String>, default: &str| match v.is_empty() {
+            true => vec![default.to_owned()],
+            false => v,
+        };
+        SourceInfo {
+            cats,
+            filters: or_default(desc.filters, "No Filter"),
+            sorts: or_default(desc.sorts, "Default"),
+        }
+    }
+}
+
+/// Run `cmd --describe` to get the categories, sorts and filters of a command
+pub fn describe(cmd: &CommandSourceConfig) -> Result<SourceInfo, Box<dyn Error + Send + Sync>> {
+    let output = CommandBuilder::new(format!("{} --describe", cmd.cmd)).output(
+        cmd.shell_cmd.to_owned(),
+        None,
+        Some(Duration::from_secs(cmd.timeout)),
+        None,
+    )?;
+    parse_description(&output)
+}
+
+/// Description of a command, loaded in the background once per config load. Failures are kept
+/// too, so a broken command isn't run again until the config changes.
+#[derive(Default)]
+struct Description {
+    described: Mutex<Option<Result<SourceInfo, String>>>,
+    done: Condvar,
+}
+
+impl Description {
+    fn start(cmd: &CommandSourceConfig) -> Arc<Description> {
+        let description = Arc::new(Description::default());
+        let (cmd, desc) = (cmd.to_owned(), description.clone());
+        thread::spawn(move || {
+            let described = describe(&cmd).map_err(|e| e.to_string());
+            *desc.described.lock().unwrap_or_else(|e| e.into_inner()) = Some(described);
+            desc.done.notify_all();
+            DESCRIBED
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .push(cmd.name.trim().to_owned());
+        });
+        description
+    }
+
+    /// The description if it loaded, without waiting for it
+    fn info(&self) -> Option<SourceInfo> {
+        match &*self.described.lock().unwrap_or_else(|e| e.into_inner()) {
+            Some(Ok(info)) => Some(info.clone()),
+            _ => None,
+        }
+    }
+
+    /// Wait for the command to be described, which is bounded by its timeout
+    fn wait(&self) {
+        let described = self.described.lock().unwrap_or_else(|e| e.into_inner());
+        let _described = self
+            .done
+            .wait_while(described, |d| d.is_none())
+            .unwrap_or_else(|e| e.into_inner());
+    }
+}
+
+/// Descriptions of the command sources declared in the config, by name
+static DESCRIPTIONS: Mutex<Vec<(String, Arc<Description>)>> = Mutex::new(Vec::new());
+
+/// Names of the command sources described since [`take_described`] was last called
+static DESCRIBED: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+/// Forget the descriptions of the previous config, before its sources are registered again
+pub fn clear_descriptions() {
+    DESCRIPTIONS
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .clear();
+}
+
+/// Wait for the command source named `name` to be described, doing nothing for other sources
+pub fn wait_described(name: &str) {
+    let description = DESCRIPTIONS
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .iter()
+        .find(|(n, _)| n == name)
+        .map(|(_, d)| d.clone());
+    if let Some(description) = description {
+        description.wait();
+    }
+}
+
+/// Names of the command sources which were described since the last call, so the categories,
+/// sorts and filters shown for them can be refreshed
+pub fn take_described() -> Vec<String> {
+    std::mem::take(&mut *DESCRIBED.lock().unwrap_or_else(|e| e.into_inner()))
+}
+
+/// Sets its flag when dropped, so the command of an aborted search is killed
+struct KillOnDrop(Arc<AtomicBool>);
+
+impl Drop for KillOnDrop {
+    fn drop(&mut self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+}
+
+pub fn parse_description(output: &str) -> Result<SourceInfo, Box<dyn Error + Send + Sync>> {
+    serde_json::from_str::<SourceDescription>(output)
+        .map(Into::into)
+        .map_err(|e| format!("Invalid description:\n{}", e).into())
+}
+
+/// Parse the JSON results written by a command
+pub fn parse_results(
+    output: &str,
+    info: &SourceInfo,
+) -> Result<ResultResponse, Box<dyn Error + Send + Sync>> {
+    let mut res: ResultResponse =
+        serde_json::from_str(output).map_err(|e| format!("Invalid results:\n{}", e))?;
+    for item in res.items.iter_mut() {
+        item.icon = info.clone().entry_from_id(item.category).icon;
+        if item.file_name.is_empty() {
+            item.file_name = format!("{}.torrent", item.id);
+        }
+        if item.hash.is_none() {
+            item.hash = InfoHash::from_magnet(&item.magnet_link);
+        }
+        // Commands written before dates were typed give the date as a `timestamp` extra
+        let timestamp = item
+            .extra
+            .get("timestamp")
+            .and_then(|t| t.parse::<i64>().ok())
+            .and_then(|t| DateTime::<Utc>::from_timestamp(t, 0));
+        if let (true, Some(date)) = (item.date == DateTime::<Utc>::default(), timestamp) {
+            item.date = date;
+        }
+    }
+    res.last_page = res.last_page.max(1);
+    if res.total_results == 0 {
+        res.total_results = res.items.len();
+    }
+    Ok(res)
+}
+
+/// A source backed by an external command declared in `[[source.command]]`
+pub struct CommandSource {
+    config: CommandSourceConfig,
+    description: Arc<Description>,
+}
+
+impl CommandSource {
+    /// Describe the command in the background. Until it is described, or if that fails, the
+    /// source has no categories.
+    pub fn new(config: CommandSourceConfig) -> Self {
+        let description = Description::start(&config);
+        DESCRIPTIONS
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .push((config.name.trim().to_owned(), description.clone()));
+        CommandSource {
+            config,
+            description,
+        }
+    }
+}
+
+impl Source for CommandSource {
+    fn name(&self) -> String {
+        self.config.name.trim().to_owned()
+    }
+
+    fn search<'a>(
+        &'a self,
+        _client: &'a reqwest::Client,
+        search: &'a SearchQuery,
+        _config: &'a SourceConfig,
+        _extra: &'a SourceExtraConfig,
+    ) -> SourceFuture<'a, SourceResponse> {
+        boxed(async move {
+            let cmd = self.config.to_owned();
+            let input = serde_json::to_string(search)?;
+            let description = self.description.clone();
+            let cancel = Arc::new(AtomicBool::new(false));
+            let _kill = KillOnDrop(cancel.clone());
+            let output = tokio::task::spawn_blocking(move || {
+                // Categories are needed to read the results
+                description.wait();
+                CommandBuilder::new(cmd.cmd).output(
+                    cmd.shell_cmd,
+                    Some(input),
+                    Some(Duration::from_secs(cmd.timeout)),
+                    Some(&cancel),
+                )
+            })
+            .await??;
+            Ok(SourceResponse::Results(parse_results(
+                &output,
+                &self.info(),
+            )?))
+        })
+    }
+
+    fn info(&self) -> SourceInfo {
+        self.description
+            .info()
+            .unwrap_or_else(|| SourceDescription::default().into())
+    }
+
+    fn default_category(&self, _cfg: &SourceConfig) -> usize {
+        self.info().entry_from_cfg(&self.config.default_category).id
+    }
+
+    fn default_sort(&self, _cfg: &SourceConfig) -> SelectedSort {
+        SelectedSort {
+            sort: self
+                .info()
+                .sorts
+                .iter()
+                .position(|s| *s == self.config.default_sort)
+                .unwrap_or_default(),
+            dir: self.config.default_sort_dir,
+        }
+    }
+
+    fn default_filter(&self, _cfg: &SourceConfig) -> usize {
+        self.info()
+            .filters
+            .iter()
+            .position(|f| *f == self.config.default_filter)
+            .unwrap_or_default()
+    }
+
+    fn default_search(&self, _cfg: &SourceConfig) -> String {
+        self.config.default_search.to_owned()
+    }
+
+    fn format_table(
+        &self,
+        items: &[Item],
+        search: &SearchQuery,
+        _config: &SourceConfig,
+        extra: &SourceExtraConfig,
+        theme: &Theme,
+    ) -> ResultTable {
+        nyaa_table(
+            items.into(),
+            theme,
+            &search.sort,
+            &self.config.columns,
+            extra,
+        )
+    }
+}
@@ -0,0 +1,185 @@
+use std::{
+    error::Error,
+    fs,
+    io::{BufReader, Write as _},
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, RwLock,
+    },
+};
+
+use chrono::Utc;
+use cookie_store::{CookieStore, RawCookie};
+use reqwest::{header::HeaderValue, Url};
+
+use crate::util::conv::add_protocol;
+
+use super::SourceConfig;
+
+pub static COOKIES_FILE: &str = "cookies.json";
+
+/// Cookies shared by every request, which can be saved between sessions so captchas and
+/// DDoS protection don't have to be passed again
+#[derive(Default)]
+pub struct CookieJar {
+    store: RwLock<CookieStore>,
+    changed: AtomicBool,
+}
+
+impl CookieJar {
+    /// Load the cookies saved at `path`, starting empty if there are none
+    pub fn load(path: &Path) -> Result<Self, Box<dyn Error>> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let file = fs::File::open(path)?;
+        let store = CookieStore::load_json(BufReader::new(file)).map_err(|e| e.to_string())?;
+        Ok(Self {
+            store: RwLock::new(store),
+            changed: AtomicBool::new(false),
+        })
+    }
+
+    /// Save the unexpired cookies which are not limited to a session, if any changed
+    pub fn save(&self, path: &Path) -> Result<(), Box<dyn Error>> {
+        if !self.changed.swap(false, Ordering::Relaxed) {
+            return Ok(());
+        }
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let mut content = vec![];
+        self.store
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .save_json(&mut content)
+            .map_err(|e| e.to_string())?;
+        let mut options = fs::OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        // Cookies can log in to a site, so only the user may read them
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+        let mut file = options.open(path)?;
+        // The mode only applies to new files, older ones may still be readable by others
+        #[cfg(unix)]
+        file.set_permissions(std::os::unix::fs::PermissionsExt::from_mode(0o600))?;
+        file.write_all(&content)?;
+        Ok(())
+    }
+
+    /// Import cookies from a Netscape `cookies.txt` file, as exported from a browser.
+    /// Only cookies for one of `hosts` or their subdomains are kept, returning how many were.
+    pub fn import(&self, content: &str, hosts: &[String]) -> usize {
+        let mut store = self.store.write().unwrap_or_else(|e| e.into_inner());
+        let mut count = 0;
+        for line in content.lines() {
+            // Cookies only sent over http are prefixed, other comments are skipped
+            let line = line.strip_prefix("#HttpOnly_").unwrap_or(line);
+            if line.starts_with('#') {
+                continue;
+            }
+            let fields: Vec<&str> = line.trim_end_matches('\r').split('\t').collect();
+            let [domain, subdomains, path, secure, expires, name, value] = fields[..] else {
+                continue;
+            };
+            let host = domain.trim_start_matches('.');
+            if !hosts
+                .iter()
+                .any(|h| h == host || h.ends_with(&format!(".{}", host)))
+            {
+                continue;
+            }
+
+            let mut cookie = format!("{}={}; Path={}", name, value, path);
+            if subdomains.eq_ignore_ascii_case("TRUE") {
+                cookie.push_str(&format!("; Domain={}", host));
+            }
+            if secure.eq_ignore_ascii_case("TRUE") {
+                cookie.push_str("; Secure");
+            }
+            // Session cookies have an expiry of 0
+            if let Some(expires) = expires.parse::<i64>().ok().filter(|e| *e > 0) {
+                let max_age = expires - Utc::now().timestamp();
+                if max_age <= 0 {
+                    continue;
+                }
+                cookie.push_str(&format!("; Max-Age={}", max_age));
+            }
+            let (Ok(cookie), Ok(url)) = (
+                RawCookie::parse(cookie),
+                Url::parse(&format!("https://{}{}", host, path)),
+            ) else {
+                continue;
+            };
+            if store.insert_raw(&cookie, &url).is_ok() {
+                count += 1;
+            }
+        }
+        if count > 0 {
+            self.changed.store(true, Ordering::Relaxed);
+        }
+        count
+    }
+}
+
+impl reqwest::cookie::CookieStore for CookieJar {
+    fn set_cookies(&self, cookie_headers: &mut dyn Iterator<Item = &HeaderValue>, url: &Url) {
+        let cookies = cookie_headers
+            .filter_map(|h| h.to_str().ok())
+            .filter_map(|h| RawCookie::parse(h.to_owned()).ok())
+            .collect::<Vec<_>>();
+        if cookies.is_empty() {
+            return;
+        }
+        self.store
+            .write()
+            .unwrap_or_else(|e| e.into_inner())
+            .store_response_cookies(cookies.into_iter(), url);
+        self.changed.store(true, Ordering::Relaxed);
+    }
+
+    fn cookies(&self, url: &Url) -> Option<HeaderValue> {
+        let store = self.store.read().unwrap_or_else(|e| e.into_inner());
+        let cookies = store
+            .get_request_values(url)
+            .map(|(name, value)| format!("{}={}", name, value))
+            .collect::<Vec<_>>()
+            .join("; ");
+        match cookies.is_empty() {
+            true => None,
+            false => HeaderValue::from_str(&cookies).ok(),
+        }
+    }
+}
+
+/// Load the cookies saved at `path` and import the `cookies.txt` files configured for each source.
+/// Files which can't be read are returned as errors, leaving the rest of the jar usable.
+pub fn open(path: Option<&PathBuf>, sources: &SourceConfig) -> (Arc<CookieJar>, Vec<String>) {
+    let mut errors = vec![];
+    let jar = match path.map(|p| CookieJar::load(p)).transpose() {
+        Ok(jar) => jar.unwrap_or_default(),
+        Err(e) => {
+            errors.push(format!("Failed to load cookies:\n{}", e));
+            CookieJar::default()
+        }
+    };
+    for (name, file, urls) in sources.cookie_files() {
+        let hosts: Vec<String> = urls
+            .into_iter()
+            .filter_map(|u| add_protocol(u, true).ok())
+            .filter_map(|u| u.host_str().map(ToOwned::to_owned))
+            .collect();
+        let file = shellexpand::tilde(&file).to_string();
+        match fs::read_to_string(&file) {
+            Ok(content) => {
+                jar.import(&content, &hosts);
+            }
+            Err(e) => errors.push(format!(
+                "Failed to import cookies for {} from \"{}\":\n{}",
+                name, file, e
+            )),
+        }
+    }
+    (Arc::new(jar), errors)
+}
@@ -0,0 +1,471 @@
+use std::{
+    collections::HashMap,
+    error::Error,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use chrono::{Local, NaiveDate, NaiveDateTime, TimeZone as _, Utc};
+use ratatui::style::Color;
+use reqwest::{StatusCode, Url};
+use scraper::{ElementRef, Html, Selector};
+use serde::{Deserialize, Serialize};
+use strum::VariantArray as _;
+use urlencoding::encode;
+
+use crate::{
+    results::ResultResponse,
+    sel,
+    sync::SearchQuery,
+    theme::Theme,
+    util::{
+        conv::parse_bytes,
+        html::{as_type, attr, inner},
+    },
+    widget::{
+        category::{CatEntry, CatStruct},
+        sort::{SelectedSort, SortDir},
+    },
+};
+
+use super::{
+    add_protocol,
+    all::sort_items,
+    boxed,
+    command::{self, CommandSource},
+    intern,
+    nyaa_html::{nyaa_table, NyaaColumns, NyaaSort},
+    registry, request,
+    scrape::{required, Defaults, ParseReport},
+    InfoHash, Item, ResultTable, Source, SourceConfig, SourceError, SourceExtraConfig,
+    SourceFuture, SourceInfo, SourceResponse, Sources, DEFAULT_DATE_FORMAT,
+};
+
+/// CSS selectors used to scrape a custom source. Each selector is relative to the row, and reads
+/// the inner html of the element unless an attribute is given with `selector@attribute`
+#[derive(Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct CustomSelectors {
+    pub row: String,
+    pub title: String,
+    /// Reads `href` unless another attribute is given
+    pub magnet: String,
+    /// Reads `href` unless another attribute is given
+    pub torrent: String,
+    pub size: String,
+    pub date: String,
+    pub seeders: String,
+    pub leechers: String,
+    pub downloads: Option<String>,
+    /// Link to the post page, reads `href` unless another attribute is given
+    pub post: Option<String>,
+    /// Matched against the whole page, the highest number found is the last page
+    pub last_page: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct CustomCategory {
+    pub name: String,
+    /// Inserted into the url template in place of `{category}`
+    pub value: String,
+    /// Label shown in the category column, up to 3 characters
+    pub icon: String,
+    /// One of "fg", "info", "success", "warning" or "error"
+    pub color: String,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct CustomSourceConfig {
+    pub name: String,
+    /// Search url containing `{query}`, `{page}` and `{category}` placeholders
+    pub url: String,
+    /// Format of the scraped dates, read in local time. Dates which don't match it are shown
+    /// as scraped
+    pub date_format: Option<String>,
+    pub selectors: CustomSelectors,
+    pub categories: Vec<CustomCategory>,
+    pub default_sort: NyaaSort,
+    pub default_sort_dir: SortDir,
+    pub default_category: String,
+    pub default_search: String,
+    pub timeout: Option<u64>,
+    /// Netscape `cookies.txt` exported from a browser, imported on startup
+    pub cookies: Option<String>,
+    pub columns: Option<NyaaColumns>,
+}
+
+impl Default for CustomSourceConfig {
+    fn default() -> Self {
+        Self {
+            name: Default::default(),
+            url: Default::default(),
+            date_format: None,
+            selectors: Default::default(),
+            categories: vec![],
+            default_sort: NyaaSort::Date,
+            default_sort_dir: SortDir::Desc,
+            default_category: "AllCategories".to_owned(),
+            default_search: Default::default(),
+            timeout: None,
+            cookies: None,
+            columns: None,
+        }
+    }
+}
+
+/// Sources registered from the config along with the config they were declared in, which are
+/// removed again when it changes
+static DECLARED: Mutex<(Vec<Sources>, String)> = Mutex::new((Vec::new(), String::new()));
+
+pub fn category_color(color: &str) -> fn(&Theme) -> Color {
+    match color {
+        "info" => |t| t.info,
+        "success" => |t| t.success,
+        "warning" => |t| t.warning,
+        "error" => |t| t.error,
+        _ => |t| t.fg,
+    }
+}
+
+/// Register the custom and command sources declared in the config, unless they are already
+/// registered from the same config. Sources with an empty name or the name of a source which is
+/// already registered are ignored.
+pub fn register(config: &SourceConfig) {
+    let declared_config =
+        serde_json::to_string(&(&config.custom, &config.command)).unwrap_or_default();
+    let mut declared = DECLARED.lock().unwrap_or_else(|e| e.into_inner());
+    if declared.1 == declared_config {
+        return;
+    }
+    declared.1 = declared_config;
+    command::clear_descriptions();
+
+    let custom = config.custom.iter().map(|c| {
+        let source: Arc<dyn Source> = Arc::new(CustomSource::new(c.to_owned()));
+        (c.name.trim(), source)
+    });
+    let command = config.command.iter().map(|c| {
+        let source: Arc<dyn Source> = Arc::new(CommandSource::new(c.to_owned()));
+        (c.name.trim(), source)
+    });
+    let sources: Vec<(&str, Arc<dyn Source>)> = custom.chain(command).collect();
+
+    let mut registry = registry().write().unwrap_or_else(|e| e.into_inner());
+    for src in declared.0.drain(..) {
+        registry.unregister(src);
+    }
+    for (name, source) in sources {
+        if name.is_empty() || registry.get(Sources::new(name)).is_some() {
+            continue;
+        }
+        declared.0.push(registry.register_shared(name, source));
+    }
+}
+
+fn invalid_selector(name: &str, e: String) -> SourceError {
+    SourceError::config(
+        format!("source.custom.selectors.{}", name),
+        format!("Invalid \"{}\" selector:\n{}", name, e),
+    )
+}
+
+/// A selector along with the attribute to read, if any
+struct Field {
+    selector: Selector,
+    attr: Option<String>,
+}
+
+impl Field {
+    fn parse(
+        name: &str,
+        text: &str,
+        default_attr: Option<&str>,
+    ) -> Result<Field, Box<dyn Error + Send + Sync>> {
+        let (text, attr) = match text.rsplit_once('@') {
+            Some((s, a)) if !a.is_empty() && a.chars().all(|c| c.is_alphanumeric() || c == '-') => {
+                (s, Some(a.to_owned()))
+            }
+            _ => (text, default_attr.map(ToOwned::to_owned)),
+        };
+        let selector = sel!(text.trim()).map_err(|e| invalid_selector(name, e))?;
+        Ok(Field { selector, attr })
+    }
+
+    fn get(&self, e: ElementRef, default: &str) -> String {
+        let value = match &self.attr {
+            Some(a) => attr(e, &self.selector, a),
+            None => inner(e, &self.selector, default),
+        };
+        value.trim().to_owned()
+    }
+}
+
+/// Parse a page of a custom source using its configured selectors
+pub fn parse_results(
+    content: &str,
+    url: &Url,
+    custom: &CustomSourceConfig,
+    search: &SearchQuery,
+) -> Result<ResultResponse, Box<dyn Error + Send + Sync>> {
+    let s = &custom.selectors;
+    let row_sel = &sel!(&s.row).map_err(|e| invalid_selector("row", e))?;
+    let title = Field::parse("title", &s.title, None)?;
+    let magnet = Field::parse("magnet", &s.magnet, Some("href"))?;
+    let torrent = Field::parse("torrent", &s.torrent, Some("href"))?;
+    let size = Field::parse("size", &s.size, None)?;
+    let date = Field::parse("date", &s.date, None)?;
+    let seeders = Field::parse("seeders", &s.seeders, None)?;
+    let leechers = Field::parse("leechers", &s.leechers, None)?;
+    let downloads = s
+        .downloads
+        .as_ref()
+        .map(|d| Field::parse("downloads", d, None))
+        .transpose()?;
+    let post = s
+        .post
+        .as_ref()
+        .map(|p| Field::parse("post", p, Some("href")))
+        .transpose()?;
+
+    let doc = Html::parse_document(content);
+    let date_format = custom.date_format.as_deref().unwrap_or(DEFAULT_DATE_FORMAT);
+    let info = custom_info(custom);
+    // Rows have no category of their own, use the one searched in
+    let category = match search.category <= custom.categories.len() {
+        true => search.category,
+        false => 0,
+    };
+    let icon = info.entry_from_id(category).icon;
+
+    let mut defaults = Defaults::default();
+    let (mut items, report) =
+        ParseReport::collect(doc.select(row_sel).enumerate().map(|(i, e)| {
+            let title = required(title.get(e, ""), "title")?;
+            let link = |field: &Field| -> String {
+                let href = field.get(e, "");
+                match href.is_empty() || href.starts_with("magnet:") {
+                    true => href,
+                    false => url.join(&href).map(Into::into).unwrap_or(href),
+                }
+            };
+            let magnet_link = link(&magnet);
+            let torrent_link = link(&torrent);
+            let post_link = post.as_ref().map(link).unwrap_or_default();
+
+            let size = defaults.or_default(parse_bytes(&size.get(e, "")), "size");
+            let seeders = defaults.or_default(as_type(seeders.get(e, "")), "seeders");
+            let leechers = as_type(leechers.get(e, "0")).unwrap_or_default();
+            let downloads = downloads
+                .as_ref()
+                .and_then(|d| as_type(d.get(e, "0")))
+                .unwrap_or_default();
+
+            // Dates are read in local time, dates which can't be read are shown as scraped
+            let raw_date = date.get(e, "");
+            let date = NaiveDateTime::parse_from_str(&raw_date, date_format)
+                .or_else(|_| {
+                    NaiveDate::parse_from_str(&raw_date, date_format)
+                        .map(|d| d.and_time(Default::default()))
+                })
+                .ok()
+                .and_then(|d| Local.from_local_datetime(&d).earliest())
+                .map(|d| d.with_timezone(&Utc));
+            let mut extra = HashMap::new();
+            let date = defaults.date(date, &raw_date, &mut extra);
+
+            // Ids must be unique for pages to be appended and rows to be selected, so rows without
+            // a hash fall back to their links, then to their position in the results
+            let hash = InfoHash::from_magnet(&magnet_link);
+            let key = hash
+                .map(|h| h.to_string())
+                .or_else(|| post_link.rsplit('/').next().map(str::to_owned))
+                .into_iter()
+                .chain([torrent_link.clone(), post_link.clone()])
+                .find(|k| !k.is_empty())
+                .unwrap_or_else(|| format!("{}.{}", search.page, i + 1));
+            let id = format!("{}-{}", custom.name, key);
+            let file_key: String = key
+                .chars()
+                .map(|c| match c.is_alphanumeric() || "-_.".contains(c) {
+                    true => c,
+                    false => '_',
+                })
+                .collect();
+
+            Ok(Item {
+                file_name: format!("{}-{}.torrent", custom.name, file_key),
+                id,
+                date,
+                seeders,
+                leechers,
+                downloads,
+                size,
+                title,
+                torrent_link,
+                magnet_link,
+                hash,
+                post_link,
+                category,
+                icon: icon.clone(),
+                extra,
+                ..Default::default()
+            })
+        }));
+
+    // Without a pagination selector, allow going to the next page until it is empty
+    let (last_page, listed) = match &s.last_page {
+        Some(p) => {
+            let page_sel = &sel!(p).map_err(|e| invalid_selector("last_page", e))?;
+            let pages: Vec<usize> = doc
+                .select(page_sel)
+                .filter_map(|e| as_type::<usize>(e.inner_html()))
+                .collect();
+            let last_page = pages.iter().copied().max().unwrap_or(search.page);
+            (last_page.max(search.page), !pages.is_empty())
+        }
+        None if items.is_empty() => (search.page, false),
+        None => (search.page + 1, false),
+    };
+
+    sort_items(&mut items, search.sort);
+    Ok(ResultResponse {
+        total_results: items.len(),
+        items,
+        last_page,
+        report: Some(report.defaults(defaults).listing(listed).page(content)),
+        ..Default::default()
+    })
+}
+
+/// Value of the category with the given id, which is empty for all categories
+fn search_value(custom: &CustomSourceConfig, id: usize) -> &str {
+    id.checked_sub(1)
+        .and_then(|i| custom.categories.get(i))
+        .map(|c| c.value.as_str())
+        .unwrap_or("")
+}
+
+/// A source scraped with the selectors declared in `[[source.custom]]`
+pub struct CustomSource {
+    config: CustomSourceConfig,
+    info: SourceInfo,
+}
+
+impl CustomSource {
+    pub fn new(config: CustomSourceConfig) -> Self {
+        let info = custom_info(&config);
+        CustomSource { config, info }
+    }
+}
+
+fn custom_info(custom: &CustomSourceConfig) -> SourceInfo {
+    let mut entries = vec![CatEntry::new(
+        "All Categories",
+        "AllCategories",
+        0,
+        "---",
+        |t| t.fg,
+    )];
+    for (i, cat) in custom.categories.iter().enumerate() {
+        let icon: String = cat.icon.chars().take(3).collect();
+        entries.push(CatEntry::new(
+            &cat.name,
+            &cat.name,
+            i + 1,
+            intern(&format!("{:<3}", icon)),
+            category_color(&cat.color),
+        ));
+    }
+    SourceInfo {
+        cats: vec![CatStruct {
+            name: "Categories".to_owned(),
+            entries,
+        }],
+        filters: vec!["No Filter".to_owned()],
+        sorts: NyaaSort::VARIANTS.iter().map(ToString::to_string).collect(),
+    }
+}
+
+impl Source for CustomSource {
+    fn name(&self) -> String {
+        self.config.name.trim().to_owned()
+    }
+
+    fn search<'a>(
+        &'a self,
+        client: &'a reqwest::Client,
+        search: &'a SearchQuery,
+        _config: &'a SourceConfig,
+        _extra: &'a SourceExtraConfig,
+    ) -> SourceFuture<'a, SourceResponse> {
+        boxed(async move {
+            let custom = &self.config;
+            let url = custom
+                .url
+                .replace("{query}", &encode(&search.query))
+                .replace("{page}", &search.page.to_string())
+                .replace("{category}", &encode(search_value(custom, search.category)));
+            let url =
+                add_protocol(url, true).map_err(|e| SourceError::config("source.custom.url", e))?;
+
+            let mut request = client.get(url.to_owned());
+            if let Some(timeout) = custom.timeout {
+                request = request.timeout(Duration::from_secs(timeout));
+            }
+            let response = request::send(request).await?;
+            let code = response.status();
+            if code != StatusCode::OK {
+                return Err(SourceError::http(code, &url));
+            }
+            let content = response.text().await?;
+            Ok(SourceResponse::Results(parse_results(
+                &content, &url, custom, search,
+            )?))
+        })
+    }
+
+    fn info(&self) -> SourceInfo {
+        self.info.clone()
+    }
+
+    fn default_category(&self, _cfg: &SourceConfig) -> usize {
+        self.info
+            .clone()
+            .entry_from_cfg(&self.config.default_category)
+            .id
+    }
+
+    fn default_sort(&self, _cfg: &SourceConfig) -> SelectedSort {
+        SelectedSort {
+            sort: self.config.default_sort as usize,
+            dir: self.config.default_sort_dir,
+        }
+    }
+
+    fn default_filter(&self, _cfg: &SourceConfig) -> usize {
+        0
+    }
+
+    fn default_search(&self, _cfg: &SourceConfig) -> String {
+        self.config.default_search.to_owned()
+    }
+
+    fn format_table(
+        &self,
+        items: &[Item],
+        search: &SearchQuery,
+        _config: &SourceConfig,
+        extra: &SourceExtraConfig,
+        theme: &Theme,
+    ) -> ResultTable {
+        nyaa_table(
+            items.into(),
+            theme,
+            &search.sort,
+            &self.config.columns,
+            extra,
+        )
+    }
+}
@@ -0,0 +1,129 @@
+use std::{error::Error, sync::Mutex};
+
+use reqwest::{RequestBuilder, Response, StatusCode, Url};
+
+use crate::util::conv::add_protocol;
+
+use super::request::{self, Retry};
+
+/// Last mirror of each source which responded, kept for the rest of the session
+static HEALTHY: Mutex<Vec<(String, String)>> = Mutex::new(Vec::new());
+
+/// The mirror of a source which last responded, if any
+pub fn healthy(key: &str) -> Option<String> {
+    HEALTHY
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .iter()
+        .find(|(k, _)| k == key)
+        .map(|(_, url)| url.to_owned())
+}
+
+fn set_healthy(key: &str, url: &str) {
+    let mut healthy = HEALTHY.lock().unwrap_or_else(|e| e.into_inner());
+    match healthy.iter_mut().find(|(k, _)| k == key) {
+        Some((_, u)) => *u = url.to_owned(),
+        None => healthy.push((key.to_owned(), url.to_owned())),
+    }
+}
+
+/// Host shown in the results title for the mirror which served a page
+pub fn display_name(base_url: &Url) -> String {
+    base_url.host_str().unwrap_or(base_url.as_str()).to_owned()
+}
+
+/// Base urls of a source, tried in order until one responds
+pub struct Mirrors {
+    key: &'static str,
+    urls: Vec<String>,
+}
+
+impl Mirrors {
+    /// `base_url` followed by `mirrors`, starting from the last healthy mirror of the session
+    pub fn new(key: &'static str, base_url: &str, mirrors: &[String]) -> Self {
+        let mut urls: Vec<String> = vec![];
+        for url in std::iter::once(base_url).chain(mirrors.iter().map(String::as_str)) {
+            let url = url.trim();
+            if !url.is_empty() && !urls.iter().any(|u| u == url) {
+                urls.push(url.to_owned());
+            }
+        }
+        if let Some(pos) = healthy(key).and_then(|h| urls.iter().position(|u| *u == h)) {
+            let url = urls.remove(pos);
+            urls.insert(0, url);
+        }
+        Mirrors { key, urls }
+    }
+
+    /// Base url to use without sending a request, like for links to a post
+    pub fn first(&self) -> Result<Url, Box<dyn Error + Send + Sync>> {
+        add_protocol(self.urls.first().cloned().unwrap_or_default(), true)
+    }
+
+    /// Send the request built for each mirror until one responds without a connection error,
+    /// timeout or 5xx status. Failing mirrors are skipped straight away; the retry config only
+    /// applies once no mirror is left, starting over from the first one, or to a 429 response,
+    /// which is retried on the same mirror. The last mirror's response or error is returned as-is.
+    pub async fn send<F>(&self, request: F) -> Result<(Url, Response), Box<dyn Error + Send + Sync>>
+    where
+        F: Fn(&Url) -> Result<RequestBuilder, Box<dyn Error + Send + Sync>>,
+    {
+        let config = request::config();
+        // Mirrors with an invalid url, reported if no other mirror is left
+        let mut failures: Vec<String> = vec![];
+        let mut attempt = 0;
+        let mut i = 0;
+        loop {
+            let Some(url) = self.urls.get(i) else {
+                return Err(format!("All mirrors failed:\n{}", failures.join("\n")).into());
+            };
+            let last = i + 1 == self.urls.len();
+            let base_url = match add_protocol(url.to_owned(), true) {
+                Ok(base_url) => base_url,
+                Err(e) if self.urls.len() == 1 => return Err(e),
+                Err(e) => {
+                    failures.push(format!("{}: {}", url, e));
+                    i += 1;
+                    continue;
+                }
+            };
+            let res = request(&base_url)?.send().await;
+            let failed = match &res {
+                Ok(response) => response.status().is_server_error(),
+                Err(e) => e.is_connect() || e.is_timeout(),
+            };
+            if failed && !last {
+                i += 1;
+                continue;
+            }
+            let retry = match attempt < config.retries {
+                true => request::retry_delay(&config, attempt + 1, &res),
+                false => None,
+            };
+            let Some((delay, reason)) = retry else {
+                return match res {
+                    Ok(response) => {
+                        if !response.status().is_server_error() {
+                            set_healthy(self.key, url);
+                        }
+                        Ok((base_url, response))
+                    }
+                    // Kept as a reqwest error so timeouts and connection errors stay retryable
+                    Err(e) => Err(e.into()),
+                };
+            };
+            attempt += 1;
+            request::wait(Retry {
+                attempt,
+                retries: config.retries,
+                delay,
+                reason,
+            })
+            .await;
+            // Rate limits are retried on the same mirror, anything else starts over
+            if !matches!(&res, Ok(response) if response.status() == StatusCode::TOO_MANY_REQUESTS) {
+                i = 0;
+            }
+        }
+    }
+}
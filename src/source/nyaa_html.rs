@@ -1,35 +1,39 @@
-use std::fmt::Write;
 use std::{cmp::max, error::Error, time::Duration};
 
-use chrono::{DateTime, Local, NaiveDateTime, TimeZone};
+use chrono::NaiveDateTime;
 use ratatui::{
     layout::{Alignment, Constraint},
     style::{Color, Stylize as _},
 };
-use reqwest::StatusCode;
-use scraper::{Html, Selector};
+use reqwest::{StatusCode, Url};
+use scraper::{ElementRef, Html, Selector};
 use serde::{Deserialize, Serialize};
 use strum::{Display, FromRepr, VariantArray};
+
 use urlencoding::encode;
 
-use crate::util;
 use crate::{
-    cats, cond_vec,
+    cats, collection, cond_vec,
     results::{ResultColumn, ResultHeader, ResultResponse, ResultRow, ResultTable},
     sel,
     sync::SearchQuery,
     theme::Theme,
     util::{
         colors::color_to_tui,
-        conv::{shorten_number, to_bytes},
+        conv::{parse_bytes, shorten_number, to_size},
         html::{as_type, attr, inner},
     },
     widget::sort::{SelectedSort, SortDir},
 };
 
 use super::{
-    add_protocol, nyaa_rss, Item, ItemType, Source, SourceConfig, SourceExtraConfig, SourceInfo,
-    SourceResponse,
+    boxed,
+    mirror::{self, Mirrors},
+    nyaa_rss,
+    release::{self, ReleaseField},
+    scrape::{required, Defaults, ParseReport},
+    InfoHash, Item, ItemType, Source, SourceConfig, SourceError, SourceExtraConfig, SourceFuture,
+    SourceInfo, SourceResponse, DEFAULT_DATE_FORMAT,
 };
 
 #[derive(Serialize, Deserialize, Clone, Copy, Default)]
@@ -107,6 +111,8 @@ impl Default for NyaaCategoryTheme {
 #[serde(default)]
 pub struct NyaaConfig {
     pub base_url: String,
+    /// Tried in order when `base_url` is down
+    pub mirrors: Vec<String>,
     pub default_sort: NyaaSort,
     pub default_sort_dir: SortDir,
     pub default_filter: NyaaFilter,
@@ -114,25 +120,44 @@ pub struct NyaaConfig {
     pub default_search: String,
     pub rss: bool,
     pub timeout: Option<u64>,
+    /// Netscape `cookies.txt` exported from a browser, imported on startup
+    pub cookies: Option<String>,
     pub columns: Option<NyaaColumns>,
 }
 
+impl NyaaConfig {
+    pub fn mirrors(&self) -> Mirrors {
+        Mirrors::new("nyaa", &self.base_url, &self.mirrors)
+    }
+}
+
 #[derive(Clone, Copy, Serialize, Deserialize, Default)]
 pub struct NyaaColumns {
     category: Option<bool>,
     title: Option<bool>,
+    comments: Option<bool>,
     size: Option<bool>,
     date: Option<bool>,
     seeders: Option<bool>,
     leechers: Option<bool>,
     downloads: Option<bool>,
+    group: Option<bool>,
+    episode: Option<bool>,
+    resolution: Option<bool>,
+    codec: Option<bool>,
 }
 
 impl NyaaColumns {
-    fn array(self) -> [bool; 7] {
+    fn array(self) -> [bool; 12] {
         [
             self.category.unwrap_or(true),
             self.title.unwrap_or(true),
+            // Hidden unless enabled
+            self.comments.unwrap_or(false),
+            self.group.unwrap_or(false),
+            self.episode.unwrap_or(false),
+            self.resolution.unwrap_or(false),
+            self.codec.unwrap_or(false),
             self.size.unwrap_or(true),
             self.date.unwrap_or(true),
             self.seeders.unwrap_or(true),
@@ -146,6 +171,7 @@ impl Default for NyaaConfig {
     fn default() -> Self {
         Self {
             base_url: "https://nyaa.si/".to_owned(),
+            mirrors: vec![],
             default_sort: NyaaSort::Date,
             default_sort_dir: SortDir::Desc,
             default_filter: NyaaFilter::NoFilter,
@@ -153,6 +179,7 @@ impl Default for NyaaConfig {
             default_search: Default::default(),
             rss: false,
             timeout: None,
+            cookies: None,
             columns: None,
         }
     }
@@ -171,6 +198,15 @@ pub enum NyaaSort {
     Leechers = 3,
     #[strum(serialize = "Size")]
     Size = 4,
+    /// Sorted locally by the parts of the release name
+    #[strum(serialize = "Group")]
+    Group = 5,
+    #[strum(serialize = "Episode")]
+    Episode = 6,
+    #[strum(serialize = "Resolution")]
+    Resolution = 7,
+    #[strum(serialize = "Codec")]
+    Codec = 8,
 }
 
 impl NyaaSort {
@@ -181,6 +217,10 @@ impl NyaaSort {
             NyaaSort::Seeders => "seeders".to_owned(),
             NyaaSort::Leechers => "leechers".to_owned(),
             NyaaSort::Size => "size".to_owned(),
+            // Newest first, before sorting locally
+            NyaaSort::Group | NyaaSort::Episode | NyaaSort::Resolution | NyaaSort::Codec => {
+                "id".to_owned()
+            }
         }
     }
 }
@@ -205,13 +245,31 @@ pub fn nyaa_table(
     theme: &Theme,
     sel_sort: &SelectedSort,
     columns: &Option<NyaaColumns>,
+    extra: &SourceExtraConfig,
 ) -> ResultTable {
-    let raw_date_width = items.iter().map(|i| i.date.len()).max().unwrap_or_default() as u16;
+    let dates: Vec<String> = items.iter().map(|i| extra.format_item_date(i)).collect();
+    let raw_date_width = dates.iter().map(|d| d.len()).max().unwrap_or_default() as u16;
     let date_width = max(raw_date_width, 6);
+    // Parts of the release name, hidden unless enabled
+    let [group_col, ep_col, res_col, codec_col] = [
+        (ReleaseField::Group, "Group", 16, NyaaSort::Group),
+        (ReleaseField::Episode, "Ep", 7, NyaaSort::Episode),
+        (ReleaseField::Resolution, "Res", 5, NyaaSort::Resolution),
+        (ReleaseField::Codec, "Codec", 4, NyaaSort::Codec),
+    ]
+    .map(|(field, name, max, sort)| {
+        let width = release::column_width(&items, field, name, max);
+        ResultColumn::Sorted(name.to_owned(), width, sort as u32)
+    });
 
     let header = ResultHeader::new([
         ResultColumn::Normal("Cat".to_owned(), Constraint::Length(3)),
         ResultColumn::Normal("Name".to_owned(), Constraint::Min(3)),
+        ResultColumn::Normal("".to_owned(), Constraint::Length(3)),
+        group_col,
+        ep_col,
+        res_col,
+        codec_col,
         ResultColumn::Sorted("Size".to_owned(), 9, NyaaSort::Size as u32),
         ResultColumn::Sorted("Date".to_owned(), date_width, NyaaSort::Date as u32),
         ResultColumn::Sorted("".to_owned(), 4, NyaaSort::Seeders as u32),
@@ -227,10 +285,23 @@ pub fn nyaa_table(
         Alignment::Right,
         Alignment::Right,
         Alignment::Left,
+        Alignment::Right,
+        Alignment::Left,
+        Alignment::Right,
+        Alignment::Right,
+        Alignment::Left,
     ];
     let mut rows: Vec<ResultRow> = items
         .into_iter()
-        .map(|item| {
+        .zip(dates)
+        .map(|(item, date)| {
+            let [group, episode, resolution, codec] = [
+                ReleaseField::Group,
+                ReleaseField::Episode,
+                ReleaseField::Resolution,
+                ReleaseField::Codec,
+            ]
+            .map(|f| f.get(&item).unwrap_or_default());
             ResultRow::new([
                 item.icon.label.fg((item.icon.color)(theme)),
                 item.title.fg(match item.item_type {
@@ -238,8 +309,16 @@ pub fn nyaa_table(
                     ItemType::Remake => theme.error,
                     ItemType::None => theme.fg,
                 }),
-                item.size.fg(theme.fg),
-                item.date.fg(theme.fg),
+                match item.extra.get("comments").map(String::as_str) {
+                    None | Some("0") => "".fg(theme.fg),
+                    Some(c) => c.to_owned().fg(theme.info),
+                },
+                group.fg(theme.fg),
+                episode.fg(theme.fg),
+                resolution.fg(theme.fg),
+                codec.fg(theme.fg),
+                to_size(item.size).fg(theme.fg),
+                date.fg(theme.fg),
                 item.seeders.to_string().fg(theme.success),
                 item.leechers.to_string().fg(theme.error),
                 shorten_number(item.downloads).fg(theme.fg),
@@ -250,20 +329,18 @@ pub fn nyaa_table(
         .collect();
 
     let mut headers = header.get_row(sel_sort.dir, sel_sort.sort as u32);
-    if let Some(columns) = columns {
-        let cols = columns.array();
-
-        headers.cells = cond_vec!(cols ; headers.cells);
-        rows = rows
-            .clone()
-            .into_iter()
-            .map(|mut r| {
-                r.cells = cond_vec!(cols ; r.cells.to_owned());
-                r
-            })
-            .collect::<Vec<ResultRow>>();
-        binding = cond_vec!(cols ; binding);
-    }
+    let cols = columns.unwrap_or_default().array();
+
+    headers.cells = cond_vec!(cols ; headers.cells);
+    rows = rows
+        .clone()
+        .into_iter()
+        .map(|mut r| {
+            r.cells = cond_vec!(cols ; r.cells.to_owned());
+            r
+        })
+        .collect::<Vec<ResultRow>>();
+    binding = cond_vec!(cols ; binding);
     ResultTable {
         headers,
         rows,
@@ -272,127 +349,124 @@ pub fn nyaa_table(
 }
 
 impl Source for NyaaHtmlSource {
-    async fn search(
-        client: &reqwest::Client,
-        search: &SearchQuery,
-        config: &SourceConfig,
-        extra: &SourceExtraConfig,
-    ) -> Result<SourceResponse, Box<dyn Error + Send + Sync>> {
-        let nyaa = config.nyaa.to_owned().unwrap_or_default();
-        if nyaa.rss {
-            return nyaa_rss::search_rss::<Self>(
-                nyaa.base_url,
-                nyaa.timeout,
-                client,
-                search,
-                extra,
-            )
-            .await;
-        }
-        let cat = search.category;
-        let filter = search.filter;
-        let page = search.page;
-        let user = search.user.to_owned().unwrap_or_default();
-        let sort = NyaaSort::from_repr(search.sort.sort)
-            .unwrap_or(NyaaSort::Date)
-            .to_url();
-
-        let base_url = add_protocol(nyaa.base_url, true)?;
-        let mut url = base_url.clone();
-        // let base_url = add_protocol(ctx.config.base_url.clone(), true);
-        let (high, low) = (cat / 10, cat % 10);
-        let query = encode(&search.query);
-        let dir = search.sort.dir.to_url();
-        url.set_query(Some(&format!(
-            "q={}&c={}_{}&f={}&p={}&s={}&o={}&u={}",
-            query, high, low, filter, page, sort, dir, user
-        )));
-
-        let mut request = client.get(url.to_owned());
-        if let Some(timeout) = nyaa.timeout {
-            request = request.timeout(Duration::from_secs(timeout));
-        }
-        let response = request.send().await?;
-        if response.status() != StatusCode::OK {
-            // Throw error if response code is not OK
-            let code = response.status().as_u16();
-            return Err(format!("{}\nInvalid response code: {}", url, code).into());
-        }
-        let content = response.bytes().await?;
-        let doc = Html::parse_document(std::str::from_utf8(&content[..])?);
-
-        // let item_sel = &Selector::parse("table.torrent-list > tbody > tr")?;
-        let item_sel = &sel!("table.torrent-list > tbody > tr")?;
-        let icon_sel = &sel!("td:first-of-type > a")?;
-        let title_sel = &sel!("td:nth-of-type(2) > a:last-of-type")?;
-        let torrent_sel = &sel!("td:nth-of-type(3) > a:nth-of-type(1)")?;
-        let magnet_sel = &sel!("td:nth-of-type(3) > a:nth-of-type(2)")?;
-        let size_sel = &sel!("td:nth-of-type(4)")?;
-        let date_sel = &sel!("td:nth-of-type(5)").unwrap();
-        let seed_sel = &sel!("td:nth-of-type(6)")?;
-        let leech_sel = &sel!("td:nth-of-type(7)")?;
-        let dl_sel = &sel!("td:nth-of-type(8)")?;
-        let pagination_sel = &sel!(".pagination-page-info")?;
-
-        let mut last_page = 100;
-        let mut total_results = 7500;
-        // For searches, pagination has a description of total results found
-        if let Some(pagination) = doc.select(pagination_sel).next() {
-            // 6th word in pagination description contains total number of results
-            if let Some(num_results_str) = pagination.inner_html().split(' ').nth(5) {
-                if let Ok(num_results) = num_results_str.parse::<usize>() {
-                    last_page = (num_results + 74) / 75;
-                    total_results = num_results;
+    fn name(&self) -> String {
+        "Nyaa".to_owned()
+    }
+
+    fn search<'a>(
+        &'a self,
+        client: &'a reqwest::Client,
+        search: &'a SearchQuery,
+        config: &'a SourceConfig,
+        _extra: &'a SourceExtraConfig,
+    ) -> SourceFuture<'a, SourceResponse> {
+        boxed(async move {
+            let nyaa = config.nyaa.to_owned().unwrap_or_default();
+            if nyaa.rss {
+                return nyaa_rss::search_rss(self, &nyaa.mirrors(), nyaa.timeout, client, search)
+                    .await;
+            }
+            let cat = search.category;
+            let filter = search.filter;
+            let page = search.page;
+            let user = search.user.to_owned().unwrap_or_default();
+            let sort = NyaaSort::from_repr(search.sort.sort)
+                .unwrap_or(NyaaSort::Date)
+                .to_url();
+
+            let (high, low) = (cat / 10, cat % 10);
+            let query = encode(&search.query);
+            let dir = search.sort.dir.to_url();
+            let (base_url, response) = nyaa
+                .mirrors()
+                .send(|base_url| {
+                    let mut url = base_url.clone();
+                    url.set_query(Some(&format!(
+                        "q={}&c={}_{}&f={}&p={}&s={}&o={}&u={}",
+                        query, high, low, filter, page, sort, dir, user
+                    )));
+                    let mut request = client.get(url);
+                    if let Some(timeout) = nyaa.timeout {
+                        request = request.timeout(Duration::from_secs(timeout));
+                    }
+                    Ok(request)
+                })
+                .await?;
+            let url = response.url().to_owned();
+            if response.status() != StatusCode::OK {
+                // Throw error if response code is not OK
+                return Err(SourceError::http(response.status(), &url));
+            }
+            let content = response.bytes().await?;
+            let content = std::str::from_utf8(&content[..])?;
+            let doc = Html::parse_document(content);
+
+            // let item_sel = &Selector::parse("table.torrent-list > tbody > tr")?;
+            let table_sel = &sel!("table.torrent-list")?;
+            let item_sel = &sel!("table.torrent-list > tbody > tr")?;
+            let icon_sel = &sel!("td:first-of-type > a")?;
+            let title_sel = &sel!("td:nth-of-type(2) > a:last-of-type")?;
+            let torrent_sel = &sel!("td:nth-of-type(3) > a:nth-of-type(1)")?;
+            let magnet_sel = &sel!("td:nth-of-type(3) > a:nth-of-type(2)")?;
+            let size_sel = &sel!("td:nth-of-type(4)")?;
+            let date_sel = &sel!("td:nth-of-type(5)").unwrap();
+            let seed_sel = &sel!("td:nth-of-type(6)")?;
+            let leech_sel = &sel!("td:nth-of-type(7)")?;
+            let dl_sel = &sel!("td:nth-of-type(8)")?;
+            let comments_sel = &sel!("td:nth-of-type(2) > a.comments")?;
+            let pagination_sel = &sel!(".pagination-page-info")?;
+
+            let mut last_page = 100;
+            let mut total_results = 7500;
+            let mut expected = None;
+            // For searches, pagination has a description of total results found
+            if let Some(pagination) = doc.select(pagination_sel).next() {
+                // 6th word in pagination description contains total number of results
+                if let Some(num_results_str) = pagination.inner_html().split(' ').nth(5) {
+                    if let Ok(num_results) = num_results_str.parse::<usize>() {
+                        last_page = num_results.div_ceil(75);
+                        total_results = num_results;
+                        expected = Some(num_results);
+                    }
                 }
             }
-        }
 
-        let items: Vec<Item> = doc
-            .select(item_sel)
-            .filter_map(|e| {
+            // Pages without results say so instead of showing an empty table
+            let listed = doc.select(table_sel).next().is_some()
+                || doc.select(pagination_sel).next().is_some()
+                || !content.contains("No results found");
+
+            let mut defaults = Defaults::default();
+            let (items, report) = ParseReport::collect(doc.select(item_sel).map(|e| {
                 let cat_str = attr(e, icon_sel, "href");
-                let cat_str = cat_str.split('=').last().unwrap_or("");
-                let cat = Self::info().entry_from_str(cat_str);
+                let cat_str = cat_str.split('=').next_back().unwrap_or("");
+                let cat = self.info().entry_from_str(cat_str);
                 let category = cat.id;
                 let icon = cat.icon.clone();
 
                 let torrent = attr(e, torrent_sel, "href");
                 let id = torrent
                     .split('/')
-                    .last()?
-                    .split('.')
-                    .next()?
-                    .parse::<usize>()
-                    .ok()?;
+                    .next_back()
+                    .and_then(|t| t.split('.').next())
+                    .and_then(|t| t.parse::<usize>().ok())
+                    .ok_or("id")?;
                 let id = format!("nyaa-{}", id);
                 let file_name = format!("{}.torrent", id);
 
-                let size = inner(e, size_sel, "0 bytes")
-                    .replace('i', "")
-                    .replace("Bytes", "B");
-                let bytes = to_bytes(&size);
-
-                const DEFAULT_DATE_FORMAT: &str = "%Y-%m-%d %H:%M";
-                let date_raw = inner(e, date_sel, "");
-                let naive = NaiveDateTime::parse_from_str(&date_raw, DEFAULT_DATE_FORMAT)
-                    .unwrap_or_default();
-                let date_time: DateTime<Local> = Local.from_utc_datetime(&naive);
-                let date_format = extra.date_format.as_deref().unwrap_or(DEFAULT_DATE_FORMAT);
-
-                let date = if extra.relative_date.unwrap_or(false) {
-                    util::conv::to_relative_date(
-                        date_time,
-                        extra.relative_date_short.unwrap_or(false),
-                    )
-                } else {
-                    let mut newstr = String::new();
-                    if write!(newstr, "{}", date_time.format(date_format)).is_err() {
-                        newstr = format!("Invalid format string: `{}`", date_format);
-                    }
-                    newstr
-                };
-
-                let seeders = as_type(inner(e, seed_sel, "0")).unwrap_or_default();
+                let size = defaults.or_default(parse_bytes(&inner(e, size_sel, "")), "size");
+                // Dates are shown in UTC
+                let comments = as_type::<u32>(inner(e, comments_sel, "0")).unwrap_or_default();
+                let raw_date = inner(e, date_sel, "");
+                let date = NaiveDateTime::parse_from_str(&raw_date, DEFAULT_DATE_FORMAT)
+                    .ok()
+                    .map(|d| d.and_utc());
+                let mut extra = collection![
+                    "comments".to_owned() => comments.to_string(),
+                ];
+                let date = defaults.date(date, &raw_date, &mut extra);
+
+                let seeders = defaults.or_default(as_type(inner(e, seed_sel, "")), "seeders");
                 let leechers = as_type(inner(e, leech_sel, "0")).unwrap_or_default();
                 let downloads = as_type(inner(e, dl_sel, "0")).unwrap_or_default();
                 let torrent_link = base_url
@@ -404,6 +478,8 @@ impl Source for NyaaHtmlSource {
                     .map(Into::into)
                     .unwrap_or("null".to_owned());
 
+                let magnet_link = attr(e, magnet_sel, "href");
+
                 let trusted = e.value().classes().any(|e| e == "success");
                 let remake = e.value().classes().any(|e| e == "danger");
                 let item_type = match (trusted, remake) {
@@ -412,77 +488,75 @@ impl Source for NyaaHtmlSource {
                     _ => ItemType::None,
                 };
 
-                Some(Item {
+                Ok(Item {
                     id,
                     date,
                     seeders,
                     leechers,
                     downloads,
                     size,
-                    bytes,
-                    title: attr(e, title_sel, "title"),
+                    title: required(attr(e, title_sel, "title"), "title")?,
                     torrent_link,
-                    magnet_link: attr(e, magnet_sel, "href"),
+                    hash: InfoHash::from_magnet(&magnet_link),
+                    magnet_link,
                     post_link,
                     file_name: file_name.to_owned(),
                     category,
                     icon,
                     item_type,
-                    ..Default::default()
+                    extra,
                 })
-            })
-            .collect();
-
-        Ok(SourceResponse::Results(ResultResponse {
-            items,
-            total_results,
-            last_page,
-        }))
+            }));
+
+            Ok(SourceResponse::Results(ResultResponse {
+                items,
+                total_results,
+                last_page,
+                mirror: Some(mirror::display_name(&base_url)),
+                report: Some(
+                    report
+                        .expect(expected)
+                        .defaults(defaults)
+                        .listing(listed)
+                        .page(content),
+                ),
+                ..Default::default()
+            }))
+        })
     }
-    async fn sort(
-        client: &reqwest::Client,
-        search: &SearchQuery,
-        config: &SourceConfig,
-        extra: &SourceExtraConfig,
-    ) -> Result<SourceResponse, Box<dyn Error + Send + Sync>> {
-        let nyaa = config.nyaa.to_owned().unwrap_or_default();
-        let sort = search.sort;
-        let mut res = NyaaHtmlSource::search(client, search, config, extra).await;
-
-        if nyaa.rss {
-            if let Ok(SourceResponse::Results(res)) = &mut res {
-                nyaa_rss::sort_items(&mut res.items, sort);
+    fn sort<'a>(
+        &'a self,
+        client: &'a reqwest::Client,
+        search: &'a SearchQuery,
+        config: &'a SourceConfig,
+        extra: &'a SourceExtraConfig,
+    ) -> SourceFuture<'a, SourceResponse> {
+        boxed(async move {
+            let nyaa = config.nyaa.to_owned().unwrap_or_default();
+            let sort = search.sort;
+            let mut res = self.search(client, search, config, extra).await;
+
+            if nyaa.rss {
+                if let Ok(SourceResponse::Results(res)) = &mut res {
+                    nyaa_rss::sort_items(&mut res.items, sort);
+                }
             }
-        }
-        res
-    }
-    async fn filter(
-        client: &reqwest::Client,
-        search: &SearchQuery,
-        config: &SourceConfig,
-        extra: &SourceExtraConfig,
-    ) -> Result<SourceResponse, Box<dyn Error + Send + Sync>> {
-        NyaaHtmlSource::search(client, search, config, extra).await
-    }
-    async fn categorize(
-        client: &reqwest::Client,
-        search: &SearchQuery,
-        config: &SourceConfig,
-        extra: &SourceExtraConfig,
-    ) -> Result<SourceResponse, Box<dyn Error + Send + Sync>> {
-        NyaaHtmlSource::search(client, search, config, extra).await
+            res
+        })
     }
-    async fn solve(
-        _solution: String,
-        client: &reqwest::Client,
-        search: &SearchQuery,
-        config: &SourceConfig,
-        extra: &SourceExtraConfig,
-    ) -> Result<SourceResponse, Box<dyn Error + Send + Sync>> {
-        NyaaHtmlSource::search(client, search, config, extra).await
+    fn details<'a>(
+        &'a self,
+        client: &'a reqwest::Client,
+        item: &'a Item,
+        config: &'a SourceConfig,
+    ) -> SourceFuture<'a, ItemDetails> {
+        boxed(async move {
+            let nyaa = config.nyaa.to_owned().unwrap_or_default();
+            view_details(client, &nyaa.mirrors(), nyaa.timeout, view_id(item)?).await
+        })
     }
 
-    fn info() -> SourceInfo {
+    fn info(&self) -> SourceInfo {
         let cats = cats! {
             "All Categories" => {
                 0 => ("---", "All Categories", "AllCategories", fg);
@@ -533,22 +607,22 @@ impl Source for NyaaHtmlSource {
         }
     }
 
-    fn load_config(config: &mut SourceConfig) {
+    fn load_config(&self, config: &mut SourceConfig) {
         if config.nyaa.is_none() {
             config.nyaa = Some(NyaaConfig::default());
         }
     }
 
-    fn default_category(cfg: &SourceConfig) -> usize {
+    fn default_category(&self, cfg: &SourceConfig) -> usize {
         let default = cfg
             .nyaa
             .as_ref()
             .map(|c| c.default_category.to_owned())
             .unwrap_or_default();
-        Self::info().entry_from_cfg(&default).id
+        self.info().entry_from_cfg(&default).id
     }
 
-    fn default_sort(cfg: &SourceConfig) -> SelectedSort {
+    fn default_sort(&self, cfg: &SourceConfig) -> SelectedSort {
         cfg.nyaa
             .as_ref()
             .map(|c| SelectedSort {
@@ -558,14 +632,14 @@ impl Source for NyaaHtmlSource {
             .unwrap_or_default()
     }
 
-    fn default_filter(cfg: &SourceConfig) -> usize {
+    fn default_filter(&self, cfg: &SourceConfig) -> usize {
         cfg.nyaa
             .as_ref()
             .map(|c| c.default_filter as usize)
             .unwrap_or_default()
     }
 
-    fn default_search(cfg: &SourceConfig) -> String {
+    fn default_search(&self, cfg: &SourceConfig) -> String {
         cfg.nyaa
             .as_ref()
             .map(|c| c.default_search.to_owned())
@@ -573,12 +647,234 @@ impl Source for NyaaHtmlSource {
     }
 
     fn format_table(
+        &self,
         items: &[Item],
         search: &SearchQuery,
         config: &SourceConfig,
+        extra: &SourceExtraConfig,
         theme: &Theme,
     ) -> ResultTable {
         let nyaa = config.nyaa.to_owned().unwrap_or_default();
-        nyaa_table(items.into(), theme, &search.sort, &nyaa.columns)
+        nyaa_table(items.into(), theme, &search.sort, &nyaa.columns, extra)
     }
 }
+
+#[derive(Clone, Default)]
+pub struct ItemDetails {
+    pub title: String,
+    pub submitter: String,
+    pub info_hash: String,
+    pub description: String,
+    pub files: Vec<FileEntry>,
+    pub comment_count: usize,
+    pub comments: Vec<Comment>,
+}
+
+#[derive(Clone, Default)]
+pub struct FileEntry {
+    pub name: String,
+    /// Folders have no size
+    pub size: Option<String>,
+    pub depth: usize,
+}
+
+#[derive(Clone, Default)]
+pub struct Comment {
+    pub author: String,
+    pub date: String,
+    pub body: String,
+    /// The author is a trusted uploader
+    pub trusted: bool,
+    /// The author uploaded the torrent
+    pub uploader: bool,
+}
+
+/// Fetch a `/view/<id>` page, returning the base url of the mirror which served it
+async fn fetch_view(
+    client: &reqwest::Client,
+    mirrors: &Mirrors,
+    timeout: Option<u64>,
+    id: usize,
+) -> Result<(Url, Url, String), Box<dyn Error + Send + Sync>> {
+    let (base_url, response) = mirrors
+        .send(|base_url| {
+            let mut request = client.get(base_url.join(&format!("view/{}", id))?);
+            if let Some(timeout) = timeout {
+                request = request.timeout(Duration::from_secs(timeout));
+            }
+            Ok(request)
+        })
+        .await?;
+    let url = response.url().to_owned();
+    if response.status() != StatusCode::OK {
+        // Throw error if response code is not OK
+        return Err(SourceError::http(response.status(), &url).into());
+    }
+    let content = response.bytes().await?;
+    Ok((base_url, url, std::str::from_utf8(&content[..])?.to_owned()))
+}
+
+/// Fetch a single torrent from its `/view/<id>` page on nyaa or sukebei.
+/// Only the fields needed for downloading are filled in.
+pub async fn view_item(
+    client: &reqwest::Client,
+    mirrors: &Mirrors,
+    timeout: Option<u64>,
+    prefix: &str,
+    id: usize,
+) -> Result<Item, Box<dyn Error + Send + Sync>> {
+    let (base_url, url, content) = fetch_view(client, mirrors, timeout, id).await?;
+    let doc = Html::parse_document(&content);
+
+    let title_sel = &sel!(".panel-heading > h3.panel-title")?;
+    let magnet_sel = &sel!(".panel-footer a[href^=\"magnet:\"]")?;
+    let root = doc.root_element();
+
+    let title = root
+        .select(title_sel)
+        .next()
+        .map(|e| e.text().collect::<String>().trim().to_owned())
+        .unwrap_or_default();
+    let torrent_link = base_url
+        .join(&format!("download/{}.torrent", id))
+        .map(Into::into)
+        .unwrap_or("null".to_owned());
+
+    let id = format!("{}-{}", prefix, id);
+    Ok(Item {
+        title,
+        torrent_link,
+        magnet_link: attr(root, magnet_sel, "href"),
+        post_link: url.into(),
+        file_name: format!("{}.torrent", id),
+        id,
+        ..Default::default()
+    })
+}
+
+/// Numeric id of an item from nyaa or sukebei, as used in its `/view/<id>` page
+pub fn view_id(item: &Item) -> Result<usize, String> {
+    item.id
+        .rsplit('-')
+        .next()
+        .and_then(|id| id.parse::<usize>().ok())
+        .ok_or(format!("Invalid id \"{}\"", item.id))
+}
+
+/// Fetch the description, files and comments of a torrent from its
+/// `/view/<id>` page on nyaa or sukebei
+pub async fn view_details(
+    client: &reqwest::Client,
+    mirrors: &Mirrors,
+    timeout: Option<u64>,
+    id: usize,
+) -> Result<ItemDetails, SourceError> {
+    let (_, _, content) = fetch_view(client, mirrors, timeout, id).await?;
+    Ok(parse_details(&content)?)
+}
+
+pub fn parse_details(content: &str) -> Result<ItemDetails, String> {
+    let doc = Html::parse_document(content);
+    let root = doc.root_element();
+
+    let title_sel = &sel!(".panel-heading > h3.panel-title")?;
+    let label_sel = &sel!(".panel-body > .row > div")?;
+    let hash_sel = &sel!(".panel-body kbd")?;
+    let desc_sel = &sel!("#torrent-description")?;
+    let file_sel = &sel!(".torrent-file-list li")?;
+    let count_sel = &sel!("#comments .panel-heading .panel-title")?;
+    let comment_sel = &sel!("#comments .comment-panel")?;
+    let author_sel = &sel!(".col-md-2 > p")?;
+    let author_link_sel = &sel!("a")?;
+    let comment_date_sel = &sel!(".comment-details small")?;
+    let comment_body_sel = &sel!(".comment-content")?;
+
+    let text = |e: ElementRef| e.text().collect::<String>().trim().to_owned();
+
+    // Fields are listed as a label followed by the value in the next column
+    let field = |label: &str| {
+        root.select(label_sel)
+            .find(|e| text(*e) == label)
+            .and_then(|e| e.next_siblings().find_map(ElementRef::wrap))
+            .map(text)
+            .unwrap_or_default()
+    };
+
+    let files = root
+        .select(file_sel)
+        .map(|li| {
+            // Nested folders are nested lists inside the list of files
+            let depth = li
+                .ancestors()
+                .filter_map(ElementRef::wrap)
+                .filter(|e| e.value().name() == "ul")
+                .count()
+                .saturating_sub(1);
+            let mut children = li.children().filter_map(ElementRef::wrap);
+            match children.find(|c| c.value().classes().any(|c| c == "folder")) {
+                Some(folder) => FileEntry {
+                    name: text(folder),
+                    size: None,
+                    depth,
+                },
+                None => FileEntry {
+                    name: li
+                        .children()
+                        .filter_map(|c| c.value().as_text().map(|t| t.to_string()))
+                        .collect::<String>()
+                        .trim()
+                        .to_owned(),
+                    size: li
+                        .children()
+                        .filter_map(ElementRef::wrap)
+                        .find(|c| c.value().classes().any(|c| c == "file-size"))
+                        .map(|s| text(s).trim_matches(['(', ')']).to_owned()),
+                    depth,
+                },
+            }
+        })
+        .collect();
+
+    let comments = root
+        .select(comment_sel)
+        .map(|e| {
+            let author = e.select(author_sel).next();
+            let link = author.and_then(|a| a.select(author_link_sel).next());
+            Comment {
+                author: link.or(author).map(text).unwrap_or_default(),
+                date: e
+                    .select(comment_date_sel)
+                    .next()
+                    .map(text)
+                    .unwrap_or_default(),
+                body: e
+                    .select(comment_body_sel)
+                    .next()
+                    .map(text)
+                    .unwrap_or_default(),
+                trusted: link.is_some_and(|a| {
+                    a.value().classes().any(|c| c == "text-success")
+                        || a.value().attr("title") == Some("Trusted")
+                }),
+                uploader: author.is_some_and(|a| text(a).ends_with("(uploader)")),
+            }
+        })
+        .collect::<Vec<Comment>>();
+
+    // The heading reads "Comments - <count>"
+    let comment_count = root
+        .select(count_sel)
+        .next()
+        .and_then(|e| text(e).rsplit(' ').next()?.parse().ok())
+        .unwrap_or(comments.len());
+
+    Ok(ItemDetails {
+        title: root.select(title_sel).next().map(text).unwrap_or_default(),
+        submitter: field("Submitter:"),
+        info_hash: root.select(hash_sel).next().map(text).unwrap_or_default(),
+        description: root.select(desc_sel).next().map(text).unwrap_or_default(),
+        files,
+        comment_count,
+        comments,
+    })
+}
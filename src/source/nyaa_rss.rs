@@ -1,20 +1,22 @@
-use std::fmt::Write;
-use std::{cmp::Ordering, collections::BTreeMap, error::Error, str::FromStr, time::Duration};
+use std::{cmp::Ordering, collections::BTreeMap, str::FromStr, time::Duration};
 
-use chrono::{DateTime, Local};
+use chrono::{DateTime, Utc};
 use reqwest::StatusCode;
 use rss::{extension::Extension, Channel};
 use urlencoding::encode;
 
 use crate::{
+    collection,
     results::ResultResponse,
     sync::SearchQuery,
-    util::{self, conv::to_bytes},
+    util::conv::to_bytes,
     widget::sort::{SelectedSort, SortDir},
 };
 
 use super::{
-    add_protocol, nyaa_html::NyaaSort, Item, ItemType, Source, SourceExtraConfig, SourceResponse,
+    mirror::{self, Mirrors},
+    nyaa_html::NyaaSort,
+    InfoHash, Item, ItemType, Source, SourceError, SourceResponse,
 };
 
 type ExtensionMap = BTreeMap<String, Vec<Extension>>;
@@ -33,8 +35,8 @@ pub fn sort_items(items: &mut [Item], sort: SelectedSort) {
         Some(NyaaSort::Downloads) => |a, b| b.downloads.cmp(&a.downloads),
         Some(NyaaSort::Seeders) => |a, b| b.seeders.cmp(&a.seeders),
         Some(NyaaSort::Leechers) => |a, b| b.leechers.cmp(&a.leechers),
-        Some(NyaaSort::Size) => |a, b| b.bytes.cmp(&a.bytes),
-        _ => |a, b| a.id.cmp(&b.id),
+        Some(NyaaSort::Size) => |a, b| b.size.cmp(&a.size),
+        _ => |a, b| b.date.cmp(&a.date),
     };
     items.sort_by(f);
     if sort.dir == SortDir::Asc {
@@ -42,13 +44,13 @@ pub fn sort_items(items: &mut [Item], sort: SelectedSort) {
     }
 }
 
-pub async fn search_rss<S: Source>(
-    base_url: String,
+pub async fn search_rss(
+    source: &dyn Source,
+    mirrors: &Mirrors,
     timeout: Option<u64>,
     client: &reqwest::Client,
     search: &SearchQuery,
-    extra: &SourceExtraConfig,
-) -> Result<SourceResponse, Box<dyn Error + Send + Sync>> {
+) -> Result<SourceResponse, SourceError> {
     let query = search.query.to_owned();
     let cat = search.category;
     let filter = search.filter;
@@ -56,24 +58,25 @@ pub async fn search_rss<S: Source>(
     let last_page = 1;
     let (high, low) = (cat / 10, cat % 10);
     let query = encode(&query);
-    let base_url = add_protocol(base_url, true)?;
-
-    let mut url = base_url.clone();
     let query = format!(
         "page=rss&f={}&c={}_{}&q={}&u={}&m",
         filter, high, low, query, user
     );
-    url.set_query(Some(&query));
-
-    let mut request = client.get(url.to_owned());
-    if let Some(timeout) = timeout {
-        request = request.timeout(Duration::from_secs(timeout));
-    }
-    let response = request.send().await?;
-    let code = response.status().as_u16();
-    if code != StatusCode::OK {
+    let (base_url, response) = mirrors
+        .send(|base_url| {
+            let mut url = base_url.clone();
+            url.set_query(Some(&query));
+            let mut request = client.get(url);
+            if let Some(timeout) = timeout {
+                request = request.timeout(Duration::from_secs(timeout));
+            }
+            Ok(request)
+        })
+        .await?;
+    let url = response.url().to_owned();
+    if response.status() != StatusCode::OK {
         // Throw error if response code is not OK
-        return Err(format!("{}\nInvalid response code: {}", url, code).into());
+        return Err(SourceError::http(response.status(), &url));
     }
 
     let bytes = response.bytes().await?;
@@ -90,15 +93,14 @@ pub async fn search_rss<S: Source>(
                                                                         // `https://nyaa.si/view/{id}`
             let id_usize = id.parse::<usize>().ok()?;
             let category_str = get_ext_value::<String>(ext, "categoryId");
-            let cat = S::info().entry_from_str(&category_str);
+            let cat = source.info().entry_from_str(&category_str);
             let category = cat.id;
             let icon = cat.icon.clone();
-            let size = get_ext_value::<String>(ext, "size")
-                .replace('i', "")
-                .replace("Bytes", "B");
+            let size = to_bytes(&get_ext_value::<String>(ext, "size"));
             let pub_date = item.pub_date().unwrap_or("");
-            let date_time = DateTime::parse_from_rfc2822(pub_date).unwrap_or_default();
-            let date_time = date_time.with_timezone(&Local);
+            let date = DateTime::parse_from_rfc2822(pub_date)
+                .map(|d| d.with_timezone(&Utc))
+                .unwrap_or_default();
             let torrent_link = base_url
                 .join(&format!("/download/{}.torrent", id))
                 .map(Into::into)
@@ -110,20 +112,7 @@ pub async fn search_rss<S: Source>(
                 (_, true) => ItemType::Remake,
                 _ => ItemType::None,
             };
-            let date_format = extra
-                .date_format
-                .to_owned()
-                .unwrap_or("%Y-%m-%d %H:%M".to_owned());
-
-            let date = if extra.relative_date.unwrap_or(false) {
-                util::conv::to_relative_date(date_time, extra.relative_date_short.unwrap_or(false))
-            } else {
-                let mut newstr = String::new();
-                if write!(newstr, "{}", date_time.format(&date_format)).is_err() {
-                    newstr = format!("Invalid format string: `{}`", date_format);
-                }
-                newstr
-            };
+            let magnet_link = item.link().unwrap_or("???").to_owned();
 
             Some(Item {
                 id: format!("nyaa-{}", id_usize),
@@ -131,17 +120,22 @@ pub async fn search_rss<S: Source>(
                 seeders: get_ext_value(ext, "seeders"),
                 leechers: get_ext_value(ext, "leechers"),
                 downloads: get_ext_value(ext, "downloads"),
-                bytes: to_bytes(&size),
                 size,
                 title: item.title().unwrap_or("???").to_owned(),
                 torrent_link,
-                magnet_link: item.link().unwrap_or("???").to_owned(),
+                hash: get_ext_value::<String>(ext, "infoHash")
+                    .parse()
+                    .ok()
+                    .or_else(|| InfoHash::from_magnet(&magnet_link)),
+                magnet_link,
                 post_link: post,
                 file_name: format!("{}.torrent", id),
                 item_type,
                 category,
                 icon,
-                ..Default::default()
+                extra: collection![
+                    "comments".to_owned() => get_ext_value::<u32>(ext, "comments").to_string(),
+                ],
             })
         })
         .collect();
@@ -151,5 +145,7 @@ pub async fn search_rss<S: Source>(
         items,
         last_page,
         total_results,
+        mirror: Some(mirror::display_name(&base_url)),
+        ..Default::default()
     }))
 }
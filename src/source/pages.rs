@@ -0,0 +1,149 @@
+use std::{cmp::Ordering, collections::HashSet};
+
+use serde::{Deserialize, Serialize};
+use tokio::task::JoinSet;
+
+use crate::{app::LoadType, sync::SearchQuery, widget::sort::SortDir};
+
+use super::{
+    release::ReleaseField, Item, SourceConfig, SourceError, SourceExtraConfig, SourceResponse,
+    Sources,
+};
+
+/// Loading every page of a search at once, so the results can be sorted and paginated locally
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(default)]
+pub struct AllPagesConfig {
+    /// Most pages to load, the rest of the results are left out
+    pub max_pages: usize,
+    /// Pages requested at the same time
+    pub concurrency: usize,
+    /// Results shown on each page once loaded
+    pub page_size: usize,
+}
+
+impl Default for AllPagesConfig {
+    fn default() -> Self {
+        Self {
+            max_pages: 20,
+            concurrency: 3,
+            page_size: 75,
+        }
+    }
+}
+
+/// Sort items locally by the name of one of the sorts of a source.
+/// Items are left in the order they were loaded in for sorts which can't be done locally.
+pub fn sort_items(items: &mut [Item], sort: &str, dir: SortDir) {
+    if let Ok(field) = sort.parse::<ReleaseField>() {
+        return sort_by(items, dir, |a, b| field.compare(a, b));
+    }
+    let f: fn(&Item, &Item) -> Ordering = match sort {
+        "Date" => |a, b| a.date.cmp(&b.date),
+        "Downloads" => |a, b| a.downloads.cmp(&b.downloads),
+        "Seeders" => |a, b| a.seeders.cmp(&b.seeders),
+        "Leechers" => |a, b| a.leechers.cmp(&b.leechers),
+        "Size" => |a, b| a.size.cmp(&b.size),
+        "Name" | "Title" => |a, b| a.title.to_lowercase().cmp(&b.title.to_lowercase()),
+        _ => return,
+    };
+    sort_by(items, dir, f);
+}
+
+fn sort_by(items: &mut [Item], dir: SortDir, f: impl Fn(&Item, &Item) -> Ordering) {
+    match dir {
+        SortDir::Asc => items.sort_by(f),
+        SortDir::Desc => items.sort_by(|a, b| f(b, a)),
+    }
+}
+
+/// Load the pages of a search from the first up to `max_pages`, merging their items into one
+/// response sorted locally. `on_progress` is called with the number of pages done out of the total
+/// as each page loads. Pages which fail are reported in the errors of the response, unless the
+/// first page fails.
+pub async fn load_all(
+    src: Sources,
+    client: &reqwest::Client,
+    search: &SearchQuery,
+    config: &SourceConfig,
+    extra: &SourceExtraConfig,
+    pages: AllPagesConfig,
+    on_progress: impl Fn(usize, usize),
+) -> Result<SourceResponse, SourceError> {
+    let first = SearchQuery {
+        page: 1,
+        ..search.to_owned()
+    };
+    // Only infallible without captchas
+    #[allow(clippy::infallible_destructuring_match)]
+    let mut res = match src
+        .load(LoadType::Searching, client, &first, config, extra)
+        .await?
+    {
+        SourceResponse::Results(res) => res,
+        #[cfg(feature = "captcha")]
+        captcha => return Ok(captcha),
+    };
+
+    let last_page = res.last_page.clamp(1, pages.max_pages.max(1));
+    if res.last_page > last_page {
+        res.errors.push(format!(
+            "Only the first {} of {} pages were loaded",
+            last_page, res.last_page
+        ));
+    }
+    let mut done = 1;
+    on_progress(done, last_page);
+
+    let mut loaded = vec![(1, std::mem::take(&mut res.items))];
+    let mut tasks = JoinSet::new();
+    let mut next = 2;
+    while next <= last_page || !tasks.is_empty() {
+        while next <= last_page && tasks.len() < pages.concurrency.max(1) {
+            let search = SearchQuery {
+                page: next,
+                ..search.to_owned()
+            };
+            let (client, config, extra) = (client.clone(), config.clone(), extra.clone());
+            tasks.spawn(async move {
+                let res = src
+                    .load(LoadType::Searching, &client, &search, &config, &extra)
+                    .await;
+                (search.page, res)
+            });
+            next += 1;
+        }
+        let Some(joined) = tasks.join_next().await else {
+            break;
+        };
+        done += 1;
+        on_progress(done, last_page);
+        match joined {
+            Ok((page, Ok(SourceResponse::Results(page_res)))) => {
+                loaded.push((page, page_res.items))
+            }
+            #[cfg(feature = "captcha")]
+            Ok((page, Ok(SourceResponse::Captcha(_)))) => res
+                .errors
+                .push(format!("Page {}:\nA captcha must be solved first", page)),
+            Ok((page, Err(e))) => res.errors.push(format!("Page {}:\n{}", page, e)),
+            Err(e) => res.errors.push(e.to_string()),
+        }
+    }
+
+    // Items move onto the next page when new ones are uploaded while loading, only keep them once
+    loaded.sort_by_key(|(page, _)| *page);
+    let mut ids = HashSet::new();
+    let mut items: Vec<Item> = loaded
+        .into_iter()
+        .flat_map(|(_, items)| items)
+        .filter(|i| i.id.is_empty() || ids.insert(i.id.clone()))
+        .collect();
+    if let Some(sort) = src.info().sorts.get(search.sort.sort) {
+        sort_items(&mut items, sort, search.sort.dir);
+    }
+    res.total_results = items.len();
+    res.last_page = 1;
+    res.items = items;
+    Ok(SourceResponse::Results(res))
+}
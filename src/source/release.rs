@@ -0,0 +1,279 @@
+use std::{cmp::Ordering, str::FromStr, sync::OnceLock};
+
+use regex::Regex;
+
+use super::Item;
+
+/// Compile a regex once, on first use
+macro_rules! re {
+    ($re:literal) => {{
+        static RE: OnceLock<Regex> = OnceLock::new();
+        RE.get_or_init(|| Regex::new($re).unwrap())
+    }};
+}
+
+/// Parts of a release name like `[SubsPlease] Show - 12 (1080p) [ABCD1234].mkv`
+#[derive(Clone, Default, Debug, PartialEq, Eq)]
+pub struct Release {
+    pub group: Option<String>,
+    pub series: Option<String>,
+    /// Episode as written, e.g. `12`, `12v2`, `01-12` or `S01E05`
+    pub episode: Option<String>,
+    /// Vertical resolution, e.g. `1080p`
+    pub resolution: Option<String>,
+    /// Video codec, `HEVC` and `AVC` also stand for x265/H.265 and x264/H.264
+    pub codec: Option<String>,
+    /// Where the video was ripped from, one of `BD`, `WEB`, `TV` or `DVD`
+    pub source: Option<String>,
+    /// CRC32 checksum of the file, in uppercase
+    pub crc: Option<String>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ReleaseField {
+    Group,
+    Series,
+    Episode,
+    Resolution,
+    Codec,
+    Source,
+    Crc,
+}
+
+impl ReleaseField {
+    pub const ALL: [ReleaseField; 7] = [
+        ReleaseField::Group,
+        ReleaseField::Series,
+        ReleaseField::Episode,
+        ReleaseField::Resolution,
+        ReleaseField::Codec,
+        ReleaseField::Source,
+        ReleaseField::Crc,
+    ];
+
+    /// Key of the field in the extra of an item. The source is stored as `media`, since `source`
+    /// names the site of merged results.
+    pub fn key(self) -> &'static str {
+        match self {
+            ReleaseField::Group => "group",
+            ReleaseField::Series => "series",
+            ReleaseField::Episode => "episode",
+            ReleaseField::Resolution => "resolution",
+            ReleaseField::Codec => "codec",
+            ReleaseField::Source => "media",
+            ReleaseField::Crc => "crc",
+        }
+    }
+
+    /// Value of the field for an item, parsed from its title if it wasn't loaded through a source
+    pub fn get(self, item: &Item) -> Option<String> {
+        if let Some(value) = item.extra.get(self.key()) {
+            return Some(value.to_owned());
+        }
+        // Parts missing from the title are left out of items which were parsed already
+        match ReleaseField::ALL
+            .iter()
+            .any(|f| item.extra.contains_key(f.key()))
+        {
+            true => None,
+            false => Release::parse(&item.title).get(self).map(ToOwned::to_owned),
+        }
+    }
+
+    /// Order two items by the field, comparing episodes and resolutions as numbers.
+    /// Items without the field come first.
+    pub fn compare(self, a: &Item, b: &Item) -> Ordering {
+        let (a, b) = (self.get(a), self.get(b));
+        match self {
+            ReleaseField::Episode => a
+                .as_deref()
+                .and_then(episode_number)
+                .cmp(&b.as_deref().and_then(episode_number)),
+            ReleaseField::Resolution => a
+                .as_deref()
+                .and_then(leading_number)
+                .cmp(&b.as_deref().and_then(leading_number)),
+            _ => a
+                .map(|a| a.to_lowercase())
+                .cmp(&b.map(|b| b.to_lowercase())),
+        }
+    }
+}
+
+impl FromStr for ReleaseField {
+    type Err = ();
+
+    /// Names of the field in sorts and local filters
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "group" => Ok(ReleaseField::Group),
+            "series" | "show" => Ok(ReleaseField::Series),
+            "episode" | "ep" => Ok(ReleaseField::Episode),
+            "resolution" | "res" => Ok(ReleaseField::Resolution),
+            "codec" => Ok(ReleaseField::Codec),
+            "source" | "media" => Ok(ReleaseField::Source),
+            "crc" => Ok(ReleaseField::Crc),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Episode as a number to compare, with the season for `S01E05`
+pub fn episode_number(episode: &str) -> Option<u64> {
+    if let Some(c) = re!(r"(?i)^S(\d+)E(\d+)").captures(episode) {
+        return Some(c[1].parse::<u64>().ok()? * 10000 + c[2].parse::<u64>().ok()?);
+    }
+    leading_number(episode)
+}
+
+fn leading_number(s: &str) -> Option<u64> {
+    let end = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+    s[..end].parse().ok()
+}
+
+impl Release {
+    pub fn parse(title: &str) -> Release {
+        let name = re!(r"(?i)\.(mkv|mp4|avi|webm|m4v|ts)$").replace(title.trim(), "");
+        let name = name.trim();
+        // Tags in brackets or parentheses, e.g. [Group], [1080p] or (BD 1080p HEVC)
+        let tags: Vec<&str> = re!(r"[\[(]([^\[\]()]*)[\])]")
+            .captures_iter(name)
+            .filter_map(|c| c.get(1).map(|t| t.as_str().trim()))
+            .collect();
+
+        let crc = tags
+            .iter()
+            .find(|t| re!(r"^[0-9A-Fa-f]{8}$").is_match(t))
+            .map(|t| t.to_uppercase());
+        let resolution = re!(r"(?i)\b(\d{3,4})[pi]\b")
+            .captures(name)
+            .map(|c| format!("{}p", &c[1]))
+            .or_else(|| {
+                re!(r"\b\d{3,4}x(\d{3,4})\b")
+                    .captures(name)
+                    .map(|c| format!("{}p", &c[1]))
+            })
+            .or_else(|| {
+                re!(r"(?i)\b(4K|UHD)\b")
+                    .is_match(name)
+                    .then(|| "2160p".to_owned())
+            });
+        let codec = re!(r"(?i)\b(x\.?265|h\.?265|hevc|x\.?264|h\.?264|avc|av1|vp9|xvid)\b")
+            .captures(name)
+            .map(|c| match c[1].to_lowercase().replace('.', "").as_str() {
+                "x265" | "h265" | "hevc" => "HEVC".to_owned(),
+                "x264" | "h264" | "avc" => "AVC".to_owned(),
+                "xvid" => "XviD".to_owned(),
+                codec => codec.to_uppercase(),
+            });
+        let source =
+            re!(r"(?i)\b(blu-?ray|bdrip|bdremux|bdmv|bd|web-?dl|web-?rip|web|hdtv|dvdrip|dvd)\b")
+                .captures(name)
+                .map(|c| {
+                    let source = c[1].to_lowercase();
+                    match source.as_str() {
+                        s if s.starts_with('b') => "BD",
+                        s if s.starts_with("web") => "WEB",
+                        s if s.starts_with("dvd") => "DVD",
+                        _ => "TV",
+                    }
+                    .to_owned()
+                });
+
+        // Fansubs start with the group, scene releases end with it, e.g. `Show.S01E05.1080p-GROUP`
+        let (group, body) = match name.strip_prefix('[').and_then(|n| n.split_once(']')) {
+            Some((group, body)) => (Some(group.trim().to_owned()), body.trim()),
+            None => {
+                let group = re!(r"^\S+-([A-Za-z0-9]+)$")
+                    .captures(name)
+                    .map(|c| c[1].to_owned());
+                (group, name)
+            }
+        };
+
+        let (series, episode) = if let Some(c) =
+            re!(r"(?i)^(.*?)[ ._-]*\bS(\d{1,2})E(\d{1,4})\b").captures(body)
+        {
+            (
+                Some(c[1].to_owned()),
+                Some(format!("S{:0>2}E{:0>2}", &c[2], &c[3])),
+            )
+        } else if let Some(c) = re!(
+            r"^(.*?)\s+-\s+(\d{1,4}(?:\.\d)?(?:v\d)?(?:\s*[-~]\s*\d{1,4}(?:v\d)?)?)(?:\s|$|\[|\()"
+        )
+        .captures(body)
+        {
+            (Some(c[1].to_owned()), Some(c[2].replace(' ', "")))
+        } else if let Some(c) =
+            re!(r"(?i)^(.*?)\s*\b(?:ep?|episode)\.? ?(\d{1,4})\b").captures(body)
+        {
+            (Some(c[1].to_owned()), Some(c[2].to_owned()))
+        } else {
+            // Up to the first tag or resolution
+            let end = re!(r"(?i)[\[(]|\b\d{3,4}[pi]\b")
+                .find(body)
+                .map(|m| m.start())
+                .unwrap_or(body.len());
+            (Some(body[..end].to_owned()), None)
+        };
+        let series = series
+            .map(|s| match s.contains(' ') {
+                true => s,
+                // Scene releases separate words with dots
+                false => s.replace(['.', '_'], " "),
+            })
+            .map(|s| {
+                s.trim_matches(|c: char| c.is_whitespace() || c == '-')
+                    .to_owned()
+            })
+            .filter(|s| !s.is_empty());
+
+        Release {
+            group: group.filter(|g| !g.is_empty()),
+            series,
+            episode,
+            resolution,
+            codec,
+            source,
+            crc,
+        }
+    }
+
+    pub fn get(&self, field: ReleaseField) -> Option<&str> {
+        match field {
+            ReleaseField::Group => self.group.as_deref(),
+            ReleaseField::Series => self.series.as_deref(),
+            ReleaseField::Episode => self.episode.as_deref(),
+            ReleaseField::Resolution => self.resolution.as_deref(),
+            ReleaseField::Codec => self.codec.as_deref(),
+            ReleaseField::Source => self.source.as_deref(),
+            ReleaseField::Crc => self.crc.as_deref(),
+        }
+    }
+}
+
+/// Width of a column of `field`, fitting its longest value up to `max` and its header with the
+/// sort arrow
+pub fn column_width(items: &[Item], field: ReleaseField, header: &str, max: u16) -> u16 {
+    let longest = items
+        .iter()
+        .filter_map(|i| field.get(i))
+        .map(|v| v.chars().count())
+        .max()
+        .unwrap_or_default() as u16;
+    longest.min(max).max(header.len() as u16 + 2)
+}
+
+/// Add the parts of the release name of each item to its extra, keeping values set by the source
+pub fn annotate(items: &mut [Item]) {
+    for item in items {
+        let release = Release::parse(&item.title);
+        for field in ReleaseField::ALL {
+            if let Some(value) = release.get(field) {
+                item.extra
+                    .entry(field.key().to_owned())
+                    .or_insert_with(|| value.to_owned());
+            }
+        }
+    }
+}
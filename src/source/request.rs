@@ -0,0 +1,172 @@
+use std::{
+    fmt::Display,
+    future::Future,
+    sync::{Arc, RwLock},
+    time::{Duration, SystemTime},
+};
+
+use chrono::{DateTime, Utc};
+use reqwest::{header::RETRY_AFTER, RequestBuilder, Response, StatusCode};
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(default)]
+pub struct RetryConfig {
+    /// Times to retry a request after the first attempt fails
+    pub retries: u32,
+    /// Delay before the first retry in milliseconds, doubled after each retry
+    pub backoff: u64,
+    /// Longest delay between two attempts in seconds, including delays asked for by `Retry-After`
+    pub max_backoff: u64,
+}
+
+const DEFAULT_RETRY: RetryConfig = RetryConfig {
+    retries: 3,
+    backoff: 500,
+    max_backoff: 30,
+};
+
+impl Default for RetryConfig {
+    fn default() -> Self {
+        DEFAULT_RETRY
+    }
+}
+
+static CONFIG: RwLock<RetryConfig> = RwLock::new(DEFAULT_RETRY);
+
+/// Set the retry config used by every source request
+pub fn configure(config: RetryConfig) {
+    *CONFIG.write().unwrap_or_else(|e| e.into_inner()) = config;
+}
+
+pub fn config() -> RetryConfig {
+    *CONFIG.read().unwrap_or_else(|e| e.into_inner())
+}
+
+/// A request which is about to be retried
+#[derive(Clone)]
+pub struct Retry {
+    pub attempt: u32,
+    pub retries: u32,
+    pub delay: Duration,
+    pub reason: String,
+}
+
+impl Display for Retry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}, retry {}/{} in {}s",
+            self.reason,
+            self.attempt,
+            self.retries,
+            self.delay.as_secs_f64().ceil()
+        )
+    }
+}
+
+type OnRetry = Arc<dyn Fn(Retry) + Send + Sync>;
+
+tokio::task_local! {
+    static ON_RETRY: OnRetry;
+}
+
+/// Run `future`, calling `on_retry` before each retry of the requests it sends
+pub async fn report<F: Future>(
+    on_retry: impl Fn(Retry) + Send + Sync + 'static,
+    future: F,
+) -> F::Output {
+    ON_RETRY.scope(Arc::new(on_retry), future).await
+}
+
+fn is_transient(status: StatusCode) -> bool {
+    matches!(
+        status,
+        StatusCode::TOO_MANY_REQUESTS
+            | StatusCode::BAD_GATEWAY
+            | StatusCode::SERVICE_UNAVAILABLE
+            | StatusCode::GATEWAY_TIMEOUT
+    )
+}
+
+/// Delay asked for by the server, either in seconds or as a date
+fn retry_after(response: &Response) -> Option<Duration> {
+    let value = response.headers().get(RETRY_AFTER)?.to_str().ok()?.trim();
+    if let Ok(secs) = value.parse::<u64>() {
+        return Some(Duration::from_secs(secs));
+    }
+    let date = DateTime::parse_from_rfc2822(value).ok()?;
+    (date.with_timezone(&Utc) - Utc::now()).to_std().ok()
+}
+
+/// Exponential backoff for the given retry, with up to half of it randomly taken off
+fn backoff(config: &RetryConfig, attempt: u32) -> Duration {
+    let max = config.max_backoff.saturating_mul(1000);
+    let millis = config
+        .backoff
+        .saturating_mul(1u64 << (attempt - 1).min(32))
+        .min(max);
+    let nanos = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map(|d| d.subsec_nanos() as u64)
+        .unwrap_or_default();
+    let jitter = nanos % (millis / 2 + 1);
+    Duration::from_millis(millis - jitter)
+}
+
+/// Delay and reason to retry a response or error after, if it's worth retrying at all
+pub fn retry_delay(
+    config: &RetryConfig,
+    attempt: u32,
+    res: &reqwest::Result<Response>,
+) -> Option<(Duration, String)> {
+    match res {
+        Ok(response) if is_transient(response.status()) => {
+            let delay = retry_after(response)
+                .map(|d| d.min(Duration::from_secs(config.max_backoff)))
+                .unwrap_or_else(|| backoff(config, attempt));
+            Some((delay, response.status().to_string()))
+        }
+        Err(e) if e.is_timeout() || e.is_connect() => {
+            let reason = match e.is_timeout() {
+                true => "Timed out",
+                false => "Failed to connect",
+            };
+            Some((backoff(config, attempt), reason.to_owned()))
+        }
+        _ => None,
+    }
+}
+
+/// Tell the task sending the request about a retry, then wait for its delay
+pub async fn wait(retry: Retry) {
+    let delay = retry.delay;
+    let _ = ON_RETRY.try_with(|on_retry| on_retry(retry));
+    tokio::time::sleep(delay).await;
+}
+
+/// Send a request, retrying on timeouts, connection errors and 429/502/503/504 responses.
+/// The last response is returned as-is once retries run out.
+pub async fn send(request: RequestBuilder) -> reqwest::Result<Response> {
+    let config = config();
+    let mut attempt = 0;
+    loop {
+        let retry = match (attempt < config.retries, request.try_clone()) {
+            (true, Some(req)) => req,
+            // Last attempt, or a body which can't be sent twice
+            _ => return request.send().await,
+        };
+        attempt += 1;
+        let res = retry.send().await;
+        let Some((delay, reason)) = retry_delay(&config, attempt, &res) else {
+            return res;
+        };
+        wait(Retry {
+            attempt,
+            retries: config.retries,
+            delay,
+            reason,
+        })
+        .await;
+    }
+}
@@ -0,0 +1,183 @@
+use std::{
+    collections::HashMap,
+    fmt::Display,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use chrono::{DateTime, Local, Utc};
+
+/// How well the rows of a scraped page could be read, to notice when a site changes its layout
+#[derive(Clone, Default, Debug)]
+pub struct ParseReport {
+    /// Rows matched by the row selector
+    pub matched: usize,
+    /// Rows which were turned into items
+    pub parsed: usize,
+    /// Fields which could not be read, along with the number of rows they failed in
+    pub failed: Vec<(&'static str, usize)>,
+    /// Fields which could not be read and were left empty, without dropping their rows
+    pub defaulted: Vec<(&'static str, usize)>,
+    /// Number of results the page claims to have, if it says so
+    pub expected: Option<usize>,
+    /// Whether the page looks like it lists results, like having a results table or pagination,
+    /// or not saying that nothing was found
+    pub listed: bool,
+    /// Html of the page, only kept when it looks broken
+    pub html: Option<String>,
+}
+
+impl ParseReport {
+    /// Read each matched row, counting the rows which fail along with the field which failed
+    pub fn collect<T>(rows: impl IntoIterator<Item = Result<T, &'static str>>) -> (Vec<T>, Self) {
+        let mut report = ParseReport::default();
+        let mut items = vec![];
+        for row in rows {
+            report.matched += 1;
+            match row {
+                Ok(item) => items.push(item),
+                Err(field) => count(&mut report.failed, field),
+            }
+        }
+        report.parsed = items.len();
+        (items, report)
+    }
+
+    /// Fields which were left empty in the rows read
+    pub fn defaults(mut self, defaults: Defaults) -> Self {
+        self.defaulted = defaults.0;
+        self
+    }
+
+    /// Whether the page looks like it lists results, so finding no rows means it is broken
+    pub fn listing(mut self, listed: bool) -> Self {
+        self.listed = listed;
+        self
+    }
+
+    /// Number of results the page claims to have
+    pub fn expect(mut self, expected: Option<usize>) -> Self {
+        self.expected = expected;
+        self
+    }
+
+    /// Keep the html of the page if it looks broken, so it can be saved for bug reports
+    pub fn page(mut self, html: &str) -> Self {
+        if self.is_broken() {
+            self.html = Some(html.to_owned());
+        }
+        self
+    }
+
+    /// Whether most rows were dropped or had a field left empty, or none matched although the
+    /// page lists results. A result count shown by the page is trusted over the rest of it.
+    pub fn is_broken(&self) -> bool {
+        match self.matched {
+            0 => self.expected.map_or(self.listed, |e| e > 0),
+            matched => {
+                self.parsed * 2 < matched || self.defaulted.iter().any(|(_, c)| c * 2 > matched)
+            }
+        }
+    }
+
+    /// Warning shown for a broken page of `src`, saving it into `dir` if given
+    pub fn warning(&self, src: &str, dir: Option<PathBuf>) -> String {
+        let msg = format!("{} may have changed its layout:\n{}", src, self);
+        match dir.map(|dir| self.dump(&dir, src)) {
+            Some(Ok(path)) => format!(
+                "{}\n\nThe page was saved to \"{}\" for bug reports",
+                msg,
+                path.display()
+            ),
+            Some(Err(e)) => format!("{}\n\nFailed to save the page:\n{}", msg, e),
+            None => msg,
+        }
+    }
+
+    /// Save the html of a broken page into `dir`, returning the file written
+    pub fn dump(&self, dir: &Path, src: &str) -> io::Result<PathBuf> {
+        fs::create_dir_all(dir)?;
+        let name: String = src
+            .chars()
+            .map(|c| if c.is_alphanumeric() { c } else { '_' })
+            .collect();
+        let path = dir.join(format!(
+            "{}-{}.html",
+            name.to_lowercase(),
+            Local::now().format("%Y%m%d-%H%M%S")
+        ));
+        let html = self.html.as_deref().unwrap_or_default();
+        fs::write(&path, format!("<!-- {} -->\n{}", self, html))?;
+        Ok(path)
+    }
+}
+
+impl Display for ParseReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.matched == 0 {
+            return match self.expected {
+                Some(expected) => write!(
+                    f,
+                    "No rows matched, although the page lists {} results",
+                    expected
+                ),
+                None => write!(
+                    f,
+                    "No rows matched, although the page does not say nothing was found"
+                ),
+            };
+        }
+        write!(f, "Read {} of {} rows", self.parsed, self.matched)?;
+        if let Some((field, count)) = self.failed.iter().max_by_key(|(_, c)| *c) {
+            write!(f, ", \"{}\" failed in {} of them", field, count)?;
+        }
+        if let Some((field, count)) = self.defaulted.iter().max_by_key(|(_, c)| *c) {
+            write!(f, ", \"{}\" was left empty in {} of them", field, count)?;
+        }
+        Ok(())
+    }
+}
+
+fn count(fields: &mut Vec<(&'static str, usize)>, field: &'static str) {
+    match fields.iter_mut().find(|(f, _)| *f == field) {
+        Some((_, count)) => *count += 1,
+        None => fields.push((field, 1)),
+    }
+}
+
+/// Fields of the rows being read which could not be, counted for the report instead of
+/// dropping their rows
+#[derive(Default)]
+pub struct Defaults(Vec<(&'static str, usize)>);
+
+impl Defaults {
+    /// The value read for `field`, or its default if it could not be read
+    pub fn or_default<T: Default>(&mut self, value: Option<T>, field: &'static str) -> T {
+        value.unwrap_or_else(|| {
+            count(&mut self.0, field);
+            T::default()
+        })
+    }
+
+    /// The date read for a row, or the epoch if it could not be. The scraped text is then kept
+    /// in `extra` as "date", to be shown as is.
+    pub fn date(
+        &mut self,
+        date: Option<DateTime<Utc>>,
+        raw: &str,
+        extra: &mut HashMap<String, String>,
+    ) -> DateTime<Utc> {
+        if date.is_none() && !raw.trim().is_empty() {
+            extra.insert("date".to_owned(), raw.trim().to_owned());
+        }
+        self.or_default(date, "date")
+    }
+}
+
+/// A field which must not be empty for the row to be read
+pub fn required(value: String, field: &'static str) -> Result<String, &'static str> {
+    match value.trim().is_empty() {
+        true => Err(field),
+        false => Ok(value),
+    }
+}
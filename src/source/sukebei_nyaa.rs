@@ -1,7 +1,6 @@
-use std::fmt::Write;
-use std::{error::Error, time::Duration};
+use std::time::Duration;
 
-use chrono::{DateTime, Local, NaiveDateTime, TimeZone};
+use chrono::NaiveDateTime;
 use ratatui::style::Color;
 use reqwest::StatusCode;
 use scraper::{Html, Selector};
@@ -10,25 +9,30 @@ use strum::VariantArray as _;
 use urlencoding::encode;
 
 use crate::{
-    cats,
+    cats, collection,
     results::ResultResponse,
     sel,
     sync::SearchQuery,
     theme::Theme,
     util::{
-        self,
         colors::color_to_tui,
-        conv::to_bytes,
-        html::{attr, inner},
+        conv::parse_bytes,
+        html::{as_type, attr, inner},
     },
     widget::sort::{SelectedSort, SortDir},
 };
 
 use super::SourceExtraConfig;
 use super::{
-    add_protocol,
-    nyaa_html::{nyaa_table, NyaaColumns, NyaaFilter, NyaaSort},
-    nyaa_rss, Item, ItemType, ResultTable, Source, SourceConfig, SourceInfo, SourceResponse,
+    boxed,
+    mirror::{self, Mirrors},
+    nyaa_html::{
+        nyaa_table, view_details, view_id, ItemDetails, NyaaColumns, NyaaFilter, NyaaSort,
+    },
+    nyaa_rss,
+    scrape::{required, Defaults, ParseReport},
+    InfoHash, Item, ItemType, ResultTable, Source, SourceConfig, SourceError, SourceFuture,
+    SourceInfo, SourceResponse, DEFAULT_DATE_FORMAT,
 };
 
 #[derive(Serialize, Deserialize, Clone, Copy, Default)]
@@ -76,6 +80,8 @@ impl Default for SukebeiCategoryTheme {
 #[serde(default)]
 pub struct SukebeiNyaaConfig {
     pub base_url: String,
+    /// Tried in order when `base_url` is down
+    pub mirrors: Vec<String>,
     pub default_sort: NyaaSort,
     pub default_sort_dir: SortDir,
     pub default_filter: NyaaFilter,
@@ -83,6 +89,8 @@ pub struct SukebeiNyaaConfig {
     pub default_search: String,
     pub rss: bool,
     pub timeout: Option<u64>,
+    /// Netscape `cookies.txt` exported from a browser, imported on startup
+    pub cookies: Option<String>,
     pub columns: Option<NyaaColumns>,
 }
 
@@ -90,6 +98,7 @@ impl Default for SukebeiNyaaConfig {
     fn default() -> Self {
         Self {
             base_url: "https://sukebei.nyaa.si/".to_owned(),
+            mirrors: vec![],
             default_sort: NyaaSort::Date,
             default_sort_dir: SortDir::Desc,
             default_filter: NyaaFilter::NoFilter,
@@ -97,127 +106,139 @@ impl Default for SukebeiNyaaConfig {
             default_search: Default::default(),
             rss: false,
             timeout: None,
+            cookies: None,
             columns: None,
         }
     }
 }
 
+impl SukebeiNyaaConfig {
+    pub fn mirrors(&self) -> Mirrors {
+        Mirrors::new("sukebei", &self.base_url, &self.mirrors)
+    }
+}
+
 pub struct SukebeiHtmlSource;
 
 impl Source for SukebeiHtmlSource {
-    async fn filter(
-        client: &reqwest::Client,
-        search: &SearchQuery,
-        config: &SourceConfig,
-        extra: &SourceExtraConfig,
-    ) -> Result<SourceResponse, Box<dyn Error + Send + Sync>> {
-        SukebeiHtmlSource::search(client, search, config, extra).await
-    }
-    async fn categorize(
-        client: &reqwest::Client,
-        search: &SearchQuery,
-        config: &SourceConfig,
-        extra: &SourceExtraConfig,
-    ) -> Result<SourceResponse, Box<dyn Error + Send + Sync>> {
-        SukebeiHtmlSource::search(client, search, config, extra).await
+    fn name(&self) -> String {
+        "Sukebei".to_owned()
     }
-    async fn sort(
-        client: &reqwest::Client,
-        search: &SearchQuery,
-        config: &SourceConfig,
-        extra: &SourceExtraConfig,
-    ) -> Result<SourceResponse, Box<dyn Error + Send + Sync>> {
-        let sukebei = config.sukebei.to_owned().unwrap_or_default();
-        let sort = search.sort;
-        let mut res = SukebeiHtmlSource::search(client, search, config, extra).await;
 
-        if sukebei.rss {
-            if let Ok(SourceResponse::Results(res)) = &mut res {
-                nyaa_rss::sort_items(&mut res.items, sort);
+    fn sort<'a>(
+        &'a self,
+        client: &'a reqwest::Client,
+        search: &'a SearchQuery,
+        config: &'a SourceConfig,
+        extra: &'a SourceExtraConfig,
+    ) -> SourceFuture<'a, SourceResponse> {
+        boxed(async move {
+            let sukebei = config.sukebei.to_owned().unwrap_or_default();
+            let sort = search.sort;
+            let mut res = self.search(client, search, config, extra).await;
+
+            if sukebei.rss {
+                if let Ok(SourceResponse::Results(res)) = &mut res {
+                    nyaa_rss::sort_items(&mut res.items, sort);
+                }
             }
-        }
-        res
+            res
+        })
     }
 
-    async fn search(
-        client: &reqwest::Client,
-        search: &SearchQuery,
-        config: &SourceConfig,
-        extra: &SourceExtraConfig,
-    ) -> Result<SourceResponse, Box<dyn Error + Send + Sync>> {
-        let sukebei = config.sukebei.to_owned().unwrap_or_default();
-        if sukebei.rss {
-            return nyaa_rss::search_rss::<Self>(
-                sukebei.base_url,
-                sukebei.timeout,
-                client,
-                search,
-                extra,
-            )
-            .await;
-        }
-        let cat = search.category;
-        let filter = search.filter;
-        let page = search.page;
-        let user = search.user.to_owned().unwrap_or_default();
-        let sort = NyaaSort::from_repr(search.sort.sort)
-            .unwrap_or(NyaaSort::Date)
-            .to_url();
-
-        let base_url = add_protocol(sukebei.base_url, true)?;
-        let (high, low) = (cat / 10, cat % 10);
-        let query = encode(&search.query);
-        let dir = search.sort.dir.to_url();
-        let mut url_query = base_url.clone();
-        url_query.set_query(Some(&format!(
-            "q={}&c={}_{}&f={}&p={}&s={}&o={}&u={}",
-            query, high, low, filter, page, sort, dir, user
-        )));
-
-        let mut request = client.get(url_query.to_owned());
-        if let Some(timeout) = sukebei.timeout {
-            request = request.timeout(Duration::from_secs(timeout));
-        }
-        let response = request.send().await?;
-        if response.status() != StatusCode::OK {
-            // Throw error if response code is not OK
-            let code = response.status().as_u16();
-            return Err(format!("{}\nInvalid response code: {}", url_query, code).into());
-        }
-        let content = response.bytes().await?;
-        let doc = Html::parse_document(std::str::from_utf8(&content[..])?);
-
-        let item_sel = &sel!("table.torrent-list > tbody > tr")?;
-        let icon_sel = &sel!("td:first-of-type > a")?;
-        let title_sel = &sel!("td:nth-of-type(2) > a:last-of-type")?;
-        let torrent_sel = &sel!("td:nth-of-type(3) > a:nth-of-type(1)")?;
-        let magnet_sel = &sel!("td:nth-of-type(3) > a:nth-of-type(2)")?;
-        let size_sel = &sel!("td:nth-of-type(4)")?;
-        let date_sel = &sel!("td:nth-of-type(5)").unwrap();
-        let seed_sel = &sel!("td:nth-of-type(6)")?;
-        let leech_sel = &sel!("td:nth-of-type(7)")?;
-        let dl_sel = &sel!("td:nth-of-type(8)")?;
-        let pagination_sel = &sel!(".pagination-page-info")?;
-
-        let mut last_page = 100;
-        let mut total_results = 7500;
-        // For searches, pagination has a description of total results found
-        if let Some(pagination) = doc.select(pagination_sel).next() {
-            // 6th word in pagination description contains total number of results
-            if let Some(num_results_str) = pagination.inner_html().split(' ').nth(5) {
-                if let Ok(num_results) = num_results_str.parse::<usize>() {
-                    last_page = (num_results + 74) / 75;
-                    total_results = num_results;
+    fn search<'a>(
+        &'a self,
+        client: &'a reqwest::Client,
+        search: &'a SearchQuery,
+        config: &'a SourceConfig,
+        _extra: &'a SourceExtraConfig,
+    ) -> SourceFuture<'a, SourceResponse> {
+        boxed(async move {
+            let sukebei = config.sukebei.to_owned().unwrap_or_default();
+            if sukebei.rss {
+                return nyaa_rss::search_rss(
+                    self,
+                    &sukebei.mirrors(),
+                    sukebei.timeout,
+                    client,
+                    search,
+                )
+                .await;
+            }
+            let cat = search.category;
+            let filter = search.filter;
+            let page = search.page;
+            let user = search.user.to_owned().unwrap_or_default();
+            let sort = NyaaSort::from_repr(search.sort.sort)
+                .unwrap_or(NyaaSort::Date)
+                .to_url();
+
+            let (high, low) = (cat / 10, cat % 10);
+            let query = encode(&search.query);
+            let dir = search.sort.dir.to_url();
+            let (base_url, response) = sukebei
+                .mirrors()
+                .send(|base_url| {
+                    let mut url_query = base_url.clone();
+                    url_query.set_query(Some(&format!(
+                        "q={}&c={}_{}&f={}&p={}&s={}&o={}&u={}",
+                        query, high, low, filter, page, sort, dir, user
+                    )));
+                    let mut request = client.get(url_query);
+                    if let Some(timeout) = sukebei.timeout {
+                        request = request.timeout(Duration::from_secs(timeout));
+                    }
+                    Ok(request)
+                })
+                .await?;
+            let url_query = response.url().to_owned();
+            if response.status() != StatusCode::OK {
+                // Throw error if response code is not OK
+                return Err(SourceError::http(response.status(), &url_query));
+            }
+            let content = response.bytes().await?;
+            let content = std::str::from_utf8(&content[..])?;
+            let doc = Html::parse_document(content);
+
+            let table_sel = &sel!("table.torrent-list")?;
+            let item_sel = &sel!("table.torrent-list > tbody > tr")?;
+            let icon_sel = &sel!("td:first-of-type > a")?;
+            let title_sel = &sel!("td:nth-of-type(2) > a:last-of-type")?;
+            let torrent_sel = &sel!("td:nth-of-type(3) > a:nth-of-type(1)")?;
+            let magnet_sel = &sel!("td:nth-of-type(3) > a:nth-of-type(2)")?;
+            let size_sel = &sel!("td:nth-of-type(4)")?;
+            let date_sel = &sel!("td:nth-of-type(5)").unwrap();
+            let seed_sel = &sel!("td:nth-of-type(6)")?;
+            let leech_sel = &sel!("td:nth-of-type(7)")?;
+            let dl_sel = &sel!("td:nth-of-type(8)")?;
+            let comments_sel = &sel!("td:nth-of-type(2) > a.comments")?;
+            let pagination_sel = &sel!(".pagination-page-info")?;
+
+            let mut last_page = 100;
+            let mut total_results = 7500;
+            let mut expected = None;
+            // For searches, pagination has a description of total results found
+            if let Some(pagination) = doc.select(pagination_sel).next() {
+                // 6th word in pagination description contains total number of results
+                if let Some(num_results_str) = pagination.inner_html().split(' ').nth(5) {
+                    if let Ok(num_results) = num_results_str.parse::<usize>() {
+                        last_page = num_results.div_ceil(75);
+                        total_results = num_results;
+                        expected = Some(num_results);
+                    }
                 }
             }
-        }
 
-        let items: Vec<Item> = doc
-            .select(item_sel)
-            .filter_map(|e| {
+            // Pages without results say so instead of showing an empty table
+            let listed = doc.select(table_sel).next().is_some()
+                || doc.select(pagination_sel).next().is_some()
+                || !content.contains("No results found");
+
+            let mut defaults = Defaults::default();
+            let (items, report) = ParseReport::collect(doc.select(item_sel).map(|e| {
                 let cat_str = attr(e, icon_sel, "href");
-                let cat_str = cat_str.split('=').last().unwrap_or("");
-                let cat = Self::info().entry_from_str(cat_str);
+                let cat_str = cat_str.split('=').next_back().unwrap_or("");
+                let cat = self.info().entry_from_str(cat_str);
                 let category = cat.id;
                 let icon = cat.icon.clone();
 
@@ -226,35 +247,27 @@ impl Source for SukebeiHtmlSource {
                     .join(&attr(e, title_sel, "href"))
                     .map(Into::into)
                     .unwrap_or("null".to_owned());
-                let id = post_link.split('/').last()?.parse::<usize>().ok()?;
+                let id = post_link
+                    .split('/')
+                    .next_back()
+                    .and_then(|id| id.parse::<usize>().ok())
+                    .ok_or("id")?;
                 let id = format!("sukebei-{}", id);
                 let file_name = format!("{}.torrent", id);
 
-                let size = inner(e, size_sel, "0 B")
-                    .replace('i', "")
-                    .replace("Bytes", "B");
-                let bytes = to_bytes(&size);
-
-                const DEFAULT_DATE_FORMAT: &str = "%Y-%m-%d %H:%M";
-                let date_format = extra.date_format.as_deref().unwrap_or(DEFAULT_DATE_FORMAT);
-                let date = inner(e, date_sel, "");
-                let naive = NaiveDateTime::parse_from_str(&date, date_format).unwrap_or_default();
-                let date_time: DateTime<Local> = Local.from_utc_datetime(&naive);
-
-                let date = if extra.relative_date.unwrap_or(false) {
-                    util::conv::to_relative_date(
-                        date_time,
-                        extra.relative_date_short.unwrap_or(false),
-                    )
-                } else {
-                    let mut newstr = String::new();
-                    if write!(newstr, "{}", date_time.format(date_format)).is_err() {
-                        newstr = format!("Invalid format string: `{}`", date_format);
-                    }
-                    newstr
-                };
-
-                let seeders = inner(e, seed_sel, "0").parse().unwrap_or(0);
+                let size = defaults.or_default(parse_bytes(&inner(e, size_sel, "")), "size");
+                // Dates are shown in UTC
+                let comments = as_type::<u32>(inner(e, comments_sel, "0")).unwrap_or_default();
+                let raw_date = inner(e, date_sel, "");
+                let date = NaiveDateTime::parse_from_str(&raw_date, DEFAULT_DATE_FORMAT)
+                    .ok()
+                    .map(|d| d.and_utc());
+                let mut extra = collection![
+                    "comments".to_owned() => comments.to_string(),
+                ];
+                let date = defaults.date(date, &raw_date, &mut extra);
+
+                let seeders = defaults.or_default(inner(e, seed_sel, "").parse().ok(), "seeders");
                 let leechers = inner(e, leech_sel, "0").parse().unwrap_or(0);
                 let downloads = inner(e, dl_sel, "0").parse().unwrap_or(0);
                 let torrent_link = base_url
@@ -262,6 +275,8 @@ impl Source for SukebeiHtmlSource {
                     .map(Into::into)
                     .unwrap_or("null".to_owned());
 
+                let magnet_link = attr(e, magnet_sel, "href");
+
                 let trusted = e.value().classes().any(|e| e == "success");
                 let remake = e.value().classes().any(|e| e == "danger");
                 let item_type = match (trusted, remake) {
@@ -270,52 +285,63 @@ impl Source for SukebeiHtmlSource {
                     _ => ItemType::None,
                 };
 
-                Some(Item {
+                Ok(Item {
                     id,
                     date,
                     seeders,
                     leechers,
                     downloads,
                     size,
-                    bytes,
-                    title: attr(e, title_sel, "title"),
+                    title: required(attr(e, title_sel, "title"), "title")?,
                     torrent_link,
-                    magnet_link: attr(e, magnet_sel, "href"),
+                    hash: InfoHash::from_magnet(&magnet_link),
+                    magnet_link,
                     post_link,
                     file_name: file_name.to_owned(),
                     category,
                     icon,
                     item_type,
-                    ..Default::default()
+                    extra,
                 })
-            })
-            .collect();
-        Ok(SourceResponse::Results(ResultResponse {
-            items,
-            last_page,
-            total_results,
-        }))
-        // Ok(nyaa_table(
-        //     items,
-        //     &theme,
-        //     &search.sort,
-        //     sukebei.columns,
-        //     last_page,
-        //     total_results,
-        // ))
+            }));
+            Ok(SourceResponse::Results(ResultResponse {
+                items,
+                last_page,
+                total_results,
+                mirror: Some(mirror::display_name(&base_url)),
+                report: Some(
+                    report
+                        .expect(expected)
+                        .defaults(defaults)
+                        .listing(listed)
+                        .page(content),
+                ),
+                ..Default::default()
+            }))
+            // Ok(nyaa_table(
+            //     items,
+            //     &theme,
+            //     &search.sort,
+            //     sukebei.columns,
+            //     last_page,
+            //     total_results,
+            // ))
+        })
     }
 
-    async fn solve(
-        _solution: String,
-        client: &reqwest::Client,
-        search: &SearchQuery,
-        config: &SourceConfig,
-        extra: &SourceExtraConfig,
-    ) -> Result<SourceResponse, Box<dyn Error + Send + Sync>> {
-        SukebeiHtmlSource::search(client, search, config, extra).await
+    fn details<'a>(
+        &'a self,
+        client: &'a reqwest::Client,
+        item: &'a Item,
+        config: &'a SourceConfig,
+    ) -> SourceFuture<'a, ItemDetails> {
+        boxed(async move {
+            let sukebei = config.sukebei.to_owned().unwrap_or_default();
+            view_details(client, &sukebei.mirrors(), sukebei.timeout, view_id(item)?).await
+        })
     }
 
-    fn info() -> SourceInfo {
+    fn info(&self) -> SourceInfo {
         let cats = cats! {
             "All Categories" => {
                 0 => ("---", "All Categories", "AllCategories", fg);
@@ -344,22 +370,22 @@ impl Source for SukebeiHtmlSource {
         }
     }
 
-    fn load_config(config: &mut SourceConfig) {
+    fn load_config(&self, config: &mut SourceConfig) {
         if config.sukebei.is_none() {
             config.sukebei = Some(SukebeiNyaaConfig::default());
         }
     }
 
-    fn default_category(cfg: &SourceConfig) -> usize {
+    fn default_category(&self, cfg: &SourceConfig) -> usize {
         let default = cfg
             .sukebei
             .as_ref()
             .map(|c| c.default_category.to_owned())
             .unwrap_or_default();
-        Self::info().entry_from_cfg(&default).id
+        self.info().entry_from_cfg(&default).id
     }
 
-    fn default_sort(cfg: &SourceConfig) -> SelectedSort {
+    fn default_sort(&self, cfg: &SourceConfig) -> SelectedSort {
         cfg.sukebei
             .as_ref()
             .map(|c| SelectedSort {
@@ -369,14 +395,14 @@ impl Source for SukebeiHtmlSource {
             .unwrap_or_default()
     }
 
-    fn default_filter(cfg: &SourceConfig) -> usize {
+    fn default_filter(&self, cfg: &SourceConfig) -> usize {
         cfg.sukebei
             .as_ref()
             .map(|c| c.default_filter as usize)
             .unwrap_or_default()
     }
 
-    fn default_search(cfg: &SourceConfig) -> String {
+    fn default_search(&self, cfg: &SourceConfig) -> String {
         cfg.sukebei
             .as_ref()
             .map(|c| c.default_search.to_owned())
@@ -384,12 +410,14 @@ impl Source for SukebeiHtmlSource {
     }
 
     fn format_table(
+        &self,
         items: &[Item],
         search: &SearchQuery,
         config: &SourceConfig,
+        extra: &SourceExtraConfig,
         theme: &Theme,
     ) -> ResultTable {
         let sukebei = config.sukebei.to_owned().unwrap_or_default();
-        nyaa_table(items.into(), theme, &search.sort, &sukebei.columns)
+        nyaa_table(items.into(), theme, &search.sort, &sukebei.columns, extra)
     }
 }
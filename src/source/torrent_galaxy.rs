@@ -5,11 +5,12 @@ use std::{
     time::{Duration, SystemTime, UNIX_EPOCH},
 };
 
+use chrono::NaiveDateTime;
 use ratatui::{
     layout::{Alignment, Constraint},
     style::{Color, Stylize},
 };
-use reqwest::{StatusCode, Url};
+use reqwest::{RequestBuilder, Response, StatusCode, Url};
 use scraper::{selectable::Selectable, Html, Selector};
 use serde::{Deserialize, Serialize};
 use strum::{FromRepr, VariantArray};
@@ -23,15 +24,20 @@ use crate::{
     theme::Theme,
     util::{
         colors::color_to_tui,
-        conv::{shorten_number, to_bytes},
+        conv::{parse_bytes, shorten_number, to_size},
         html::{as_type, attr, inner},
     },
     widget::sort::{SelectedSort, SortDir},
 };
 
 use super::{
-    add_protocol, Item, ItemType, Source, SourceConfig, SourceExtraConfig, SourceInfo,
-    SourceResponse,
+    boxed,
+    mirror::{self, Mirrors},
+    release::{self, ReleaseField},
+    request,
+    scrape::{required, Defaults, ParseReport},
+    InfoHash, Item, ItemType, Source, SourceConfig, SourceError, SourceExtraConfig, SourceFuture,
+    SourceInfo, SourceResponse,
 };
 
 #[derive(Serialize, Deserialize, Clone, Copy, Default)]
@@ -169,12 +175,16 @@ impl Default for TgxCategoryTheme {
 #[serde(default)]
 pub struct TgxConfig {
     pub base_url: String,
+    /// Tried in order when `base_url` is down
+    pub mirrors: Vec<String>,
     pub default_sort: TgxSort,
     pub default_sort_dir: SortDir,
     pub default_filter: TgxFilter,
     pub default_category: String,
     pub default_search: String,
     pub timeout: Option<u64>,
+    /// Netscape `cookies.txt` exported from a browser, imported on startup
+    pub cookies: Option<String>,
     pub columns: Option<TgxColumns>,
 }
 
@@ -182,17 +192,25 @@ impl Default for TgxConfig {
     fn default() -> Self {
         Self {
             base_url: "https://torrentgalaxy.to/".to_owned(),
+            mirrors: vec![],
             default_sort: TgxSort::Date,
             default_sort_dir: SortDir::Desc,
             default_filter: TgxFilter::NoFilter,
             default_category: "AllCategories".to_owned(),
             default_search: Default::default(),
             timeout: None,
+            cookies: None,
             columns: None,
         }
     }
 }
 
+impl TgxConfig {
+    pub fn mirrors(&self) -> Mirrors {
+        Mirrors::new("torrentgalaxy", &self.base_url, &self.mirrors)
+    }
+}
+
 #[derive(Clone, Copy, Serialize, Deserialize, Default)]
 pub struct TgxColumns {
     category: Option<bool>,
@@ -205,16 +223,25 @@ pub struct TgxColumns {
     seeders: Option<bool>,
     leechers: Option<bool>,
     views: Option<bool>,
+    group: Option<bool>,
+    episode: Option<bool>,
+    resolution: Option<bool>,
+    codec: Option<bool>,
 }
 
 impl TgxColumns {
-    fn array(self) -> [bool; 10] {
+    fn array(self) -> [bool; 14] {
         [
             self.category.unwrap_or(true),
             self.language.unwrap_or(true),
             self.title.unwrap_or(true),
             self.imdb.unwrap_or(true),
             self.uploader.unwrap_or(true),
+            // Hidden unless enabled
+            self.group.unwrap_or(false),
+            self.episode.unwrap_or(false),
+            self.resolution.unwrap_or(false),
+            self.codec.unwrap_or(false),
             self.size.unwrap_or(true),
             self.date.unwrap_or(true),
             self.seeders.unwrap_or(true),
@@ -234,6 +261,11 @@ pub enum TgxSort {
     Leechers = 2,
     Size = 3,
     Name = 4,
+    /// Sorted locally by the parts of the release name
+    Group = 5,
+    Episode = 6,
+    Resolution = 7,
+    Codec = 8,
 }
 
 #[derive(
@@ -254,10 +286,10 @@ pub enum TgxFilter {
 pub struct TorrentGalaxyHtmlSource;
 
 fn get_url(
-    base_url: String,
+    base_url: &Url,
     search: &SearchQuery,
 ) -> Result<(Url, Url), Box<dyn Error + Send + Sync>> {
-    let base_url = add_protocol(base_url, true)?.join("torrents.php")?;
+    let base_url = base_url.join("torrents.php")?;
 
     let query = encode(&search.query);
 
@@ -295,26 +327,33 @@ fn get_url(
     Ok((base_url, url))
 }
 
+fn content_request(client: &reqwest::Client, timeout: Option<u64>, url: &Url) -> RequestBuilder {
+    let mut request = client.get(url.to_owned());
+    if let Some(timeout) = timeout {
+        request = request.timeout(Duration::from_secs(timeout));
+    }
+    request.header(
+        "User-Agent",
+        "Mozilla/5.0 (Windows NT 10.0; Win64; x64; rv:126.0) Gecko/20100101 Firefox/126.0",
+    )
+}
+
 async fn try_get_content(
     client: &reqwest::Client,
     timeout: Option<u64>,
     url: &Url,
 ) -> Result<String, Box<dyn Error + Send + Sync>> {
-    let mut request = client.get(url.to_owned());
-    if let Some(timeout) = timeout {
-        request = request.timeout(Duration::from_secs(timeout));
-    }
-    let response = request
-        .header(
-            "User-Agent",
-            "Mozilla/5.0 (Windows NT 10.0; Win64; x64; rv:126.0) Gecko/20100101 Firefox/126.0",
-        )
-        .send()
-        .await?;
+    let response = request::send(content_request(client, timeout, url)).await?;
+    get_content(response, url).await
+}
+
+async fn get_content(
+    response: Response,
+    url: &Url,
+) -> Result<String, Box<dyn Error + Send + Sync>> {
     if response.status() != StatusCode::OK {
         // Throw error if response code is not OK
-        let code = response.status().as_u16();
-        return Err(format!("{}\nInvalid response code: {}", url, code).into());
+        return Err(SourceError::http(response.status(), url).into());
     }
     Ok(response.text().await?)
 }
@@ -366,130 +405,130 @@ fn get_status_color(status: String) -> Option<Color> {
 }
 
 impl Source for TorrentGalaxyHtmlSource {
-    async fn filter(
-        client: &reqwest::Client,
-        search: &SearchQuery,
-        config: &SourceConfig,
-        extra: &SourceExtraConfig,
-    ) -> Result<SourceResponse, Box<dyn Error + Send + Sync>> {
-        TorrentGalaxyHtmlSource::search(client, search, config, extra).await
-    }
-    async fn categorize(
-        client: &reqwest::Client,
-        search: &SearchQuery,
-        config: &SourceConfig,
-        extra: &SourceExtraConfig,
-    ) -> Result<SourceResponse, Box<dyn Error + Send + Sync>> {
-        TorrentGalaxyHtmlSource::search(client, search, config, extra).await
-    }
-    async fn sort(
-        client: &reqwest::Client,
-        search: &SearchQuery,
-        config: &SourceConfig,
-        extra: &SourceExtraConfig,
-    ) -> Result<SourceResponse, Box<dyn Error + Send + Sync>> {
-        TorrentGalaxyHtmlSource::search(client, search, config, extra).await
+    fn name(&self) -> String {
+        "TorrentGalaxy".to_owned()
     }
 
-    async fn search(
-        client: &reqwest::Client,
-        search: &SearchQuery,
-        config: &SourceConfig,
-        _extra: &SourceExtraConfig,
-    ) -> Result<SourceResponse, Box<dyn Error + Send + Sync>> {
-        let tgx = config.tgx.to_owned().unwrap_or_default();
-        let (base_url, url) = get_url(tgx.base_url.clone(), search)?;
-
-        let table_sel = &sel!(".tgxtable")?;
-
-        // First try checkpoint
-        let content = try_get_content(client, tgx.timeout, &url).await?;
-        if Html::parse_document(&content).select(table_sel).count() == 0 {
-            let time = SystemTime::now().duration_since(UNIX_EPOCH)?.as_millis();
-
-            let hash = "4578678889c4b42ae37b543434c81d85";
-            let mut hash_url = base_url.clone().join("hub.php")?;
-            hash_url.set_query(Some(&format!("a=vlad&u={}", time)));
-            client
-                .post(hash_url.clone())
-                .body(format!("fash={}", hash))
-                .header("Content-Type", "application/x-www-form-urlencoded")
-                .header(
-                    "User-Agent",
-                    "Mozilla/5.0 (Windows NT 10.0; Win64; x64; rv:126.0) Gecko/20100101 Firefox/126.0",
+    fn search<'a>(
+        &'a self,
+        client: &'a reqwest::Client,
+        search: &'a SearchQuery,
+        config: &'a SourceConfig,
+        _extra: &'a SourceExtraConfig,
+    ) -> SourceFuture<'a, SourceResponse> {
+        boxed(async move {
+            let tgx = config.tgx.to_owned().unwrap_or_default();
+            let (root, response) = tgx
+                .mirrors()
+                .send(|base_url| {
+                    let (_, url) = get_url(base_url, search)?;
+                    Ok(content_request(client, tgx.timeout, &url))
+                })
+                .await?;
+            let (base_url, url) = get_url(&root, search)?;
+
+            let table_sel = &sel!(".tgxtable")?;
+
+            // First try checkpoint
+            let content = get_content(response, &url).await?;
+            if Html::parse_document(&content).select(table_sel).count() == 0 {
+                let time = SystemTime::now().duration_since(UNIX_EPOCH)?.as_millis();
+
+                let hash = "4578678889c4b42ae37b543434c81d85";
+                let mut hash_url = base_url.clone().join("hub.php")?;
+                hash_url.set_query(Some(&format!("a=vlad&u={}", time)));
+                request::send(
+                    client
+                        .post(hash_url.clone())
+                        .body(format!("fash={}", hash))
+                        .header("Content-Type", "application/x-www-form-urlencoded")
+                        .header(
+                            "User-Agent",
+                            "Mozilla/5.0 (Windows NT 10.0; Win64; x64; rv:126.0) Gecko/20100101 Firefox/126.0",
+                        ),
                 )
-                .send()
                 .await?;
-        }
-
-        // If that doesn't work, try making the user solve a captcha
-        let content = try_get_content(client, tgx.timeout, &url).await?;
-        if Html::parse_document(&content).select(table_sel).count() == 0 {
-            #[cfg(not(feature = "captcha"))]
-            {
-                return Err("Unable to get response, most likely due to rate limit.\nWait a bit before retrying...".into());
             }
-            #[cfg(feature = "captcha")]
-            {
-                let mut captcha_url = base_url.clone().join("captcha/cpt_show.pnp")?;
-                captcha_url.set_query(Some("v=txlight&63fd4c746843c74b53ca60277192fb48"));
-                let mut request = client.get(captcha_url);
-                if let Some(timeout) = tgx.timeout {
-                    request = request.timeout(Duration::from_secs(timeout));
+
+            // If that doesn't work, try making the user solve a captcha
+            let content = try_get_content(client, tgx.timeout, &url).await?;
+            if Html::parse_document(&content).select(table_sel).count() == 0 {
+                #[cfg(not(feature = "captcha"))]
+                {
+                    return Err(SourceError::Captcha {
+                        url: url.to_string(),
+                    });
+                }
+                #[cfg(feature = "captcha")]
+                {
+                    let mut captcha_url = base_url.clone().join("captcha/cpt_show.pnp")?;
+                    captcha_url.set_query(Some("v=txlight&63fd4c746843c74b53ca60277192fb48"));
+                    let mut request = client.get(captcha_url);
+                    if let Some(timeout) = tgx.timeout {
+                        request = request.timeout(Duration::from_secs(timeout));
+                    }
+                    let response = request::send(request
+                        .header("User-Agent", "Mozilla/5.0 (Windows NT 10.0; Win64; x64; rv:126.0) Gecko/20100101 Firefox/126.0"))
+                        .await?;
+                    let bytes = response.bytes().await?;
+                    let mut picker = ratatui_image::picker::Picker::new((1, 2));
+                    picker.protocol_type = ratatui_image::picker::ProtocolType::Halfblocks;
+                    let dyn_image = image::load_from_memory(&bytes[..])?;
+                    let image = picker.new_resize_protocol(dyn_image);
+
+                    return Ok(SourceResponse::Captcha(image));
                 }
-                let response = request
-                    .header("User-Agent", "Mozilla/5.0 (Windows NT 10.0; Win64; x64; rv:126.0) Gecko/20100101 Firefox/126.0")
-                    .send()
-                    .await?;
-                let bytes = response.bytes().await?;
-                let mut picker = ratatui_image::picker::Picker::new((1, 2));
-                picker.protocol_type = ratatui_image::picker::ProtocolType::Halfblocks;
-                let dyn_image = image::load_from_memory(&bytes[..])?;
-                let image = picker.new_resize_protocol(dyn_image);
-
-                return Ok(SourceResponse::Captcha(image));
             }
-        }
 
-        // Results table found, can start parsing
-        let doc = Html::parse_document(&content);
-
-        let item_sel = &sel!("div.tgxtablerow")?;
-        let title_sel = &sel!("div.tgxtablecell:nth-of-type(4) > div > a.txlight")?;
-        let imdb_sel = &sel!("div.tgxtablecell:nth-of-type(4) > div > a:last-of-type")?;
-        let cat_sel = &sel!("div.tgxtablecell:nth-of-type(1) > a")?;
-        let date_sel = &sel!("div.tgxtablecell:nth-of-type(12)")?;
-        let seed_sel = &sel!("div.tgxtablecell:nth-of-type(11) > span > font:first-of-type > b")?;
-        let leech_sel = &sel!("div.tgxtablecell:nth-of-type(11) > span > font:last-of-type > b")?;
-        let size_sel = &sel!("div.tgxtablecell:nth-of-type(8) > span")?;
-        let trust_sel = &sel!("div.tgxtablecell:nth-of-type(2) > i")?;
-        let views_sel = &sel!("div.tgxtablecell:nth-of-type(10) > span > font > b")?;
-        let torrent_sel = &sel!("div.tgxtablecell:nth-of-type(5) > a:first-of-type")?;
-        let magnet_sel = &sel!("div.tgxtablecell:nth-of-type(5) > a:last-of-type")?;
-        let lang_sel = &sel!("div.tgxtablecell:nth-of-type(3) > img")?;
-        let uploader_sel = &sel!("div.tgxtablecell:nth-of-type(7) > span > a > span")?;
-        let uploader_status_sel = &sel!("div.tgxtablecell:nth-of-type(7) > span > a")?;
-
-        let pagination_sel = &sel!("div#filterbox2 > span.badge")?;
-
-        let items = doc
-            .select(item_sel)
-            .filter_map(|e| {
+            // Results table found, can start parsing
+            let doc = Html::parse_document(&content);
+
+            let table_sel = &sel!("div.tgxtable")?;
+            let item_sel = &sel!("div.tgxtablerow")?;
+            let title_sel = &sel!("div.tgxtablecell:nth-of-type(4) > div > a.txlight")?;
+            let imdb_sel = &sel!("div.tgxtablecell:nth-of-type(4) > div > a:last-of-type")?;
+            let cat_sel = &sel!("div.tgxtablecell:nth-of-type(1) > a")?;
+            let date_sel = &sel!("div.tgxtablecell:nth-of-type(12)")?;
+            let seed_sel =
+                &sel!("div.tgxtablecell:nth-of-type(11) > span > font:first-of-type > b")?;
+            let leech_sel =
+                &sel!("div.tgxtablecell:nth-of-type(11) > span > font:last-of-type > b")?;
+            let size_sel = &sel!("div.tgxtablecell:nth-of-type(8) > span")?;
+            let trust_sel = &sel!("div.tgxtablecell:nth-of-type(2) > i")?;
+            let views_sel = &sel!("div.tgxtablecell:nth-of-type(10) > span > font > b")?;
+            let torrent_sel = &sel!("div.tgxtablecell:nth-of-type(5) > a:first-of-type")?;
+            let magnet_sel = &sel!("div.tgxtablecell:nth-of-type(5) > a:last-of-type")?;
+            let lang_sel = &sel!("div.tgxtablecell:nth-of-type(3) > img")?;
+            let uploader_sel = &sel!("div.tgxtablecell:nth-of-type(7) > span > a > span")?;
+            let uploader_status_sel = &sel!("div.tgxtablecell:nth-of-type(7) > span > a")?;
+
+            let pagination_sel = &sel!("div#filterbox2 > span.badge")?;
+
+            // Pages without results say so instead of showing an empty table
+            let listed =
+                doc.select(table_sel).next().is_some() || !content.contains("No results found");
+
+            let mut defaults = Defaults::default();
+            let (items, report) = ParseReport::collect(doc.select(item_sel).map(|e| {
                 let cat_id = attr(e, cat_sel, "href")
                     .rsplit_once('=')
                     .map(|v| v.1)
                     .and_then(|v| v.parse::<usize>().ok())
                     .unwrap_or_default();
-                let icon = Self::info().entry_from_id(cat_id).icon;
-                let date = e
+                let icon = self.info().entry_from_id(cat_id).icon;
+                let raw_date: String = e
                     .select(date_sel)
                     .nth(0)
                     .map(|e| e.text().collect())
                     .unwrap_or_default();
-                let seeders = as_type(inner(e, seed_sel, "0")).unwrap_or_default();
+                // Dates are shown like "15/10/24 12:34"
+                let date = NaiveDateTime::parse_from_str(raw_date.trim(), "%d/%m/%y %H:%M")
+                    .ok()
+                    .map(|d| d.and_utc());
+                let seeders = defaults.or_default(as_type(inner(e, seed_sel, "")), "seeders");
                 let leechers = as_type(inner(e, leech_sel, "0")).unwrap_or_default();
                 let views = as_type(inner(e, views_sel, "0")).unwrap_or_default();
-                let mut size = inner(e, size_sel, "0 MB");
+                let mut size = inner(e, size_sel, "");
 
                 // Convert numbers like 1,015 KB => 1.01 MB
                 if let Some((x, y)) = size.split_once(',') {
@@ -525,7 +564,7 @@ impl Source for TorrentGalaxyHtmlSource {
                 let post_link = attr(e, title_sel, "href");
 
                 let binding = post_link.split('/').collect::<Vec<&str>>();
-                let id = format!("tgx-{}", binding.get(2)?);
+                let id = format!("tgx-{}", binding.get(2).ok_or("id")?);
 
                 let post_link = base_url
                     .join(&post_link)
@@ -540,23 +579,24 @@ impl Source for TorrentGalaxyHtmlSource {
                     i => i,
                 };
 
-                let extra: HashMap<String, String> = collection![
+                let mut extra: HashMap<String, String> = collection![
                     "uploader".to_owned() => inner(e, uploader_sel, "???"),
                     "uploader_status".to_owned() => attr(e, uploader_status_sel, "title"),
                     "lang".to_owned() => attr(e, lang_sel, "title"),
                     "imdb".to_owned() => imdb.to_owned(),
                 ];
+                let date = defaults.date(date, &raw_date, &mut extra);
 
-                Some(Item {
+                Ok(Item {
                     id,
                     date,
                     seeders,
                     leechers,
                     downloads: views,
-                    bytes: to_bytes(&size),
-                    size,
-                    title: attr(e, title_sel, "title"),
+                    size: defaults.or_default(parse_bytes(&size), "size"),
+                    title: required(attr(e, title_sel, "title"), "title")?,
                     torrent_link,
+                    hash: InfoHash::from_magnet(&magnet_link),
                     magnet_link,
                     post_link,
                     file_name,
@@ -565,93 +605,106 @@ impl Source for TorrentGalaxyHtmlSource {
                     item_type,
                     extra,
                 })
-            })
-            .collect::<Vec<Item>>();
-
-        let mut last_page = 50;
-        let mut total_results = 2500;
-        if let Some(pagination) = doc.select(pagination_sel).nth(0) {
-            if let Ok(num_results) = pagination
-                .inner_html()
-                .chars()
-                .filter(|c| c.is_ascii_digit())
-                .collect::<String>()
-                .parse::<usize>()
-            {
-                if num_results != 0 || items.is_empty() {
-                    last_page = (num_results + 49) / 50;
-                    total_results = num_results;
+            }));
+
+            let mut last_page = 50;
+            let mut total_results = 2500;
+            let mut expected = None;
+            if let Some(pagination) = doc.select(pagination_sel).nth(0) {
+                if let Ok(num_results) = pagination
+                    .inner_html()
+                    .chars()
+                    .filter(|c| c.is_ascii_digit())
+                    .collect::<String>()
+                    .parse::<usize>()
+                {
+                    if num_results != 0 || items.is_empty() {
+                        last_page = num_results.div_ceil(50);
+                        total_results = num_results;
+                        expected = Some(num_results);
+                    }
                 }
             }
-        }
 
-        Ok(SourceResponse::Results(ResultResponse {
-            items,
-            total_results,
-            last_page,
-        }))
+            Ok(SourceResponse::Results(ResultResponse {
+                items,
+                total_results,
+                last_page,
+                mirror: Some(mirror::display_name(&root)),
+                report: Some(
+                    report
+                        .expect(expected)
+                        .defaults(defaults)
+                        .listing(listed)
+                        .page(&content),
+                ),
+                ..Default::default()
+            }))
+        })
     }
 
-    async fn solve(
+    fn solve<'a>(
+        &'a self,
         solution: String,
-        client: &reqwest::Client,
-        search: &SearchQuery,
-        config: &SourceConfig,
-        extra: &SourceExtraConfig,
-    ) -> Result<SourceResponse, Box<dyn Error + Send + Sync>> {
-        let tgx = config.tgx.to_owned().unwrap_or_default();
-        let time = SystemTime::now().duration_since(UNIX_EPOCH)?.as_millis();
-
-        let hash = "4578678889c4b42ae37b543434c81d85";
-        let base_url = Url::parse(&tgx.base_url)?;
-        let mut hash_url = base_url.clone().join("hub.php")?;
-        hash_url.set_query(Some(&format!("a=vlad&u={}", time)));
-        client
-            .post(hash_url.clone())
-            .body(format!("fash={}", hash))
-            .header("Content-Type", "application/x-www-form-urlencoded")
-            .header(
-                "User-Agent",
-                "Mozilla/5.0 (Windows NT 10.0; Win64; x64; rv:126.0) Gecko/20100101 Firefox/126.0",
+        client: &'a reqwest::Client,
+        search: &'a SearchQuery,
+        config: &'a SourceConfig,
+        extra: &'a SourceExtraConfig,
+    ) -> SourceFuture<'a, SourceResponse> {
+        boxed(async move {
+            let tgx = config.tgx.to_owned().unwrap_or_default();
+            let time = SystemTime::now().duration_since(UNIX_EPOCH)?.as_millis();
+
+            let hash = "4578678889c4b42ae37b543434c81d85";
+            let base_url = tgx.mirrors().first()?;
+            let mut hash_url = base_url.clone().join("hub.php")?;
+            hash_url.set_query(Some(&format!("a=vlad&u={}", time)));
+            request::send(
+                client
+                    .post(hash_url.clone())
+                    .body(format!("fash={}", hash))
+                    .header("Content-Type", "application/x-www-form-urlencoded")
+                    .header(
+                        "User-Agent",
+                        "Mozilla/5.0 (Windows NT 10.0; Win64; x64; rv:126.0) Gecko/20100101 Firefox/126.0",
+                    ),
             )
-            .send()
             .await?;
 
-        let (_base_url, url) = get_url(tgx.base_url, search)?;
-        let mut full_url = base_url.clone().join("galaxyfence.php")?;
-        full_url.set_query(Some(&format!(
-            "captcha={}&dropoff={}",
-            solution,
-            encode(&format!(
-                "{}?{}",
-                url.path(),
-                url.query().unwrap_or_default()
-            ))
-        )));
-        let mut request = client.post(full_url.clone());
-        if let Some(timeout) = tgx.timeout {
-            request = request.timeout(Duration::from_secs(timeout));
-        }
-        request = request.header(
-            "Accept",
-            "text/html,application/xhtml+xml,application/xml;q=0.9,image/avif,image/webp,*/*;q=0.8",
-        )
-            .header("User-Agent", "Mozilla/5.0 (Windows NT 10.0; Win64; x64; rv:126.0) Gecko/20100101 Firefox/126.0")
-            .header("Content-Type", "application/x-www-form-urlencoded");
-
-        let response = request.send().await?;
-        if response.status() != StatusCode::OK {
-            return Err(format!(
-                "Captcha solution returned HTTP status {}",
-                response.status()
+            let (_base_url, url) = get_url(&base_url, search)?;
+            let mut full_url = base_url.clone().join("galaxyfence.php")?;
+            full_url.set_query(Some(&format!(
+                "captcha={}&dropoff={}",
+                solution,
+                encode(&format!(
+                    "{}?{}",
+                    url.path(),
+                    url.query().unwrap_or_default()
+                ))
+            )));
+            let mut request = client.post(full_url.clone());
+            if let Some(timeout) = tgx.timeout {
+                request = request.timeout(Duration::from_secs(timeout));
+            }
+            request = request.header(
+                "Accept",
+                "text/html,application/xhtml+xml,application/xml;q=0.9,image/avif,image/webp,*/*;q=0.8",
             )
-            .into());
-        }
+                .header("User-Agent", "Mozilla/5.0 (Windows NT 10.0; Win64; x64; rv:126.0) Gecko/20100101 Firefox/126.0")
+                .header("Content-Type", "application/x-www-form-urlencoded");
+
+            let response = request.send().await?;
+            if response.status() != StatusCode::OK {
+                return Err(SourceError::Captcha {
+                    url: response.url().to_string(),
+                });
+            }
 
-        TorrentGalaxyHtmlSource::search(client, search, config, extra).await
+            self.search(client, search, config, extra).await
+        })
     }
 
-    fn info() -> SourceInfo {
+    fn info(&self) -> SourceInfo {
         let cats = cats! {
             "All Categories" => { 0 => ("---", "All Categories", "AllCategories", source.tgx.cat.all_categories); }
             "Movies" => {3 => ("4kM", "4K UHD Movies", "4kMovies", source.tgx.cat.movies_4k);
@@ -701,22 +754,22 @@ impl Source for TorrentGalaxyHtmlSource {
         }
     }
 
-    fn load_config(config: &mut SourceConfig) {
+    fn load_config(&self, config: &mut SourceConfig) {
         if config.tgx.is_none() {
             config.tgx = Some(TgxConfig::default());
         }
     }
 
-    fn default_category(cfg: &SourceConfig) -> usize {
+    fn default_category(&self, cfg: &SourceConfig) -> usize {
         let default = cfg
             .tgx
             .as_ref()
             .map(|c| c.default_category.to_owned())
             .unwrap_or_default();
-        Self::info().entry_from_cfg(&default).id
+        self.info().entry_from_cfg(&default).id
     }
 
-    fn default_sort(cfg: &SourceConfig) -> SelectedSort {
+    fn default_sort(&self, cfg: &SourceConfig) -> SelectedSort {
         cfg.tgx
             .as_ref()
             .map(|c| SelectedSort {
@@ -726,14 +779,14 @@ impl Source for TorrentGalaxyHtmlSource {
             .unwrap_or_default()
     }
 
-    fn default_filter(cfg: &SourceConfig) -> usize {
+    fn default_filter(&self, cfg: &SourceConfig) -> usize {
         cfg.tgx
             .as_ref()
             .map(|c| c.default_filter as usize)
             .unwrap_or_default()
     }
 
-    fn default_search(cfg: &SourceConfig) -> String {
+    fn default_search(&self, cfg: &SourceConfig) -> String {
         cfg.tgx
             .as_ref()
             .map(|c| c.default_search.to_owned())
@@ -741,13 +794,16 @@ impl Source for TorrentGalaxyHtmlSource {
     }
 
     fn format_table(
+        &self,
         items: &[Item],
         search: &SearchQuery,
         config: &SourceConfig,
+        extra: &SourceExtraConfig,
         theme: &Theme,
     ) -> ResultTable {
         let tgx = config.tgx.to_owned().unwrap_or_default();
-        let raw_date_width = items.iter().map(|i| i.date.len()).max().unwrap_or_default() as u16;
+        let dates: Vec<String> = items.iter().map(|i| extra.format_item_date(i)).collect();
+        let raw_date_width = dates.iter().map(|d| d.len()).max().unwrap_or_default() as u16;
         let date_width = max(raw_date_width, 6);
 
         let raw_uploader_width = items
@@ -762,6 +818,17 @@ impl Source for TorrentGalaxyHtmlSource {
             .max()
             .unwrap_or_default() as u16;
         let imdb_width = max(raw_imdb_width, 4);
+        // Parts of the release name, hidden unless enabled
+        let [group_col, ep_col, res_col, codec_col] = [
+            (ReleaseField::Group, "Group", 16, TgxSort::Group),
+            (ReleaseField::Episode, "Ep", 7, TgxSort::Episode),
+            (ReleaseField::Resolution, "Res", 5, TgxSort::Resolution),
+            (ReleaseField::Codec, "Codec", 4, TgxSort::Codec),
+        ]
+        .map(|(field, name, max, sort)| {
+            let width = release::column_width(items, field, name, max);
+            ResultColumn::Sorted(name.to_owned(), width, sort as u32)
+        });
 
         let header = ResultHeader::new([
             ResultColumn::Normal("Cat".to_owned(), Constraint::Length(3)),
@@ -769,6 +836,10 @@ impl Source for TorrentGalaxyHtmlSource {
             ResultColumn::Normal("Name".to_owned(), Constraint::Min(3)),
             ResultColumn::Normal("imdb".to_owned(), Constraint::Length(imdb_width)),
             ResultColumn::Normal("Uploader".to_owned(), Constraint::Length(uploader_width)),
+            group_col,
+            ep_col,
+            res_col,
+            codec_col,
             ResultColumn::Sorted("Size".to_owned(), 9, TgxSort::Size as u32),
             ResultColumn::Sorted("Date".to_owned(), date_width, TgxSort::Date as u32),
             ResultColumn::Sorted("".to_owned(), 4, TgxSort::Seeders as u32),
@@ -782,6 +853,10 @@ impl Source for TorrentGalaxyHtmlSource {
             Alignment::Left,
             Alignment::Left,
             Alignment::Left,
+            Alignment::Left,
+            Alignment::Right,
+            Alignment::Right,
+            Alignment::Left,
             Alignment::Right,
             Alignment::Left,
             Alignment::Right,
@@ -790,7 +865,15 @@ impl Source for TorrentGalaxyHtmlSource {
         ];
         let mut rows: Vec<ResultRow> = items
             .iter()
-            .map(|item| {
+            .zip(dates)
+            .map(|(item, date)| {
+                let [group, episode, resolution, codec] = [
+                    ReleaseField::Group,
+                    ReleaseField::Episode,
+                    ReleaseField::Resolution,
+                    ReleaseField::Codec,
+                ]
+                .map(|f| f.get(item).unwrap_or_default());
                 ResultRow::new([
                     item.icon.label.fg((item.icon.color)(theme)),
                     item.extra
@@ -817,8 +900,12 @@ impl Source for TorrentGalaxyHtmlSource {
                             .get("uploader_status")
                             .and_then(|u| get_status_color(u.to_owned()))
                             .unwrap_or(theme.fg)),
-                    item.size.clone().fg(theme.fg),
-                    item.date.clone().fg(theme.fg),
+                    group.fg(theme.fg),
+                    episode.fg(theme.fg),
+                    resolution.fg(theme.fg),
+                    codec.fg(theme.fg),
+                    to_size(item.size).fg(theme.fg),
+                    date.fg(theme.fg),
                     item.seeders.to_string().fg(theme.success),
                     item.leechers.to_string().fg(theme.error),
                     shorten_number(item.downloads).fg(theme.fg),
@@ -828,20 +915,18 @@ impl Source for TorrentGalaxyHtmlSource {
             })
             .collect();
         let mut headers = header.get_row(search.sort.dir, search.sort.sort as u32);
-        if let Some(columns) = tgx.columns {
-            let cols = columns.array();
-
-            headers.cells = cond_vec!(cols ; headers.cells);
-            rows = rows
-                .clone()
-                .into_iter()
-                .map(|mut r| {
-                    r.cells = cond_vec!(cols ; r.cells.to_owned());
-                    r
-                })
-                .collect::<Vec<ResultRow>>();
-            binding = cond_vec!(cols ; binding);
-        }
+        let cols = tgx.columns.unwrap_or_default().array();
+
+        headers.cells = cond_vec!(cols ; headers.cells);
+        rows = rows
+            .clone()
+            .into_iter()
+            .map(|mut r| {
+                r.cells = cond_vec!(cols ; r.cells.to_owned());
+                r
+            })
+            .collect::<Vec<ResultRow>>();
+        binding = cond_vec!(cols ; binding);
 
         ResultTable {
             headers,
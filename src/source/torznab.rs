@@ -0,0 +1,392 @@
+use std::{collections::BTreeMap, error::Error, time::Duration};
+
+use chrono::{DateTime, Utc};
+use reqwest::StatusCode;
+use rss::{extension::Extension, Channel};
+use serde::{Deserialize, Serialize};
+use strum::VariantArray as _;
+use urlencoding::encode;
+
+use crate::{
+    cats,
+    results::ResultResponse,
+    sync::SearchQuery,
+    theme::Theme,
+    widget::sort::{SelectedSort, SortDir},
+};
+
+use super::{
+    add_protocol,
+    all::sort_items,
+    boxed,
+    nyaa_html::{nyaa_table, NyaaColumns, NyaaSort},
+    request, InfoHash, Item, ResultTable, Source, SourceConfig, SourceError, SourceExtraConfig,
+    SourceFuture, SourceInfo, SourceResponse,
+};
+
+/// Number of results requested per page
+static PAGE_SIZE: usize = 75;
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct TorznabConfig {
+    /// Url of the Torznab feed, without the trailing `api`
+    pub base_url: String,
+    pub apikey: String,
+    pub default_sort: NyaaSort,
+    pub default_sort_dir: SortDir,
+    pub default_category: String,
+    pub default_search: String,
+    pub timeout: Option<u64>,
+    /// Netscape `cookies.txt` exported from a browser, imported on startup
+    pub cookies: Option<String>,
+    pub columns: Option<NyaaColumns>,
+}
+
+impl Default for TorznabConfig {
+    fn default() -> Self {
+        Self {
+            base_url: "http://localhost:9117/api/v2.0/indexers/all/results/torznab/".to_owned(),
+            apikey: Default::default(),
+            default_sort: NyaaSort::Date,
+            default_sort_dir: SortDir::Desc,
+            default_category: "AllCategories".to_owned(),
+            default_search: Default::default(),
+            timeout: None,
+            cookies: None,
+            columns: None,
+        }
+    }
+}
+
+pub struct TorznabSource;
+
+type ExtensionMap = BTreeMap<String, Vec<Extension>>;
+
+/// Values of `<torznab:attr name="..." value="..."/>` with the given name
+fn get_attrs<'a>(ext_map: &'a ExtensionMap, name: &'a str) -> impl Iterator<Item = &'a str> {
+    ext_map
+        .get("attr")
+        .into_iter()
+        .flatten()
+        .filter(move |e| e.attrs().get("name").is_some_and(|n| n == name))
+        .filter_map(|e| e.attrs().get("value").map(String::as_str))
+}
+
+fn get_attr<T: Default + std::str::FromStr>(ext_map: &ExtensionMap, name: &str) -> T {
+    get_attrs(ext_map, name)
+        .next()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or_default()
+}
+
+/// Error of an `<error code="..." description="..."/>` response
+fn api_error(content: &str) -> Option<SourceError> {
+    let error = content.split_once("<error")?.1;
+    let attr = |name: &str| {
+        error
+            .split_once(&format!("{}=\"", name))
+            .and_then(|d| d.1.split_once('"'))
+            .map(|d| d.0)
+    };
+    let message = format!(
+        "Torznab error: {}",
+        attr("description").unwrap_or("Unknown error")
+    );
+    // Codes 100 to 199 are account errors, like an incorrect or suspended api key
+    match attr("code").and_then(|c| c.parse::<u16>().ok()) {
+        Some(100..=199) => Some(SourceError::Auth {
+            key: "source.torznab.apikey".to_owned(),
+            message,
+        }),
+        _ => Some(SourceError::Other(message)),
+    }
+}
+
+/// Parse a Torznab search response into results for the given search
+pub fn parse_results(
+    content: &str,
+    search: &SearchQuery,
+) -> Result<ResultResponse, Box<dyn Error + Send + Sync>> {
+    let channel = match Channel::read_from(content.as_bytes()) {
+        Ok(channel) => channel,
+        Err(e) => match api_error(content) {
+            Some(error) => return Err(error.into()),
+            None => return Err(e.into()),
+        },
+    };
+    let info = TorznabSource.info();
+
+    let items: Vec<Item> = channel
+        .items
+        .iter()
+        .filter_map(|item| {
+            let ext = item.extensions().get("torznab")?;
+            let title = item.title()?.to_owned();
+
+            // Use the most specific category known, falling back to its parent category
+            let category = get_attrs(ext, "category")
+                .filter_map(|c| c.parse::<usize>().ok())
+                .find_map(|c| {
+                    [c, c / 1000 * 1000].into_iter().find(|c| {
+                        info.cats
+                            .iter()
+                            .any(|cat| cat.entries.iter().any(|e| e.id == *c))
+                    })
+                })
+                .unwrap_or_default();
+            let icon = info.clone().entry_from_id(category).icon;
+
+            let size = match get_attr::<u64>(ext, "size") {
+                0 => item
+                    .enclosure()
+                    .and_then(|e| e.length().parse().ok())
+                    .unwrap_or_default(),
+                size => size,
+            };
+            let seeders: u32 = get_attr(ext, "seeders");
+            // Peers includes seeders
+            let leechers = get_attr::<u32>(ext, "peers").saturating_sub(seeders);
+            let downloads = get_attr(ext, "grabs");
+
+            let torrent_link = item
+                .enclosure()
+                .map(|e| e.url().to_owned())
+                .or(item.link().map(ToOwned::to_owned))
+                .unwrap_or_default();
+            let hash = get_attr::<String>(ext, "infohash").parse::<InfoHash>().ok();
+            let magnet_link = match (get_attr::<String>(ext, "magneturl"), hash) {
+                (m, _) if !m.is_empty() => m,
+                (_, Some(hash)) => format!("magnet:?xt=urn:btih:{}&dn={}", hash, encode(&title)),
+                _ => torrent_link.to_owned(),
+            };
+            let hash = hash.or_else(|| InfoHash::from_magnet(&magnet_link));
+            let guid = item
+                .guid()
+                .map(|g| g.value().to_owned())
+                .unwrap_or_default();
+            let id = format!(
+                "torznab-{}",
+                hash.map(|h| h.to_string()).unwrap_or(guid.to_owned())
+            );
+            let post_link = item
+                .comments()
+                .map(ToOwned::to_owned)
+                .unwrap_or(guid.to_owned());
+
+            let pub_date = item.pub_date().unwrap_or("");
+            let date = DateTime::parse_from_rfc2822(pub_date)
+                .map(|d| d.with_timezone(&Utc))
+                .unwrap_or_default();
+
+            Some(Item {
+                file_name: format!("{}.torrent", id),
+                id,
+                date,
+                seeders,
+                leechers,
+                downloads,
+                size,
+                title,
+                torrent_link,
+                magnet_link,
+                hash,
+                post_link,
+                category,
+                icon,
+                ..Default::default()
+            })
+        })
+        .collect();
+
+    // Not every indexer reports the total number of results
+    let total = channel
+        .extensions()
+        .get("newznab")
+        .and_then(|n| n.get("response"))
+        .and_then(|r| r.first())
+        .and_then(|r| r.attrs().get("total"))
+        .and_then(|t| t.parse::<usize>().ok());
+    let offset = (search.page - 1) * PAGE_SIZE;
+    let (last_page, total_results) = match total {
+        Some(total) => (total.div_ceil(PAGE_SIZE).max(1), total),
+        None if items.len() >= PAGE_SIZE => (search.page + 1, offset + items.len()),
+        None => (search.page, offset + items.len()),
+    };
+
+    let mut items = items;
+    sort_items(&mut items, search.sort);
+    Ok(ResultResponse {
+        items,
+        last_page,
+        total_results,
+        ..Default::default()
+    })
+}
+
+impl Source for TorznabSource {
+    fn name(&self) -> String {
+        "Torznab".to_owned()
+    }
+
+    fn search<'a>(
+        &'a self,
+        client: &'a reqwest::Client,
+        search: &'a SearchQuery,
+        config: &'a SourceConfig,
+        _extra: &'a SourceExtraConfig,
+    ) -> SourceFuture<'a, SourceResponse> {
+        boxed(async move {
+            let torznab = config.torznab.to_owned().unwrap_or_default();
+            let mut base_url = torznab.base_url;
+            if !base_url.ends_with('/') {
+                base_url.push('/');
+            }
+            let mut url = add_protocol(base_url, false)
+                .map_err(|e| SourceError::config("source.torznab.base_url", e))?
+                .join("api")?;
+            let cat = match search.category {
+                0 => "".to_owned(),
+                c => format!("&cat={}", c),
+            };
+            url.set_query(Some(&format!(
+                "t=search&apikey={}&q={}&offset={}&limit={}{}",
+                encode(&torznab.apikey),
+                encode(&search.query),
+                (search.page - 1) * PAGE_SIZE,
+                PAGE_SIZE,
+                cat
+            )));
+
+            let mut request = client.get(url.to_owned());
+            if let Some(timeout) = torznab.timeout {
+                request = request.timeout(Duration::from_secs(timeout));
+            }
+            let response = request::send(request).await?;
+            let code = response.status();
+            let content = response.text().await?;
+            if code != StatusCode::OK {
+                // Torznab errors are returned in the body
+                return Err(match api_error(&content) {
+                    Some(error @ SourceError::Auth { .. }) => error,
+                    _ if code == StatusCode::UNAUTHORIZED || code == StatusCode::FORBIDDEN => {
+                        SourceError::Auth {
+                            key: "source.torznab.apikey".to_owned(),
+                            message: format!("Torznab\nInvalid response code: {}", code.as_u16()),
+                        }
+                    }
+                    _ => {
+                        // Leave out the query, which contains the api key
+                        url.set_query(None);
+                        SourceError::http(code, &url)
+                    }
+                });
+            }
+            Ok(SourceResponse::Results(parse_results(&content, search)?))
+        })
+    }
+
+    fn info(&self) -> SourceInfo {
+        // Standard Newznab categories
+        let cats = cats! {
+            "All Categories" => {
+                0 => ("---", "All Categories", "AllCategories", fg);
+            }
+            "Console" => {
+                1000 => ("Con", "All Console", "Console", info);
+            }
+            "Movies" => {
+                2000 => ("Mov", "All Movies", "Movies", warning);
+                2010 => ("MFo", "Foreign", "MoviesForeign", warning);
+                2030 => ("MSD", "SD", "MoviesSD", warning);
+                2040 => ("MHD", "HD", "MoviesHD", warning);
+                2045 => ("MUH", "UHD", "MoviesUHD", warning);
+                2050 => ("MBR", "BluRay", "MoviesBluRay", warning);
+                2060 => ("M3D", "3D", "Movies3D", warning);
+            }
+            "Audio" => {
+                3000 => ("Aud", "All Audio", "Audio", success);
+                3010 => ("MP3", "MP3", "AudioMP3", success);
+                3030 => ("ABk", "Audiobook", "AudioAudiobook", success);
+                3040 => ("Los", "Lossless", "AudioLossless", success);
+            }
+            "PC" => {
+                4000 => ("PC ", "All PC", "PC", info);
+                4050 => ("Gam", "Games", "PCGames", info);
+            }
+            "TV" => {
+                5000 => ("TV ", "All TV", "TV", error);
+                5020 => ("TFo", "Foreign", "TVForeign", error);
+                5030 => ("TSD", "SD", "TVSD", error);
+                5040 => ("THD", "HD", "TVHD", error);
+                5045 => ("TUH", "UHD", "TVUHD", error);
+                5070 => ("Ani", "Anime", "TVAnime", error);
+                5080 => ("Doc", "Documentary", "TVDocumentary", error);
+            }
+            "XXX" => {
+                6000 => ("XXX", "All XXX", "XXX", fg);
+            }
+            "Books" => {
+                7000 => ("Bok", "All Books", "Books", success);
+                7020 => ("EBk", "EBook", "BooksEBook", success);
+                7030 => ("Com", "Comics", "BooksComics", success);
+            }
+            "Other" => {
+                8000 => ("Oth", "All Other", "Other", fg);
+            }
+        };
+        SourceInfo {
+            cats,
+            filters: vec!["No Filter".to_owned()],
+            sorts: NyaaSort::VARIANTS.iter().map(ToString::to_string).collect(),
+        }
+    }
+
+    fn load_config(&self, config: &mut SourceConfig) {
+        if config.torznab.is_none() {
+            config.torznab = Some(TorznabConfig::default());
+        }
+    }
+
+    fn default_category(&self, cfg: &SourceConfig) -> usize {
+        let default = cfg
+            .torznab
+            .as_ref()
+            .map(|c| c.default_category.to_owned())
+            .unwrap_or_default();
+        self.info().entry_from_cfg(&default).id
+    }
+
+    fn default_sort(&self, cfg: &SourceConfig) -> SelectedSort {
+        cfg.torznab
+            .as_ref()
+            .map(|c| SelectedSort {
+                sort: c.default_sort as usize,
+                dir: c.default_sort_dir,
+            })
+            .unwrap_or_default()
+    }
+
+    fn default_filter(&self, _cfg: &SourceConfig) -> usize {
+        0
+    }
+
+    fn default_search(&self, cfg: &SourceConfig) -> String {
+        cfg.torznab
+            .as_ref()
+            .map(|c| c.default_search.to_owned())
+            .unwrap_or_default()
+    }
+
+    fn format_table(
+        &self,
+        items: &[Item],
+        search: &SearchQuery,
+        config: &SourceConfig,
+        extra: &SourceExtraConfig,
+        theme: &Theme,
+    ) -> ResultTable {
+        let torznab = config.torznab.to_owned().unwrap_or_default();
+        nyaa_table(items.into(), theme, &search.sort, &torznab.columns, extra)
+    }
+}
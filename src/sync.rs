@@ -1,11 +1,11 @@
 use std::{
-    error::Error,
     fs,
     path::PathBuf,
     time::{Duration, SystemTime},
 };
 
 use crossterm::event::{self, Event};
+use serde::Serialize;
 use tokio::sync::mpsc;
 
 use crate::{
@@ -13,7 +13,10 @@ use crate::{
     client::{Client, ClientConfig, DownloadClientResult},
     config::CONFIG_FILE,
     results::Results,
-    source::{Item, SourceConfig, SourceExtraConfig, SourceResponse, SourceResults, Sources},
+    source::{
+        nyaa_html::ItemDetails, pages, request, Item, SourceConfig, SourceError, SourceExtraConfig,
+        SourceResponse, SourceResults, Sources,
+    },
     theme::{Theme, THEMES_PATH},
     widget::sort::SelectedSort,
 };
@@ -22,7 +25,7 @@ pub trait EventSync {
     #[allow(clippy::too_many_arguments)]
     fn load_results(
         self,
-        tx_res: mpsc::Sender<Result<SourceResults, Box<dyn Error + Send + Sync>>>,
+        tx_res: mpsc::Sender<Result<SourceResults, SourceError>>,
         load_type: LoadType,
         src: Sources,
         client: reqwest::Client,
@@ -40,6 +43,14 @@ pub trait EventSync {
         rq_client: reqwest::Client,
         client: Client,
     ) -> impl std::future::Future<Output = ()> + std::marker::Send + 'static;
+    fn load_details(
+        self,
+        tx_details: mpsc::Sender<DetailsResult>,
+        src: Sources,
+        client: reqwest::Client,
+        item: Item,
+        config: SourceConfig,
+    ) -> impl std::future::Future<Output = ()> + std::marker::Send + 'static;
     fn read_event_loop(
         self,
         tx_evt: mpsc::Sender<Event>,
@@ -50,6 +61,10 @@ pub trait EventSync {
     ) -> impl std::future::Future<Output = ()> + std::marker::Send + 'static;
 }
 
+/// Details of the item with the given id
+/// Details of an item, along with the source it was loaded from and its id
+pub type DetailsResult = ((Sources, String), Result<ItemDetails, SourceError>);
+
 #[derive(Clone)]
 pub struct AppSync {
     config_path: PathBuf,
@@ -61,7 +76,7 @@ impl AppSync {
     }
 }
 
-#[derive(Clone, Default)]
+#[derive(Clone, Default, Serialize)]
 pub struct SearchQuery {
     pub query: String,
     pub page: usize,
@@ -71,6 +86,16 @@ pub struct SearchQuery {
     pub user: Option<String>,
 }
 
+impl SearchQuery {
+    /// Whether both queries search for the same results, regardless of their page and sort
+    pub fn same_query(&self, other: &SearchQuery) -> bool {
+        self.query == other.query
+            && self.category == other.category
+            && self.filter == other.filter
+            && self.user == other.user
+    }
+}
+
 #[derive(Clone)]
 pub enum ReloadType {
     Config,
@@ -91,7 +116,7 @@ fn watch(path: &PathBuf, last_modified: SystemTime) -> bool {
 impl EventSync for AppSync {
     async fn load_results(
         self,
-        tx_res: mpsc::Sender<Result<SourceResults, Box<dyn Error + Send + Sync>>>,
+        tx_res: mpsc::Sender<Result<SourceResults, SourceError>>,
         load_type: LoadType,
         src: Sources,
         client: reqwest::Client,
@@ -100,12 +125,41 @@ impl EventSync for AppSync {
         theme: Theme,
         extra: SourceExtraConfig,
     ) {
-        let res = src.load(load_type, &client, &search, &config, &extra).await;
+        let tx_retry = tx_res.clone();
+        let all_pages = match load_type {
+            LoadType::AllPages(pages) => Some(pages),
+            _ => None,
+        };
+        let res = request::report(
+            move |retry| {
+                let _ = tx_retry.try_send(Ok(SourceResults::Progress(retry.to_string())));
+            },
+            async {
+                match all_pages {
+                    Some(pages) => {
+                        let on_progress = |done, total| {
+                            let _ = tx_res.try_send(Ok(SourceResults::Progress(format!(
+                                "{}/{} pages",
+                                done, total
+                            ))));
+                        };
+                        pages::load_all(src, &client, &search, &config, &extra, pages, on_progress)
+                            .await
+                    }
+                    None => src.load(load_type, &client, &search, &config, &extra).await,
+                }
+            },
+        )
+        .await;
         let fmt = match res {
+            // Pages are formatted once shown, after being sorted and filtered
+            Ok(SourceResponse::Results(res)) if all_pages.is_some() => Ok(SourceResults::Results(
+                Results::all_pages(search.clone(), res),
+            )),
             Ok(SourceResponse::Results(res)) => Ok(SourceResults::Results(Results::new(
                 search.clone(),
                 res.clone(),
-                src.format_table(&res.items, &search, &config, &theme),
+                src.format_table(&res.items, &search, &config, &extra, &theme),
             ))),
             #[cfg(feature = "captcha")]
             Ok(SourceResponse::Captcha(c)) => Ok(SourceResults::Captcha(c)),
@@ -134,6 +188,18 @@ impl EventSync for AppSync {
         let _ = tx_dl.send(res).await;
     }
 
+    async fn load_details(
+        self,
+        tx_details: mpsc::Sender<DetailsResult>,
+        src: Sources,
+        client: reqwest::Client,
+        item: Item,
+        config: SourceConfig,
+    ) {
+        let res = src.details(&client, &item, &config).await;
+        let _ = tx_details.send(((src.of_item(&item), item.id), res)).await;
+    }
+
     async fn read_event_loop(self, tx_evt: mpsc::Sender<Event>) {
         loop {
             if let Ok(evt) = event::read() {
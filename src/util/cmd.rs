@@ -1,7 +1,10 @@
 use std::{
     error::Error,
-    io::{BufReader, Read as _},
+    io::{BufReader, Read as _, Write as _},
     process::{Command, Stdio},
+    sync::atomic::{AtomicBool, Ordering},
+    thread,
+    time::{Duration, Instant},
 };
 
 pub struct CommandBuilder {
@@ -55,6 +58,86 @@ impl CommandBuilder {
         }
     }
 
+    /// Run the command with `input` written to its stdin, returning what it wrote to stdout.
+    /// The command is killed if it is still running after `timeout`, or once `cancel` is set.
+    pub fn output<S: Into<Option<String>>>(
+        &self,
+        shell: S,
+        input: Option<String>,
+        timeout: Option<Duration>,
+        cancel: Option<&AtomicBool>,
+    ) -> Result<String, Box<dyn Error + Send + Sync>> {
+        let shell = Into::<Option<String>>::into(shell).unwrap_or(Self::default_shell());
+        let cmds = shell.split_whitespace().collect::<Vec<&str>>();
+        let [base_cmd, args @ ..] = cmds.as_slice() else {
+            return Err(format!("Shell command is not properly formatted:\n{}", shell).into());
+        };
+        let mut child = Command::new(base_cmd)
+            .args(args)
+            .arg(&self.cmd)
+            .stdin(match input {
+                Some(_) => Stdio::piped(),
+                None => Stdio::null(),
+            })
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| format!("{}:\nFailed to run:\n{}", self.cmd, e))?;
+
+        // Write and read on separate threads so a full pipe cannot block waiting on the child
+        if let (Some(mut stdin), Some(input)) = (child.stdin.take(), input) {
+            thread::spawn(move || stdin.write_all(input.as_bytes()));
+        }
+        let read = |pipe: Option<Box<dyn std::io::Read + Send>>| {
+            thread::spawn(move || {
+                let mut out = String::new();
+                if let Some(mut pipe) = pipe {
+                    pipe.read_to_string(&mut out).unwrap_or(0);
+                }
+                out
+            })
+        };
+        let stdout = read(child.stdout.take().map(|p| Box::new(p) as _));
+        let stderr = read(child.stderr.take().map(|p| Box::new(p) as _));
+
+        let start = Instant::now();
+        let status = loop {
+            if let Some(status) = child
+                .try_wait()
+                .map_err(|e| format!("{}:\nFailed to get output:\n{}", self.cmd, e))?
+            {
+                break status;
+            }
+            if cancel.is_some_and(|c| c.load(Ordering::Relaxed)) {
+                child.kill().unwrap_or(());
+                child.wait().ok();
+                return Err(format!("{}:\nCancelled", self.cmd).into());
+            }
+            if timeout.is_some_and(|t| start.elapsed() >= t) {
+                child.kill().unwrap_or(());
+                child.wait().ok();
+                return Err(format!(
+                    "{}:\nTimed out after {} seconds",
+                    self.cmd,
+                    timeout.unwrap_or_default().as_secs()
+                )
+                .into());
+            }
+            thread::sleep(Duration::from_millis(10));
+        };
+
+        let stdout = stdout.join().unwrap_or_default();
+        if status.code() != Some(0) {
+            let err_str = stderr.join().unwrap_or_default();
+            return Err(format!(
+                "{}:\nExited with status code {}:\n{}",
+                self.cmd, status, err_str
+            )
+            .into());
+        }
+        Ok(stdout)
+    }
+
     pub fn default_shell() -> String {
         #[cfg(windows)]
         return "powershell.exe -Command".to_owned();
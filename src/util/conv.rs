@@ -49,12 +49,6 @@ pub fn to_relative_date(time: DateTime<Local>, short: bool) -> String {
     }
 }
 
-pub fn get_hash(magnet: String) -> Option<String> {
-    magnet
-        .split_once("xt=urn:btih:")
-        .and_then(|m| m.1.split_once('&').map(|m| m.0.to_owned()))
-}
-
 pub fn add_protocol<S: Into<String>>(
     url: S,
     default_https: bool,
@@ -72,12 +66,14 @@ pub fn add_protocol<S: Into<String>>(
     Ok(format!("{}://{}", protocol, url).parse::<Url>()?)
 }
 
-pub fn to_bytes(size: &str) -> usize {
+pub fn to_bytes(size: &str) -> u64 {
+    parse_bytes(size).unwrap_or_default()
+}
+
+/// Read a size like "1.4 GiB" into bytes, if it starts with a number
+pub fn parse_bytes(size: &str) -> Option<u64> {
     let mut split = size.split_whitespace();
-    let f = split
-        .next()
-        .and_then(|b| b.parse::<f64>().ok())
-        .unwrap_or(-1.);
+    let f = split.next().and_then(|b| b.parse::<f64>().ok())?;
     let power = match split.last().and_then(|u| u.chars().next()) {
         Some('T') => 4,
         Some('G') => 3,
@@ -85,7 +81,22 @@ pub fn to_bytes(size: &str) -> usize {
         Some('K') => 1,
         _ => 0,
     };
-    (1024_f64.powi(power) * f) as usize
+    Some((1024_f64.powi(power) * f) as u64)
+}
+
+/// Format a number of bytes like the sizes shown by nyaa, e.g. "1.4 GB"
+pub fn to_size(bytes: u64) -> String {
+    let units = ["B", "KB", "MB", "GB", "TB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024. && unit < units.len() - 1 {
+        size /= 1024.;
+        unit += 1;
+    }
+    match unit {
+        0 => format!("{} B", bytes),
+        _ => format!("{:.1} {}", size, units[unit]),
+    }
 }
 
 pub fn shorten_number(n: u32) -> String {
@@ -107,7 +107,7 @@ pub fn truncate_ellipsis(
         ['…'].repeat(repeat).iter().collect()
     });
 
-    return (el, chars.iter().collect::<String>(), er);
+    (el, chars.iter().collect::<String>(), er)
 }
 
 pub fn back_word(input: &str, start: usize) -> usize {
@@ -20,11 +20,14 @@ use crate::{app::Context, style, theme::Theme};
 pub mod captcha;
 
 pub mod batch;
+pub mod bookmarks;
 pub mod category;
 pub mod clients;
+pub mod details;
 pub mod filter;
 pub mod help;
 pub mod input;
+pub mod local_filter;
 pub mod notifications;
 pub mod notify_box;
 pub mod page;
@@ -140,7 +143,7 @@ pub fn centered_rect(mut x_len: u16, mut y_len: u16, r: Rect) -> Rect {
     .split(popup_layout[1])[1]
 }
 
-pub fn border_block(theme: &Theme, focused: bool) -> Block {
+pub fn border_block(theme: &Theme, focused: bool) -> Block<'_> {
     Block::new()
         .border_style(match focused {
             true => style!(fg:theme.border_focused_color),
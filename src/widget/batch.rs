@@ -12,6 +12,7 @@ use crate::{
     app::{Context, LoadType, Mode},
     source::ItemType,
     title,
+    util::conv::to_size,
 };
 
 use super::{border_block, VirtualStatefulTable};
@@ -32,7 +33,7 @@ impl super::Widget for BatchWidget {
     fn draw(&mut self, f: &mut Frame, ctx: &Context, area: Rect) {
         let buf = f.buffer_mut();
 
-        let size = human_bytes(ctx.batch.iter().fold(0, |acc, i| acc + i.bytes) as f64);
+        let size = human_bytes(ctx.batch.iter().fold(0, |acc, i| acc + i.size) as f64);
         let right_str = title!("Size({}): {}", ctx.batch.len(), size);
         let block = border_block(&ctx.theme, ctx.mode == Mode::Batch)
             .title(title!("Batch"))
@@ -53,7 +54,7 @@ impl super::Widget for BatchWidget {
                         ItemType::Remake => ctx.theme.error,
                         ItemType::None => ctx.theme.fg,
                     }),
-                    format!("{:>9}", i.size).fg(ctx.theme.fg),
+                    format!("{:>9}", to_size(i.size)).fg(ctx.theme.fg),
                 ])
             })
             .collect::<Vec<Row>>();
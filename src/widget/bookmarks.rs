@@ -0,0 +1,185 @@
+use std::cmp::min;
+
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyEventKind};
+use ratatui::{
+    layout::{Constraint, Margin, Rect},
+    widgets::{Row, ScrollbarOrientation, StatefulWidget as _, Table},
+    Frame,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    app::{Context, LoadType, Mode},
+    history::HistoryEntry,
+    style, title,
+};
+
+use super::{border_block, VirtualStatefulTable, Widget};
+
+pub static BOOKMARKS_FILE: &str = "bookmarks.toml";
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Bookmark {
+    pub name: String,
+    #[serde(flatten)]
+    pub search: HistoryEntry,
+}
+
+#[derive(Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct Bookmarks {
+    #[serde(rename = "bookmark", skip_serializing_if = "Vec::is_empty")]
+    pub bookmarks: Vec<Bookmark>,
+}
+
+impl Bookmark {
+    /// Bookmark the search the current results were loaded with
+    pub fn from_results(ctx: &Context) -> Self {
+        let search = &ctx.results.search;
+        let name = match search.query.is_empty() {
+            true => ctx.src_info.clone().entry_from_id(search.category).name,
+            false => search.query.to_owned(),
+        };
+        Self {
+            name,
+            search: HistoryEntry::new(search, ctx.src),
+        }
+    }
+}
+
+impl Bookmarks {
+    /// Add a bookmark, replacing any with the same name on the same source.
+    /// Returns whether a bookmark was replaced.
+    pub fn add(&mut self, bookmark: Bookmark) -> bool {
+        match self
+            .bookmarks
+            .iter_mut()
+            .find(|b| b.name == bookmark.name && b.search.source == bookmark.search.source)
+        {
+            Some(b) => {
+                *b = bookmark;
+                true
+            }
+            None => {
+                self.bookmarks.push(bookmark);
+                false
+            }
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct BookmarksPopup {
+    pub table: VirtualStatefulTable,
+    /// Index into `ctx.bookmarks` of the bookmark to search with
+    launched: Option<usize>,
+}
+
+impl BookmarksPopup {
+    pub fn take_launched(&mut self) -> Option<usize> {
+        self.launched.take()
+    }
+}
+
+impl Widget for BookmarksPopup {
+    fn draw(&mut self, f: &mut Frame, ctx: &Context, area: Rect) {
+        let buf = f.buffer_mut();
+        let bookmarks = &ctx.bookmarks.bookmarks;
+        let height = min(min(bookmarks.len().max(1) as u16 + 2, 12), area.height);
+        let center = super::centered_rect(50, height, area);
+        let items = bookmarks
+            .iter()
+            .map(|b| Row::new([format!(" {}", b.name), b.search.source.to_string()]));
+
+        let num_items = items.len();
+        super::scroll_padding(
+            self.table.selected().unwrap_or(0),
+            center.height as usize,
+            2,
+            num_items,
+            1,
+            self.table.state.offset_mut(),
+        );
+
+        let mut table = Table::new(items, [Constraint::Min(1), Constraint::Length(14)])
+            .block(border_block(&ctx.theme, true).title(title!("Bookmarks")))
+            .highlight_style(style!(bg:ctx.theme.hl_bg));
+        if num_items == 0 {
+            table = table.rows([Row::new([" Press B to bookmark the current search"])]);
+        }
+        super::clear(center, buf, ctx.theme.bg);
+        table.render(center, buf, &mut self.table.state);
+
+        // Only show scrollbar if content overflows
+        if num_items as u16 + 1 >= center.height {
+            let sb = super::scrollbar(ctx, ScrollbarOrientation::VerticalRight);
+            let sb_area = center.inner(Margin {
+                vertical: 1,
+                horizontal: 0,
+            });
+            sb.render(
+                sb_area,
+                buf,
+                &mut self.table.scrollbar_state.content_length(num_items),
+            );
+        }
+    }
+
+    fn handle_event(&mut self, ctx: &mut Context, e: &Event) {
+        if let Event::Key(KeyEvent {
+            code,
+            kind: KeyEventKind::Press,
+            ..
+        }) = e
+        {
+            let len = ctx.bookmarks.bookmarks.len();
+            match code {
+                KeyCode::Esc | KeyCode::Char('b') | KeyCode::Char('q') => {
+                    ctx.mode = Mode::Normal;
+                }
+                KeyCode::Char('j') | KeyCode::Down => {
+                    self.table.next_wrap(len, 1);
+                }
+                KeyCode::Char('k') | KeyCode::Up => {
+                    self.table.next_wrap(len, -1);
+                }
+                KeyCode::Char('G') => {
+                    self.table.select(len.saturating_sub(1));
+                }
+                KeyCode::Char('g') => {
+                    self.table.select(0);
+                }
+                KeyCode::Char('x') | KeyCode::Delete => {
+                    let idx = self.table.selected().unwrap_or(0);
+                    if idx < len {
+                        let bookmark = ctx.bookmarks.bookmarks.remove(idx);
+                        self.table.select(idx.min(len.saturating_sub(2)));
+                        ctx.save_bookmarks();
+                        ctx.notify_info(format!("Removed bookmark \"{}\"", bookmark.name));
+                    }
+                }
+                KeyCode::Enter => {
+                    let idx = self.table.selected().unwrap_or(0);
+                    if idx < len {
+                        self.launched = Some(idx);
+                        ctx.page = 1;
+                        ctx.mode = Mode::Loading(LoadType::Searching);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn get_help() -> Option<Vec<(&'static str, &'static str)>> {
+        Some(vec![
+            ("Enter", "Search"),
+            ("Esc, b, q", "Close"),
+            ("x, Del", "Remove bookmark"),
+            ("j, ↓", "Down"),
+            ("k, ↑", "Up"),
+            ("g", "Top"),
+            ("G", "Bottom"),
+        ])
+    }
+}
@@ -184,20 +184,18 @@ impl Widget for CategoryPopup {
                         self.table.select(self.major + self.minor + 1);
                     }
                 }
-                KeyCode::Char('k') | KeyCode::Up => {
-                    if ctx.src_info.cats.get(self.major).is_some() {
-                        self.minor = match self.minor < 1 {
-                            true => {
-                                self.prev_tab(ctx.src_info.cats.len());
-                                match ctx.src_info.cats.get(self.major) {
-                                    Some(cat) => cat.entries.len() - 1,
-                                    None => 0,
-                                }
+                KeyCode::Char('k') | KeyCode::Up if ctx.src_info.cats.get(self.major).is_some() => {
+                    self.minor = match self.minor < 1 {
+                        true => {
+                            self.prev_tab(ctx.src_info.cats.len());
+                            match ctx.src_info.cats.get(self.major) {
+                                Some(cat) => cat.entries.len() - 1,
+                                None => 0,
                             }
-                            false => self.minor - 1,
-                        };
-                        self.table.select(self.major + self.minor + 1);
-                    }
+                        }
+                        false => self.minor - 1,
+                    };
+                    self.table.select(self.major + self.minor + 1);
                 }
                 KeyCode::Char('G') => {
                     if let Some(cat) = ctx.src_info.cats.get(self.major) {
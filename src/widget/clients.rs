@@ -4,7 +4,6 @@ use ratatui::{
     widgets::{Row, StatefulWidget as _, Table},
     Frame,
 };
-use strum::VariantArray;
 
 use crate::{
     app::{Context, Mode},
@@ -21,7 +20,7 @@ pub struct ClientsPopup {
 impl Default for ClientsPopup {
     fn default() -> Self {
         ClientsPopup {
-            table: StatefulTable::new(Client::VARIANTS),
+            table: StatefulTable::new(&Client::all()),
         }
     }
 }
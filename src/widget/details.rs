@@ -0,0 +1,217 @@
+use std::collections::HashMap;
+
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use ratatui::{
+    layout::Rect,
+    style::Stylize as _,
+    text::{Line, Span},
+    widgets::{Paragraph, Widget as _, Wrap},
+    Frame,
+};
+
+use crate::{
+    app::{Context, Mode},
+    source::{nyaa_html::ItemDetails, Item, Sources},
+    style, title,
+};
+
+use super::{border_block, Widget};
+
+#[derive(Default)]
+pub struct DetailsPopup {
+    /// Details already fetched, by the source an item was loaded from and `Item.id`, since
+    /// merged results may share ids
+    pub cache: HashMap<(Sources, String), ItemDetails>,
+    item: Option<(Sources, Item)>,
+    /// Show the comments pane instead of the details
+    comments: bool,
+    scroll: u16,
+    height: u16,
+    num_lines: u16,
+}
+
+impl DetailsPopup {
+    pub fn show(&mut self, src: Sources, item: Item) {
+        self.item = Some((src, item));
+        self.comments = false;
+        self.scroll = 0;
+    }
+
+    pub fn is_showing(&self, src: Sources, id: &str) -> bool {
+        self.item
+            .as_ref()
+            .is_some_and(|(s, i)| *s == src && i.id == id)
+    }
+
+    fn scroll_by(&mut self, amt: i32) {
+        let max = self.num_lines.saturating_sub(self.height) as i32;
+        self.scroll = (self.scroll as i32 + amt).clamp(0, max.max(0)) as u16;
+    }
+}
+
+fn details_lines<'a>(ctx: &Context, details: &'a ItemDetails) -> Vec<Line<'a>> {
+    let field = |name: &'static str, value: String| {
+        Line::from(vec![
+            Span::styled(name, style!(bold, fg:ctx.theme.border_focused_color)),
+            Span::raw(value),
+        ])
+    };
+    let header = |name: String| Line::styled(name, style!(bold, underlined, fg:ctx.theme.fg));
+
+    let mut lines = vec![
+        field("Submitter: ", details.submitter.to_owned()),
+        field("Info hash: ", details.info_hash.to_owned()),
+        field("Comments:  ", details.comment_count.to_string()),
+        Line::default(),
+        header("Description".to_owned()),
+    ];
+    lines.extend(details.description.lines().map(Line::raw));
+    lines.push(Line::default());
+    lines.push(header(format!("Files ({})", details.files.len())));
+    lines.extend(details.files.iter().map(|f| {
+        let indent = "  ".repeat(f.depth);
+        match &f.size {
+            Some(size) => Line::from(vec![
+                Span::raw(format!("{}{} ", indent, f.name)),
+                Span::styled(size.to_owned(), style!(fg:ctx.theme.info)),
+            ]),
+            None => Line::from(format!("{}▼ {}", indent, f.name)).bold(),
+        }
+    }));
+    lines
+}
+
+fn comment_lines<'a>(ctx: &Context, details: &'a ItemDetails) -> Vec<Line<'a>> {
+    if details.comments.is_empty() {
+        return vec![Line::from("No comments")];
+    }
+    details
+        .comments
+        .iter()
+        .enumerate()
+        .flat_map(|(i, c)| {
+            let mut author = vec![Span::styled(
+                c.author.to_owned(),
+                match c.trusted {
+                    true => style!(bold, fg:ctx.theme.success),
+                    false => style!(bold, fg:ctx.theme.fg),
+                },
+            )];
+            if c.trusted {
+                author.push(Span::styled(" [trusted]", style!(fg:ctx.theme.success)));
+            }
+            if c.uploader {
+                author.push(Span::styled(" [uploader]", style!(fg:ctx.theme.info)));
+            }
+            author.push(Span::styled(
+                format!("  {}", c.date),
+                style!(fg:ctx.theme.border_color),
+            ));
+
+            let mut lines = match i {
+                0 => vec![],
+                _ => vec![Line::default()],
+            };
+            lines.push(Line::from(author));
+            lines.extend(c.body.lines().map(Line::raw));
+            lines
+        })
+        .collect()
+}
+
+impl Widget for DetailsPopup {
+    fn draw(&mut self, f: &mut Frame, ctx: &Context, area: Rect) {
+        let buf = f.buffer_mut();
+        let center = super::centered_rect(
+            area.width.saturating_sub(4).min(100),
+            area.height.saturating_sub(2),
+            area,
+        );
+        let Some((src, item)) = &self.item else {
+            return;
+        };
+        let details = self.cache.get(&(*src, item.id.to_owned()));
+        let lines = match details {
+            Some(details) if self.comments => comment_lines(ctx, details),
+            Some(details) => details_lines(ctx, details),
+            None => vec![Line::from("Loading…")],
+        };
+        // Count lines after wrapping so the end of the page can be scrolled to
+        let width = center.width.saturating_sub(2).max(1);
+        self.height = center.height.saturating_sub(2);
+        self.num_lines = lines
+            .iter()
+            .map(|l| (l.width() as u16).max(1).div_ceil(width))
+            .sum();
+
+        let tab = |name: String, active: bool| match active {
+            true => Span::styled(name, style!(bold, fg:ctx.theme.border_focused_color)),
+            false => Span::styled(name, style!(fg:ctx.theme.border_color)),
+        };
+        let comments = details
+            .map(|d| format!("Comments ({})", d.comment_count))
+            .unwrap_or("Comments".to_owned());
+        let tabs = Line::from(vec![
+            tab("Details".to_owned(), !self.comments),
+            Span::raw(" │ "),
+            tab(comments, self.comments),
+        ]);
+
+        let p = Paragraph::new(lines)
+            .block(
+                border_block(&ctx.theme, true)
+                    .title(title!("{}", item.title))
+                    .title_bottom(tabs),
+            )
+            .fg(ctx.theme.fg)
+            .wrap(Wrap { trim: false })
+            .scroll((self.scroll, 0));
+        super::clear(center, buf, ctx.theme.bg);
+        p.render(center, buf);
+    }
+
+    fn handle_event(&mut self, ctx: &mut Context, e: &Event) {
+        if let Event::Key(KeyEvent {
+            code,
+            kind: KeyEventKind::Press,
+            modifiers,
+            ..
+        }) = e
+        {
+            use KeyCode::*;
+            match (code, modifiers) {
+                (Esc | Char('D') | Char('q'), _) => {
+                    ctx.mode = Mode::Normal;
+                }
+                (Char('j') | Down, &KeyModifiers::NONE) => self.scroll_by(1),
+                (Char('k') | Up, &KeyModifiers::NONE) => self.scroll_by(-1),
+                (Char('J'), &KeyModifiers::SHIFT) | (PageDown, _) => {
+                    self.scroll_by(self.height as i32 / 2)
+                }
+                (Char('K'), &KeyModifiers::SHIFT) | (PageUp, _) => {
+                    self.scroll_by(-(self.height as i32 / 2))
+                }
+                (Tab | BackTab | Char('c'), _) => {
+                    self.comments = !self.comments;
+                    self.scroll = 0;
+                }
+                (Char('g'), &KeyModifiers::NONE) => self.scroll = 0,
+                (Char('G'), &KeyModifiers::SHIFT) => self.scroll_by(i32::MAX / 2),
+                _ => {}
+            }
+        }
+    }
+
+    fn get_help() -> Option<Vec<(&'static str, &'static str)>> {
+        Some(vec![
+            ("Esc, D, q", "Close"),
+            ("Tab, c", "Switch details/comments"),
+            ("j, ↓", "Scroll down"),
+            ("k, ↑", "Scroll up"),
+            ("J, PgDn", "Scroll down half a page"),
+            ("K, PgUp", "Scroll up half a page"),
+            ("g", "Top"),
+            ("G", "Bottom"),
+        ])
+    }
+}
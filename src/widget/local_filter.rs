@@ -0,0 +1,94 @@
+use crate::{
+    app::{Context, LoadType, Mode},
+    results::filter::ResultFilter,
+    title,
+};
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyEventKind};
+use ratatui::{
+    layout::{Margin, Rect},
+    widgets::{Paragraph, Widget as _},
+    Frame,
+};
+
+use super::{
+    border_block,
+    input::{self, InputWidget},
+    Widget,
+};
+
+pub struct LocalFilterPopup {
+    pub input: InputWidget,
+}
+
+impl Default for LocalFilterPopup {
+    fn default() -> Self {
+        LocalFilterPopup {
+            input: InputWidget::new(56, None),
+        }
+    }
+}
+
+impl Widget for LocalFilterPopup {
+    fn draw(&mut self, f: &mut Frame, ctx: &Context, area: Rect) {
+        let buf = f.buffer_mut();
+        let center = super::centered_rect(60, 3, area);
+        let page_p = Paragraph::new(self.input.input.clone());
+        let indicator = Paragraph::new(">")
+            .block(border_block(&ctx.theme, true).title(title!("Filter Loaded Results")));
+        super::clear(center, buf, ctx.theme.bg);
+        indicator.render(center, buf);
+
+        let input_area = center.inner(Margin {
+            vertical: 1,
+            horizontal: 1,
+        });
+        let input_area = Rect::new(
+            input_area.x + 2,
+            input_area.y,
+            input_area.width,
+            input_area.height,
+        );
+        page_p.render(input_area, buf);
+
+        if ctx.mode == Mode::LocalFilter {
+            self.input.show_cursor(f, input_area);
+        }
+    }
+
+    fn handle_event(&mut self, ctx: &mut Context, e: &Event) {
+        if let Event::Key(KeyEvent {
+            code,
+            kind: KeyEventKind::Press,
+            ..
+        }) = e
+        {
+            match code {
+                KeyCode::Esc => {
+                    ctx.mode = Mode::Normal;
+                }
+                KeyCode::Enter if self.input.input.trim().is_empty() => {
+                    ctx.local_filter = None;
+                    ctx.mode = Mode::Loading(LoadType::LocalFiltering);
+                }
+                KeyCode::Enter => match self.input.input.parse::<ResultFilter>() {
+                    Ok(filter) => {
+                        ctx.local_filter = Some(filter);
+                        ctx.mode = Mode::Loading(LoadType::LocalFiltering);
+                    }
+                    Err(e) => ctx.notify_error(format!("Invalid filter:\n{}", e)),
+                },
+                _ => {
+                    self.input.handle_event(ctx, e);
+                }
+            }
+        }
+    }
+
+    fn get_help() -> Option<Vec<(&'static str, &'static str)>> {
+        let mut search_help = vec![("Enter", "Confirm, empty to clear"), ("Esc", "Stop")];
+        if let Some(input_help) = input::InputWidget::get_help() {
+            search_help.extend(input_help);
+        }
+        Some(search_help)
+    }
+}
@@ -129,7 +129,7 @@ impl NotificationWidget {
         let res = self
             .notifs
             .iter_mut()
-            .fold(false, |acc, x| x.update(deltatime, area) || acc);
+            .fold(false, |acc, x| x.update(deltatime, area) | acc);
         let finished = self
             .notifs
             .iter()
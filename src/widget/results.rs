@@ -12,8 +12,10 @@ use ratatui::{
 
 use crate::{
     app::{Context, LoadType, Mode},
+    source::SourceExtraConfig,
+    sync::SearchQuery,
     title,
-    widget::sort::SortDir,
+    widget::{bookmarks::Bookmark, sort::SortDir},
 };
 
 use super::{border_block, centered_rect, VirtualStatefulTable};
@@ -38,6 +40,22 @@ impl ResultsWidget {
         *self.table.state.offset_mut() = 0;
     }
 
+    /// The page after the loaded results, once the cursor is within `rows` of the last result
+    pub fn next_page(&self, ctx: &Context, rows: usize) -> Option<SearchQuery> {
+        let len = ctx.results.response.items.len();
+        if rows == 0
+            || ctx.results.all.is_some()
+            || ctx.page >= ctx.results.response.last_page
+            || self.table.selected().unwrap_or(0) + rows < len
+        {
+            return None;
+        }
+        Some(SearchQuery {
+            page: ctx.page + 1,
+            ..ctx.results.search.clone()
+        })
+    }
+
     fn try_select_add(&self, ctx: &mut Context, start: usize, stop: usize) {
         if let Some(item) = ctx.results.response.items.get(start..=stop) {
             item.iter().for_each(|i| {
@@ -123,7 +141,10 @@ impl super::Widget for ResultsWidget {
         Clear.render(area, buf);
         let items: Vec<Row> = match &ctx.load_type {
             Some(loadtype) => {
-                let message = format!("{}…", loadtype);
+                let message = match &ctx.load_progress {
+                    Some(progress) => format!("{}… ({})", loadtype, progress),
+                    None => format!("{}…", loadtype),
+                };
                 let load_area = centered_rect(message.len() as u16, 1, area);
                 Paragraph::new(message).render(load_area, buf);
                 vec![]
@@ -145,23 +166,68 @@ impl super::Widget for ResultsWidget {
         });
 
         let num_items = items.len();
-        let first_item = (ctx.page - 1) * 75;
+        let page_size = match ctx.results.all {
+            Some(_) => ctx.config.all_pages.page_size.max(1),
+            None => 75,
+        };
+        // Pages appended by scrolling are counted from the first one shown
+        let first_page = match ctx.results.all {
+            Some(_) => ctx.page,
+            None => ctx.results.search.page.max(1),
+        };
+        let first_item = (first_page - 1) * page_size;
         let focused = matches!(ctx.mode, Mode::Normal | Mode::KeyCombo(_));
 
-        let dl_src = title!(
-            "dl: {}, src: {}",
-            ctx.client.to_string(),
-            ctx.src.to_string()
-        );
+        let dl_src = match &ctx.results.response.mirror {
+            Some(mirror) => title!(
+                "dl: {}, src: {} ({})",
+                ctx.client.to_string(),
+                ctx.src.to_string(),
+                mirror
+            ),
+            None => title!(
+                "dl: {}, src: {}",
+                ctx.client.to_string(),
+                ctx.src.to_string()
+            ),
+        };
 
-        let title = title!(
-            "Results {}-{} ({} total): Page {}/{}",
-            first_item + 1,
-            num_items + first_item,
-            ctx.results.response.total_results,
-            ctx.page,
-            ctx.results.response.last_page,
-        );
+        let title = match (&ctx.results.all, &ctx.results.unfiltered) {
+            (Some(all), _) if ctx.local_filter.is_some() => title!(
+                "Results {}-{} ({} matching, {} from all pages): Page {}/{}",
+                first_item + 1,
+                num_items + first_item,
+                ctx.results.response.total_results,
+                all.len(),
+                ctx.page,
+                ctx.results.response.last_page,
+            ),
+            (Some(all), _) => title!(
+                "Results {}-{} ({} from all pages): Page {}/{}",
+                first_item + 1,
+                num_items + first_item,
+                all.len(),
+                ctx.page,
+                ctx.results.response.last_page,
+            ),
+            (None, Some(unfiltered)) => title!(
+                "Results {}-{} ({} matching, {} total): Page {}/{}",
+                first_item + 1,
+                unfiltered.len() + first_item,
+                num_items,
+                ctx.results.response.total_results,
+                ctx.page,
+                ctx.results.response.last_page,
+            ),
+            (None, None) => title!(
+                "Results {}-{} ({} total): Page {}/{}",
+                first_item + 1,
+                num_items + first_item,
+                ctx.results.response.total_results,
+                ctx.page,
+                ctx.results.response.last_page,
+            ),
+        };
         let mut block = border_block(&ctx.theme, focused)
             .title(title)
             .title_top(Line::from(dl_src).right_aligned());
@@ -169,6 +235,18 @@ impl super::Widget for ResultsWidget {
             let key_str = title!(ctx.last_key);
             block = block.title_bottom(Line::from(key_str).right_aligned());
         }
+        // Relative dates are not precise, show when the selected item was published
+        if ctx.config.relative_date.unwrap_or(false) && ctx.load_type.is_none() {
+            let date = self
+                .table
+                .selected()
+                .and_then(|i| ctx.results.response.items.get(i))
+                .map(|i| SourceExtraConfig::from(&ctx.config).format_absolute_date(i.date))
+                .unwrap_or_default();
+            if !date.is_empty() {
+                block = block.title_bottom(Line::from(title!(date)).left_aligned());
+            }
+        }
 
         let table = Table::new(items, ctx.results.table.binding.to_owned())
             .header(header)
@@ -247,6 +325,9 @@ impl super::Widget for ResultsWidget {
                 (Char('f'), &KeyModifiers::NONE) => {
                     ctx.mode = Mode::Filter;
                 }
+                (Char('F'), &KeyModifiers::SHIFT) => {
+                    ctx.mode = Mode::LocalFilter;
+                }
                 (Char('t'), &KeyModifiers::NONE) => {
                     ctx.mode = Mode::Theme;
                 }
@@ -256,24 +337,34 @@ impl super::Widget for ResultsWidget {
                 (Char('p'), &KeyModifiers::CONTROL) => {
                     ctx.mode = Mode::Page;
                 }
-                (Char('p') | Char('h') | Left, &KeyModifiers::NONE) => {
-                    if ctx.page > 1 {
-                        ctx.page -= 1;
-                        ctx.mode = Mode::Loading(LoadType::Searching);
-                    }
+                (Char('p') | Char('h') | Left, &KeyModifiers::NONE) if ctx.page > 1 => {
+                    ctx.page -= 1;
+                    ctx.mode = Mode::Loading(LoadType::Searching);
                 }
-                (Char('n') | Char('l') | Right, &KeyModifiers::NONE) => {
-                    if ctx.page < ctx.results.response.last_page {
-                        ctx.page += 1;
-                        ctx.mode = Mode::Loading(LoadType::Searching);
-                    }
+                (Char('n') | Char('l') | Right, &KeyModifiers::NONE)
+                    if ctx.page < ctx.results.response.last_page =>
+                {
+                    ctx.page += 1;
+                    ctx.mode = Mode::Loading(LoadType::Searching);
                 }
                 (Char('r'), &KeyModifiers::NONE) => {
-                    ctx.mode = Mode::Loading(LoadType::Searching);
+                    ctx.mode = Mode::Loading(LoadType::Reloading);
+                }
+                (Char('A'), &KeyModifiers::SHIFT) => {
+                    ctx.page = 1;
+                    ctx.mode = Mode::Loading(LoadType::AllPages(ctx.config.all_pages));
                 }
                 (Char('q'), &KeyModifiers::NONE) => {
                     ctx.quit();
                 }
+                (Char('j') | KeyCode::Down, &KeyModifiers::NONE)
+                | (Char('J'), &KeyModifiers::SHIFT)
+                    if ctx.config.infinite_scroll
+                        && ctx.load_type.is_none()
+                        && self.next_page(ctx, 1).is_some() =>
+                {
+                    ctx.mode = Mode::Loading(LoadType::Appending);
+                }
                 (Char('j') | KeyCode::Down, &KeyModifiers::NONE) => {
                     let prev = self.table.selected().unwrap_or(0);
                     let selected = self.table.next(ctx.results.response.items.len(), 1);
@@ -308,19 +399,16 @@ impl super::Widget for ResultsWidget {
                     self.table.select(0);
                     self.select_on_move(ctx, prev, 0, prev.saturating_sub(1));
                 }
-                (Char('H') | Char('P'), &KeyModifiers::SHIFT) => {
-                    if ctx.page != 1 {
-                        ctx.page = 1;
-                        ctx.mode = Mode::Loading(LoadType::Searching);
-                    }
+                (Char('H') | Char('P'), &KeyModifiers::SHIFT) if ctx.page != 1 => {
+                    ctx.page = 1;
+                    ctx.mode = Mode::Loading(LoadType::Searching);
                 }
-                (Char('L') | Char('N'), &KeyModifiers::SHIFT) => {
+                (Char('L') | Char('N'), &KeyModifiers::SHIFT)
                     if ctx.page != ctx.results.response.last_page
-                        && ctx.results.response.last_page > 0
-                    {
-                        ctx.page = ctx.results.response.last_page;
-                        ctx.mode = Mode::Loading(LoadType::Searching);
-                    }
+                        && ctx.results.response.last_page > 0 =>
+                {
+                    ctx.page = ctx.results.response.last_page;
+                    ctx.mode = Mode::Loading(LoadType::Searching);
                 }
                 (Enter, &KeyModifiers::NONE) => {
                     ctx.mode = Mode::Loading(LoadType::Downloading);
@@ -334,6 +422,21 @@ impl super::Widget for ResultsWidget {
                 (Char('u'), &KeyModifiers::NONE) => {
                     ctx.mode = Mode::User;
                 }
+                (Char('D'), &KeyModifiers::SHIFT) => {
+                    ctx.mode = Mode::Loading(LoadType::Details);
+                }
+                (Char('b'), &KeyModifiers::NONE) => {
+                    ctx.mode = Mode::Bookmarks;
+                }
+                (Char('B'), &KeyModifiers::SHIFT) => {
+                    let bookmark = Bookmark::from_results(ctx);
+                    let name = bookmark.name.clone();
+                    match ctx.bookmarks.add(bookmark) {
+                        true => ctx.notify_info(format!("Updated bookmark \"{}\"", name)),
+                        false => ctx.notify_info(format!("Bookmarked \"{}\"", name)),
+                    }
+                    ctx.save_bookmarks();
+                }
                 (Char('o'), &KeyModifiers::NONE) => {
                     let link = ctx
                         .results
@@ -349,6 +452,16 @@ impl super::Widget for ResultsWidget {
                         ctx.notify_info(format!("Opened {}", link));
                     }
                 }
+                (Char('O'), &KeyModifiers::SHIFT) => match ctx.captcha_url.take() {
+                    Some(url) => match open::that_detached(&url) {
+                        Ok(()) => ctx.notify_info(format!("Opened {}", url)),
+                        Err(e) => {
+                            ctx.notify_error(format!("Failed to open {}:\n{}", url, e));
+                            ctx.captcha_url = Some(url);
+                        }
+                    },
+                    None => ctx.notify_warn("No captcha to solve"),
+                },
                 (Char('y'), &KeyModifiers::NONE) => ctx.mode = Mode::KeyCombo("y".to_string()),
                 (Char(' '), &KeyModifiers::CONTROL) => {
                     if self.visual_mode != VisualMode::Toggle {
@@ -428,8 +541,11 @@ impl super::Widget for ResultsWidget {
             ("p, h, ←", "Prev Page"),
             ("N, L", "Last Page"),
             ("P, H", "First Page"),
-            ("r", "Reload"),
+            ("r", "Reload, skipping the cache"),
+            ("A", "Load all pages"),
             ("o", "Open in browser"),
+            ("O", "Open captcha in browser"),
+            ("D", "View details"),
             (
                 "yt, ym, yp, yi, yn",
                 "Copy torrent/magnet/post link/imdb id/name",
@@ -440,11 +556,14 @@ impl super::Widget for ResultsWidget {
             ("/, i", "Search"),
             ("c", "Categories"),
             ("f", "Filters"),
+            ("F", "Filter loaded results"),
             ("s", "Sort"),
             ("S", "Sort reversed"),
             ("t", "Themes"),
             ("u", "Filter by User"),
             ("d", "Select download client"),
+            ("b", "Bookmarks"),
+            ("B", "Bookmark current search"),
             ("Ctrl-p", "Goto page"),
             ("Ctrl-s", "Select source"),
         ])
@@ -19,16 +19,53 @@ use super::{
 
 pub struct SearchWidget {
     pub input: InputWidget,
+    /// Index into `ctx.history` of the recalled query, if any
+    history_idx: Option<usize>,
+    /// Pattern being searched for with Ctrl-r
+    history_search: Option<String>,
+    /// Input from before recalling a query, restored when moving past the newest entry
+    draft: String,
+    /// Whether the query was submitted with Enter, so it is added to the history
+    submitted: bool,
 }
 
 impl Default for SearchWidget {
     fn default() -> Self {
         SearchWidget {
             input: InputWidget::new(300, Some(|_| true)),
+            history_idx: None,
+            history_search: None,
+            draft: String::new(),
+            submitted: false,
         }
     }
 }
 
+impl SearchWidget {
+    /// Stop recalling history, returning the index of the recalled entry
+    pub fn take_recalled(&mut self) -> Option<usize> {
+        self.history_search = None;
+        self.history_idx.take()
+    }
+
+    /// Whether the query was submitted since the last call, unlike searches for other pages
+    pub fn take_submitted(&mut self) -> bool {
+        std::mem::take(&mut self.submitted)
+    }
+
+    fn recall(&mut self, ctx: &Context, idx: Option<usize>) {
+        if self.history_idx.is_none() {
+            self.draft.clone_from(&self.input.input);
+        }
+        self.history_idx = idx;
+        self.input.input = match idx.and_then(|i| ctx.history.entries.get(i)) {
+            Some(entry) => entry.query.clone(),
+            None => self.draft.clone(),
+        };
+        self.input.set_cursor(self.input.input.chars().count());
+    }
+}
+
 impl super::Widget for SearchWidget {
     fn draw(&mut self, f: &mut Frame, ctx: &Context, area: Rect) {
         let buf = f.buffer_mut();
@@ -39,8 +76,15 @@ impl super::Widget for SearchWidget {
             "?".bold();
             " for help".into();
         );
+        let search_title = match (&self.history_search, self.history_idx) {
+            (Some(pattern), _) => title!("Search (history: \"{}\")", pattern),
+            (None, Some(idx)) => {
+                title!("Search (history {}/{})", idx + 1, ctx.history.entries.len())
+            }
+            (None, None) => title!("Search"),
+        };
         let block = border_block(&ctx.theme, ctx.mode == Mode::Search)
-            .title(title!("Search"))
+            .title(search_title)
             .title_top(Line::from(help_title).right_aligned());
         Clear.render(area, buf);
         block.render(area, buf);
@@ -64,22 +108,56 @@ impl super::Widget for SearchWidget {
         }) = evt
         {
             use KeyCode::*;
+            let len = ctx.history.entries.len();
             match (code, modifiers) {
                 (Esc, &KeyModifiers::NONE) => {
                     ctx.mode = Mode::Normal;
+                    self.take_recalled();
                 }
                 (Enter, &KeyModifiers::NONE) => {
                     ctx.mode = Mode::Loading(LoadType::Searching);
                     ctx.page = 1; // Go back to first page
+                    self.submitted = true;
+                }
+                (Up, &KeyModifiers::NONE) if len > 0 => {
+                    self.history_search = None;
+                    let idx = self.history_idx.map(|i| i.saturating_sub(1));
+                    self.recall(ctx, Some(idx.unwrap_or(len - 1)));
+                    return;
+                }
+                (Down, &KeyModifiers::NONE) if self.history_idx.is_some() => {
+                    self.history_search = None;
+                    let idx = self.history_idx.map(|i| i + 1).filter(|i| *i < len);
+                    self.recall(ctx, idx);
+                    return;
+                }
+                (Char('r'), &KeyModifiers::CONTROL) if len > 0 => {
+                    let pattern = self
+                        .history_search
+                        .get_or_insert_with(|| self.input.input.clone())
+                        .clone();
+                    let before = self.history_idx.unwrap_or(len);
+                    match ctx.history.fuzzy_find(&pattern, before) {
+                        Some(idx) => self.recall(ctx, Some(idx)),
+                        None => {
+                            ctx.notify_info(format!("No older history matching \"{}\"", pattern))
+                        }
+                    }
+                    return;
                 }
-                _ => {}
+                _ => self.history_search = None,
             };
         }
         self.input.handle_event(ctx, evt);
     }
 
     fn get_help() -> Option<Vec<(&'static str, &'static str)>> {
-        let mut search_help = vec![("Enter", "Confirm"), ("Esc", "Stop")];
+        let mut search_help = vec![
+            ("Enter", "Confirm"),
+            ("Esc", "Stop"),
+            ("↑, ↓", "Previous/next search"),
+            ("Ctrl-r", "Search history"),
+        ];
         if let Some(input_help) = input::InputWidget::get_help() {
             search_help.extend(input_help);
         }
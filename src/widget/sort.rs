@@ -15,7 +15,7 @@ use crate::{
 
 use super::{border_block, VirtualStatefulTable, Widget};
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Serialize)]
 pub struct SelectedSort {
     pub sort: usize,
     pub dir: SortDir,
@@ -4,7 +4,6 @@ use ratatui::{
     widgets::{Row, StatefulWidget as _, Table},
     Frame,
 };
-use strum::VariantArray;
 
 use crate::{
     app::{Context, LoadType, Mode},
@@ -21,7 +20,7 @@ pub struct SourcesPopup {
 impl Default for SourcesPopup {
     fn default() -> Self {
         SourcesPopup {
-            table: StatefulTable::new(Sources::VARIANTS),
+            table: StatefulTable::new(&Sources::all()),
         }
     }
 }
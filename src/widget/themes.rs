@@ -31,12 +31,7 @@ impl Default for ThemePopup {
 fn preview_theme(idx: usize, ctx: &mut Context) {
     if let Some((_, theme)) = ctx.themes.get_index(idx) {
         ctx.theme = theme.clone();
-        ctx.results.table = ctx.src.format_table(
-            &ctx.results.response.items,
-            &ctx.results.search,
-            &ctx.config.sources,
-            &ctx.theme,
-        );
+        ctx.format_results();
     }
 }
 
@@ -124,12 +119,7 @@ impl Widget for ThemePopup {
                         self.selected = idx;
                         ctx.theme = theme.clone();
                         ctx.config.theme.clone_from(&theme.name);
-                        ctx.results.table = ctx.src.format_table(
-                            &ctx.results.response.items,
-                            &ctx.results.search,
-                            &ctx.config.sources,
-                            &ctx.theme,
-                        );
+                        ctx.format_results();
                         match ctx.save_config() {
                             Ok(_) => {
                                 ctx.notify_info(format!("Updated theme to \"{}\"", theme_name))
@@ -0,0 +1,113 @@
+use nyaa::{
+    cache::{CacheHit, ResultCache},
+    results::ResultResponse,
+    source::{nyaa_html::NyaaConfig, Item, SourceConfig, Sources},
+    sync::SearchQuery,
+};
+
+#[test]
+fn test_result_cache() {
+    let dir = std::env::temp_dir().join(format!("nyaa-test-cache-{}", std::process::id()));
+    let search = SearchQuery {
+        query: "one piece".to_owned(),
+        page: 1,
+        ..Default::default()
+    };
+    let response = ResultResponse {
+        items: vec![Item {
+            id: "nyaa-1".to_owned(),
+            title: "One Piece".to_owned(),
+            category: 12,
+            ..Default::default()
+        }],
+        last_page: 1,
+        total_results: 1,
+        ..Default::default()
+    };
+
+    let mut cache = ResultCache::new(Some(dir.clone()), 60);
+    assert!(cache.get(Sources::Nyaa, &search).is_none());
+    cache.insert(Sources::Nyaa, &search, &response).unwrap();
+    assert!(matches!(
+        cache.get(Sources::Nyaa, &search),
+        Some(CacheHit::Fresh(_))
+    ));
+    assert!(cache.get(Sources::SukebeiNyaa, &search).is_none());
+    let next_page = SearchQuery {
+        page: 2,
+        ..search.clone()
+    };
+    assert!(cache.get(Sources::Nyaa, &next_page).is_none());
+
+    // Read back from disk, restoring the category icon
+    let mut cache = ResultCache::new(Some(dir.clone()), 60);
+    match cache.get(Sources::Nyaa, &search) {
+        Some(CacheHit::Fresh(res)) => {
+            assert_eq!(res.items[0].title, "One Piece");
+            assert_eq!(res.items[0].icon.label, "Sub");
+        }
+        _ => panic!("expected fresh cached results"),
+    }
+
+    // Pages loaded with another source config are not used
+    let config = SourceConfig {
+        nyaa: Some(NyaaConfig {
+            rss: true,
+            ..Default::default()
+        }),
+        ..Default::default()
+    };
+    cache.configure(&config);
+    assert!(cache.get(Sources::Nyaa, &search).is_none());
+
+    // A TTL of 0 disables the cache
+    let mut cache = ResultCache::new(Some(dir.clone()), 0);
+    assert!(cache.get(Sources::Nyaa, &search).is_none());
+
+    let _ = std::fs::remove_dir_all(dir);
+}
+
+#[test]
+fn test_cache_collision() {
+    let dir = std::env::temp_dir().join(format!("nyaa-test-collision-{}", std::process::id()));
+    let [first, second] = ["first", "second"].map(|query| SearchQuery {
+        query: query.to_owned(),
+        page: 1,
+        ..Default::default()
+    });
+    let files = |dir: &std::path::Path| -> Vec<std::path::PathBuf> {
+        std::fs::read_dir(dir)
+            .unwrap()
+            .map(|f| f.unwrap().path())
+            .collect()
+    };
+
+    // File names stay the same between runs, and don't depend on the toolchain
+    let mut cache = ResultCache::new(Some(dir.join("first")), 60);
+    cache
+        .insert(Sources::Nyaa, &first, &ResultResponse::default())
+        .unwrap();
+    let [first_file] = &files(&dir.join("first"))[..] else {
+        panic!("expected a single cached page");
+    };
+    assert_eq!(
+        first_file.file_name().unwrap().to_string_lossy(),
+        "d70734ce6c036570.json"
+    );
+    let mut cache = ResultCache::new(Some(dir.join("second")), 60);
+    cache
+        .insert(Sources::Nyaa, &second, &ResultResponse::default())
+        .unwrap();
+    let [second_file] = &files(&dir.join("second"))[..] else {
+        panic!("expected a single cached page");
+    };
+
+    // A page stored under another key is not served, even if it is in the file for this one
+    std::fs::copy(first_file, second_file).unwrap();
+    let mut cache = ResultCache::new(Some(dir.join("second")), 60);
+    assert!(cache.get(Sources::Nyaa, &second).is_none());
+    let mut cache = ResultCache::new(Some(dir.join("first")), 60);
+    assert!(cache.get(Sources::Nyaa, &first).is_some());
+
+    let _ = std::fs::remove_dir_all(dir);
+}
@@ -0,0 +1,248 @@
+use std::collections::HashMap;
+
+use nyaa::{
+    cli::{
+        download::parse_client,
+        parse_source, registered_source,
+        search::{format_items, SearchArgs, SEARCH_USAGE},
+        watch::{WatchQuery, WatchRule},
+        Command, OutputFormat,
+    },
+    client::{
+        multidownload, registry, BatchDownloadResult, Client, ClientConfig, DownloadClient,
+        DownloadFuture, SingleDownloadResult,
+    },
+    config::Config,
+    source::{Item, ItemType, Sources},
+    widget::sort::SortDir,
+};
+
+#[test]
+fn test_search_args_to_query() {
+    let args = SearchArgs {
+        query: Some("frieren".to_owned()),
+        source: Some(Sources::Nyaa),
+        category: Some("anime-english-translated".to_owned()),
+        sort: Some("seeders".to_owned()),
+        sort_dir: Some(SortDir::Asc),
+        filter: Some("Trusted Only".to_owned()),
+        page: Some(2),
+        ..Default::default()
+    };
+    let query = args.to_query(Sources::Nyaa, &Config::default()).unwrap();
+    assert_eq!(query.query, "frieren");
+    assert_eq!(query.category, 12);
+    assert_eq!(query.sort.sort, 2);
+    assert!(query.sort.dir == SortDir::Asc);
+    assert_eq!(query.filter, 2);
+    assert_eq!(query.page, 2);
+
+    let args = SearchArgs {
+        category: Some("NotACategory".to_owned()),
+        ..Default::default()
+    };
+    assert!(args.to_query(Sources::Nyaa, &Config::default()).is_err());
+}
+
+#[test]
+fn test_command_help() {
+    // Asking for the usage of a command leaves printing it to the caller
+    let mut parser = lexopt::Parser::from_args(["--help"]);
+    let mut config_path = None;
+    let Ok(Command::Help(usage)) = Command::parse("search", &mut parser, &mut config_path) else {
+        panic!("expected the usage of the search command");
+    };
+    assert_eq!(usage, SEARCH_USAGE);
+
+    let mut parser = lexopt::Parser::from_args(["--bogus"]);
+    assert!(Command::parse("watch", &mut parser, &mut config_path).is_err());
+}
+
+#[test]
+fn test_registered_source() {
+    let config = Config::default();
+    let src = parse_source("nyaaa").unwrap();
+    let Err(err) = registered_source(src, &config) else {
+        panic!("expected an unknown source");
+    };
+    assert!(err.starts_with("Unknown source \"nyaaa\""));
+    assert!(err.contains("Nyaa, SukebeiNyaa"));
+    assert!(registered_source(parse_source("tgx").unwrap(), &config).is_ok());
+}
+
+#[test]
+fn test_format_items() {
+    let items = vec![Item {
+        id: "nyaa-1".to_owned(),
+        title: "[Group] Show, \"Title\"".to_owned(),
+        size: 1073741824,
+        seeders: 10,
+        extra: HashMap::from([("uploader".to_owned(), "someone".to_owned())]),
+        ..Default::default()
+    }];
+
+    let csv = format_items(&items, OutputFormat::Csv).unwrap();
+    let mut lines = csv.lines();
+    assert!(lines
+        .next()
+        .unwrap()
+        .starts_with("id,title,date,size,bytes"));
+    assert_eq!(
+        lines.next().unwrap(),
+        r#"nyaa-1,"[Group] Show, ""Title""",,1.0 GB,1073741824,10,0,0,,,,,uploader=someone"#
+    );
+
+    let tsv = format_items(&items, OutputFormat::Tsv).unwrap();
+    assert_eq!(
+        tsv.lines().nth(1).unwrap(),
+        "nyaa-1\t[Group] Show, \"Title\"\t\t1.0 GB\t1073741824\t10\t0\t0\t\t\t\t\tuploader=someone"
+    );
+
+    let json = format_items(&items, OutputFormat::Json).unwrap();
+    assert!(json.contains(r#""bytes": 1073741824"#));
+    assert!(json.contains(r#""uploader": "someone""#));
+}
+
+#[test]
+fn test_parse_client() {
+    assert!(parse_client("qbittorrent").unwrap() == Client::Qbit);
+    assert!(parse_client("Run Command").unwrap() == Client::Cmd);
+    assert!(parse_client("download-torrent-file").unwrap() == Client::Download);
+    assert!(parse_client("aria2").is_err());
+}
+
+struct InHouseClient;
+
+impl DownloadClient for InHouseClient {
+    fn name(&self) -> String {
+        "In House".to_owned()
+    }
+
+    fn download(
+        &self,
+        item: Item,
+        conf: ClientConfig,
+        _client: reqwest::Client,
+    ) -> DownloadFuture<SingleDownloadResult> {
+        Box::pin(async move {
+            let queue: String = conf.get("inhouse").unwrap_or_default();
+            SingleDownloadResult::success(format!("Queued in {}", queue), item.id)
+        })
+    }
+
+    fn batch_download(
+        &self,
+        items: Vec<Item>,
+        conf: ClientConfig,
+        client: reqwest::Client,
+    ) -> DownloadFuture<BatchDownloadResult> {
+        multidownload(
+            self,
+            |s| format!("Queued {} torrents", s),
+            &items,
+            &conf,
+            &client,
+        )
+    }
+
+    fn load_config(&self, cfg: &mut ClientConfig) {
+        if cfg.get::<String>("inhouse").is_none() {
+            cfg.set("inhouse", &"default").unwrap();
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_register_client() {
+    let client = registry()
+        .write()
+        .unwrap()
+        .register("InHouse", InHouseClient);
+    assert!(Client::all().last() == Some(&client));
+    assert!(parse_client("in-house").unwrap() == client);
+    assert!(parse_client("inhouse").unwrap() == client);
+
+    let mut conf = ClientConfig::default();
+    client.load_config(&mut conf);
+    let items = vec![
+        Item {
+            id: "1".to_owned(),
+            ..Default::default()
+        },
+        Item {
+            id: "2".to_owned(),
+            ..Default::default()
+        },
+    ];
+    let rq = reqwest::Client::new();
+    match client
+        .download(items[0].clone(), conf.clone(), rq.clone())
+        .await
+    {
+        SingleDownloadResult::Success(s) => assert_eq!(s.id, "1"),
+        SingleDownloadResult::Error(_) => panic!("expected success"),
+    }
+    let res = client.batch_download(items, conf, rq.clone()).await;
+    assert_eq!(res.ids.len(), 2);
+
+    let unknown = Client::new("Missing");
+    assert_eq!(unknown.to_string(), "Missing");
+    let res = unknown
+        .download(Item::default(), ClientConfig::default(), rq)
+        .await;
+    assert!(res.is_error());
+}
+
+#[test]
+fn test_watch_rule() {
+    let query = WatchQuery {
+        title: Some("(?i)\\[subsplease\\].*1080p".to_owned()),
+        uploader: Some("SubsPlease".to_owned()),
+        min_seeders: Some(5),
+        trusted_only: true,
+        ..Default::default()
+    };
+    let rule = WatchRule::new(&query, Sources::TorrentGalaxy).unwrap();
+    let item = Item {
+        title: "[SubsPlease] Show - 01 (1080p) [ABCD1234].mkv".to_owned(),
+        seeders: 10,
+        item_type: ItemType::Trusted,
+        extra: HashMap::from([("uploader".to_owned(), "subsplease".to_owned())]),
+        ..Default::default()
+    };
+    assert!(rule.matches(&item));
+    assert!(!rule.matches(&Item {
+        seeders: 2,
+        ..item.clone()
+    }));
+    assert!(!rule.matches(&Item {
+        item_type: ItemType::None,
+        ..item.clone()
+    }));
+    assert!(!rule.matches(&Item {
+        title: "[SubsPlease] Show - 01 (720p) [ABCD1234].mkv".to_owned(),
+        ..item.clone()
+    }));
+    assert!(!rule.matches(&Item {
+        extra: HashMap::from([("uploader".to_owned(), "someone".to_owned())]),
+        ..item.clone()
+    }));
+    assert!(!rule.matches(&Item {
+        extra: HashMap::new(),
+        ..item.clone()
+    }));
+
+    // Nyaa searches for the uploader instead, other sources can't check it
+    let rule = WatchRule::new(&query, Sources::Nyaa).unwrap();
+    assert!(rule.matches(&Item {
+        extra: HashMap::new(),
+        ..item.clone()
+    }));
+    assert!(WatchRule::new(&query, Sources::Torznab).is_err());
+
+    let query = WatchQuery {
+        title: Some("(unclosed".to_owned()),
+        ..Default::default()
+    };
+    assert!(WatchRule::new(&query, Sources::Nyaa).is_err());
+}
@@ -6,8 +6,8 @@ use nyaa::{
     client::{Client, ClientConfig, DownloadClientResult},
     config::{Config, ConfigManager},
     results::Results,
-    source::{Item, SourceExtraConfig, SourceResults},
-    sync::{EventSync, ReloadType},
+    source::{Item, SourceError, SourceExtraConfig, SourceResults},
+    sync::{DetailsResult, EventSync, ReloadType},
 };
 use ratatui::{
     backend::{Backend as _, TestBackend},
@@ -163,7 +163,7 @@ pub fn print_buffer(buf: &Buffer) {
 impl EventSync for TestSync {
     async fn load_results(
         self,
-        _tx_res: Sender<Result<SourceResults, Box<dyn Error + Send + Sync>>>,
+        _tx_res: Sender<Result<SourceResults, SourceError>>,
         _loadtype: nyaa::app::LoadType,
         _src: nyaa::source::Sources,
         _client: reqwest::Client,
@@ -192,6 +192,16 @@ impl EventSync for TestSync {
     ) {
     }
 
+    async fn load_details(
+        self,
+        _tx_details: Sender<DetailsResult>,
+        _src: nyaa::source::Sources,
+        _client: reqwest::Client,
+        _item: Item,
+        _config: nyaa::source::SourceConfig,
+    ) {
+    }
+
     async fn watch_config_loop(self, _tx_evt: Sender<ReloadType>) {}
 }
 
@@ -0,0 +1,59 @@
+use nyaa::{
+    results::filter::ResultFilter,
+    source::{Item, ItemType},
+};
+
+fn item(title: &str, seeders: u32, size: u64, item_type: ItemType) -> Item {
+    Item {
+        title: title.to_owned(),
+        seeders,
+        size,
+        item_type,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_result_filter() {
+    let gib = 1024 * 1024 * 1024;
+    let items = [
+        item("[Group] Show - 01 [1080p]", 50, gib, ItemType::Trusted),
+        item(
+            "[Group] Show - 01 [1080p HEVC]",
+            20,
+            gib / 2,
+            ItemType::None,
+        ),
+        item("[Other] Show - 01 [720p]", 5, gib / 4, ItemType::Remake),
+        item(
+            "[Group] Show - Batch [1080p]",
+            100,
+            10 * gib,
+            ItemType::Trusted,
+        ),
+    ];
+    let matching = |filter: &str| {
+        let filter = filter.parse::<ResultFilter>().unwrap();
+        items
+            .iter()
+            .enumerate()
+            .filter(|(_, i)| filter.matches(i))
+            .map(|(idx, _)| idx)
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(matching(""), [0, 1, 2, 3]);
+    assert_eq!(matching("res:1080p -hevc seeders>10 size<2GiB"), [0]);
+    assert_eq!(matching("\"show - 01\" -is:remake"), [0, 1]);
+    assert_eq!(matching("/\\[(720|1080)p\\]/"), [0, 2, 3]);
+    assert_eq!(matching("size>=1GiB seeders<=50"), [0]);
+    assert_eq!(matching("is:trusted -title:/batch/"), [0]);
+    assert_eq!(matching("group:other"), [2]);
+    assert_eq!(matching("codec:hevc"), [1]);
+    assert_eq!(matching("ep=1 res>=1080"), [0, 1]);
+
+    assert!("size<lots".parse::<ResultFilter>().is_err());
+    assert!("color:red".parse::<ResultFilter>().is_err());
+    assert!("/[unclosed/".parse::<ResultFilter>().is_err());
+    assert!("\"unclosed".parse::<ResultFilter>().is_err());
+}
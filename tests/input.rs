@@ -1,4 +1,4 @@
-use crossterm::event::{Event, KeyCode};
+use crossterm::event::{Event, KeyCode, KeyModifiers};
 use ratatui::buffer::Buffer;
 
 use crate::common::{reset_buffer, run_app, EventBuilder};
@@ -49,3 +49,59 @@ async fn test_search() {
         ])
     );
 }
+
+#[tokio::test]
+async fn test_search_history() {
+    let sync = EventBuilder::new()
+        .string("/one")
+        .enter()
+        .string("/")
+        .key_mod(KeyCode::Char('u'), KeyModifiers::CONTROL)
+        .string("two")
+        .enter()
+        .string("/")
+        .key_mod(KeyCode::Char('u'), KeyModifiers::CONTROL)
+        .string("draft")
+        .key(KeyCode::Up)
+        .key(KeyCode::Up)
+        .key(KeyCode::Up)
+        .key(KeyCode::Down)
+        .quit()
+        .build();
+
+    let res = reset_buffer(&run_app(sync, 60, 4).await.unwrap());
+    assert_eq!(
+        res,
+        Buffer::with_lines([
+            "┌Search (history 2/2)────────────────Press F1 or ? for help┐",
+            "│two                                                       │",
+            "└──────────────────────────────────────────────────────────┘",
+            "┌Results 1-0 (0 total): Page 1/0─dl: Run Command, src<Down>┐",
+        ])
+    );
+
+    let sync = EventBuilder::new()
+        .string("/one piece")
+        .enter()
+        .string("/")
+        .key_mod(KeyCode::Char('u'), KeyModifiers::CONTROL)
+        .string("two")
+        .enter()
+        .string("/")
+        .key_mod(KeyCode::Char('u'), KeyModifiers::CONTROL)
+        .string("oe")
+        .key_mod(KeyCode::Char('r'), KeyModifiers::CONTROL)
+        .quit()
+        .build();
+
+    let res = reset_buffer(&run_app(sync, 60, 4).await.unwrap());
+    assert_eq!(
+        res,
+        Buffer::with_lines([
+            r#"┌Search (history: "oe")──────────────Press F1 or ? for help┐"#,
+            "│one piece                                                 │",
+            "└──────────────────────────────────────────────────────────┘",
+            "┌Results 1-0 (0 total): Page 1/0─dl: Run Command, src:<C-r>┐",
+        ])
+    );
+}
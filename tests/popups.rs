@@ -1,4 +1,13 @@
 use crossterm::event::{KeyCode, KeyModifiers};
+use nyaa::{
+    history::HistoryEntry,
+    source::{Item, Sources},
+    sync::SearchQuery,
+    widget::{
+        bookmarks::{Bookmark, Bookmarks},
+        details::DetailsPopup,
+    },
+};
 use ratatui::buffer::Buffer;
 
 use crate::common::{reset_buffer, run_app, EventBuilder};
@@ -106,22 +115,22 @@ async fn test_sort() {
             r#"└──────────────────────────────────────────────────────────┘"#,
             r#"┌Results 1-0 (0 total): Page 1/0─dl: Run Command, src: Nyaa┐"#,
             r#"│                                                          │"#,
-            r#"│                                                          │"#,
-            r#"│                                                          │"#,
             r#"│              ┌Sort Descending─────────────┐              │"#,
             r#"│              │   Date                     │              │"#,
             r#"│              │   Downloads                │              │"#,
             r#"│              │  Seeders                  │              │"#,
             r#"│              │   Leechers                 │              │"#,
             r#"│              │   Size                     │              │"#,
+            r#"│              │   Group                    │              │"#,
+            r#"│              │   Episode                  │              │"#,
+            r#"│              │   Resolution               │              │"#,
+            r#"│              │   Codec                    │              │"#,
             r#"│              └────────────────────────────┘              │"#,
             r#"│                                                          │"#,
             r#"│                                                          │"#,
             r#"│                                                          │"#,
             r#"│                                                          │"#,
             r#"│                                                          │"#,
-            r#"│                                                          │"#,
-            r#"│                                                          │"#,
             r#"└─────────────────────────────────────────────────────────s┘"#,
         ])
     );
@@ -145,22 +154,22 @@ async fn test_sort_reverse() {
             r#"└──────────────────────────────────────────────────────────┘"#,
             r#"┌Results 1-0 (0 total): Page 1/0─dl: Run Command, src: Nyaa┐"#,
             r#"│                                                          │"#,
-            r#"│                                                          │"#,
-            r#"│                                                          │"#,
             r#"│              ┌Sort Ascending──────────────┐              │"#,
             r#"│              │   Date                     │              │"#,
             r#"│              │   Downloads                │              │"#,
             r#"│              │  Seeders                  │              │"#,
             r#"│              │   Leechers                 │              │"#,
             r#"│              │   Size                     │              │"#,
+            r#"│              │   Group                    │              │"#,
+            r#"│              │   Episode                  │              │"#,
+            r#"│              │   Resolution               │              │"#,
+            r#"│              │   Codec                    │              │"#,
             r#"│              └────────────────────────────┘              │"#,
             r#"│                                                          │"#,
             r#"│                                                          │"#,
             r#"│                                                          │"#,
             r#"│                                                          │"#,
             r#"│                                                          │"#,
-            r#"│                                                          │"#,
-            r#"│                                                          │"#,
             r#"└─────────────────────────────────────────────────────────S┘"#,
         ])
     );
@@ -264,11 +273,12 @@ async fn test_source() {
             r#"│                                                          │"#,
             r#"│                                                          │"#,
             r#"│                                                          │"#,
-            r#"│                                                          │"#,
             r#"│              ┌Source──────────────────────┐              │"#,
             r#"│              │   Nyaa                     │              │"#,
             r#"│              │  Sukebei                  │              │"#,
             r#"│              │   TorrentGalaxy            │              │"#,
+            r#"│              │   Torznab                  │              │"#,
+            r#"│              │   All sources              │              │"#,
             r#"│              └────────────────────────────┘              │"#,
             r#"│                                                          │"#,
             r#"│                                                          │"#,
@@ -277,7 +287,6 @@ async fn test_source() {
             r#"│                                                          │"#,
             r#"│                                                          │"#,
             r#"│                                                          │"#,
-            r#"│                                                          │"#,
             r#"└─────────────────────────────────────────────────────<C-s>┘"#,
         ])
     );
@@ -373,3 +382,51 @@ async fn test_page() {
         ])
     );
 }
+
+#[tokio::test]
+async fn test_bookmarks() {
+    let sync = EventBuilder::new().string("Bb").quit().build();
+
+    assert_eq!(
+        reset_buffer(&run_app(sync, 60, 10).await.unwrap()),
+        Buffer::with_lines([
+            r#"┌Search───────────────────────│Bookmarked "All Categories"│┐"#,
+            r#"│                             └───────────────────────────┘│"#,
+            r#"└──────────────────────────────────────────────────────────┘"#,
+            r#"┌Resu┌Bookmarks───────────────────────────────────────┐Nyaa┐"#,
+            r#"│    │ All Categories                   Nyaa          │    │"#,
+            r#"│    └────────────────────────────────────────────────┘    │"#,
+            r#"│                       Searching…                         │"#,
+            r#"│                                                          │"#,
+            r#"│                                                          │"#,
+            r#"└─────────────────────────────────────────────────────────b┘"#,
+        ])
+    );
+}
+
+#[test]
+fn test_bookmark_sources() {
+    let bookmark = |source| Bookmark {
+        name: "Weekly".to_owned(),
+        search: HistoryEntry::new(&SearchQuery::default(), source),
+    };
+    let mut bookmarks = Bookmarks::default();
+    assert!(!bookmarks.add(bookmark(Sources::Nyaa)));
+    assert!(!bookmarks.add(bookmark(Sources::SukebeiNyaa)));
+    // Only the bookmark with the same name on the same source is replaced
+    assert!(bookmarks.add(bookmark(Sources::Nyaa)));
+    assert_eq!(bookmarks.bookmarks.len(), 2);
+}
+
+#[test]
+fn test_details_sources() {
+    // Merged results from different sources may share an id
+    let mut details = DetailsPopup::default();
+    let item = Item {
+        id: "1".to_owned(),
+        ..Default::default()
+    };
+    details.show(Sources::Nyaa, item);
+    assert!(details.is_showing(Sources::Nyaa, "1"));
+    assert!(!details.is_showing(Sources::TorrentGalaxy, "1"));
+}
@@ -0,0 +1,135 @@
+use std::{
+    io::{Read as _, Write as _},
+    net::TcpListener,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use nyaa::source::{
+    mirror::Mirrors,
+    request::{self, Retry, RetryConfig},
+};
+
+/// Answer each connection with the next response, repeating the last one
+fn serve(responses: &'static [&'static str]) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/", listener.local_addr().unwrap());
+    std::thread::spawn(move || {
+        for (i, mut stream) in listener.incoming().flatten().enumerate() {
+            let mut buf = [0; 1024];
+            let _ = stream.read(&mut buf);
+            let response = responses[i.min(responses.len() - 1)];
+            let _ = write!(
+                stream,
+                "HTTP/1.1 {}\r\nContent-Length: 2\r\nConnection: close\r\n\r\nok",
+                response
+            );
+        }
+    });
+    url
+}
+
+#[tokio::test]
+async fn test_retry() {
+    request::configure(RetryConfig {
+        retries: 3,
+        backoff: 10,
+        max_backoff: 1,
+    });
+    let client = reqwest::Client::new();
+
+    let url = serve(&[
+        "429 Too Many Requests\r\nRetry-After: 0",
+        "503 Service Unavailable",
+        "200 OK",
+    ]);
+    let retries: Arc<Mutex<Vec<Retry>>> = Arc::default();
+    let r = retries.clone();
+    let response = request::report(
+        move |retry| r.lock().unwrap().push(retry),
+        request::send(client.get(&url)),
+    )
+    .await
+    .unwrap();
+    assert_eq!(response.status(), 200);
+    let retries = retries.lock().unwrap().clone();
+    assert_eq!(retries.len(), 2);
+    assert_eq!(retries[0].reason, "429 Too Many Requests");
+    assert_eq!(retries[0].delay, Duration::ZERO);
+    assert_eq!(retries[1].attempt, 2);
+    assert!(retries[1].delay <= Duration::from_millis(20));
+    assert_eq!(
+        retries[1].to_string(),
+        "503 Service Unavailable, retry 2/3 in 1s"
+    );
+
+    // The last response is returned once retries run out
+    let url = serve(&["503 Service Unavailable"]);
+    let response = request::send(client.get(&url)).await.unwrap();
+    assert_eq!(response.status(), 503);
+
+    // Other errors are not retried
+    let url = serve(&["404 Not Found", "200 OK"]);
+    let response = request::send(client.get(&url)).await.unwrap();
+    assert_eq!(response.status(), 404);
+}
+
+#[tokio::test]
+async fn test_mirror_retry() {
+    request::configure(RetryConfig {
+        retries: 3,
+        backoff: 10,
+        max_backoff: 1,
+    });
+    let client = reqwest::Client::new();
+    let dead = {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        format!("http://{}/", listener.local_addr().unwrap())
+    };
+    let send = |mirrors: Mirrors| {
+        let client = client.clone();
+        let retries: Arc<Mutex<Vec<Retry>>> = Arc::default();
+        let r = retries.clone();
+        async move {
+            let (base_url, response) = request::report(
+                move |retry| r.lock().unwrap().push(retry),
+                mirrors.send(|base_url| Ok(client.get(base_url.clone()))),
+            )
+            .await
+            .unwrap();
+            let retries = retries.lock().unwrap().clone();
+            (base_url.to_string(), response.status(), retries)
+        }
+    };
+
+    // A dead mirror is skipped without backing off
+    let up = serve(&["200 OK"]);
+    let (base_url, status, retries) =
+        send(Mirrors::new("retry-dead", &dead, std::slice::from_ref(&up))).await;
+    assert_eq!(base_url, up);
+    assert_eq!(status, 200);
+    assert!(retries.is_empty());
+
+    // Once every mirror failed, backoff applies and the first mirror is tried again
+    let down = serve(&["503 Service Unavailable"]);
+    let flaky = serve(&["503 Service Unavailable", "200 OK"]);
+    let (base_url, status, retries) = send(Mirrors::new(
+        "retry-down",
+        &down,
+        std::slice::from_ref(&flaky),
+    ))
+    .await;
+    assert_eq!(base_url, flaky);
+    assert_eq!(status, 200);
+    assert_eq!(retries.len(), 1);
+    assert_eq!(retries[0].reason, "503 Service Unavailable");
+
+    // Rate limits are retried on the same mirror
+    let limited = serve(&["429 Too Many Requests\r\nRetry-After: 0", "200 OK"]);
+    let up = serve(&["200 OK"]);
+    let (base_url, status, retries) = send(Mirrors::new("retry-limited", &limited, &[up])).await;
+    assert_eq!(base_url, limited);
+    assert_eq!(status, 200);
+    assert_eq!(retries.len(), 1);
+    assert_eq!(retries[0].delay, Duration::ZERO);
+}
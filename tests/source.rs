@@ -0,0 +1,1056 @@
+use nyaa::{
+    app::LoadType,
+    results::{filter::ResultFilter, ResultResponse, ResultTable, Results},
+    source::{
+        all::{merge_items, sort_items},
+        boxed, command,
+        cookies::CookieJar,
+        custom::{self, CustomSourceConfig},
+        mirror::{self, Mirrors},
+        nyaa_html::{parse_details, NyaaConfig},
+        pages::{self, AllPagesConfig},
+        registry,
+        release::{self, Release, ReleaseField},
+        request::{self, RetryConfig},
+        scrape::{Defaults, ParseReport},
+        torznab::{self, TorznabConfig},
+        InfoHash, Item, Source, SourceConfig, SourceError, SourceExtraConfig, SourceFuture,
+        SourceInfo, SourceResponse, Sources,
+    },
+    sync::SearchQuery,
+    util::cmd::CommandBuilder,
+    widget::sort::{SelectedSort, SortDir},
+};
+
+static VIEW_PAGE: &str = r##"<html><body><div class="container">
+<div class="panel panel-success">
+  <div class="panel-heading"><h3 class="panel-title">
+    [Group] Show - 01 [1080p]
+  </h3></div>
+  <div class="panel-body">
+    <div class="row">
+      <div class="col-md-1">Category:</div>
+      <div class="col-md-5"><a href="/?c=1_0">Anime</a> - <a href="/?c=1_2">English-translated</a></div>
+      <div class="col-md-1">Date:</div>
+      <div class="col-md-5" data-timestamp="1700000000">2023-11-14 22:13 UTC</div>
+    </div>
+    <div class="row">
+      <div class="col-md-1">Submitter:</div>
+      <div class="col-md-5"><a class="text-success" href="/user/uploader">uploader</a></div>
+      <div class="col-md-1">Seeders:</div>
+      <div class="col-md-5"><span style="color: green;">12</span></div>
+    </div>
+    <div class="row">
+      <div class="col-md-offset-6 col-md-1">Info hash:</div>
+      <div class="col-md-5"><kbd>0123456789abcdef0123456789abcdef01234567</kbd></div>
+    </div>
+  </div>
+</div>
+<div class="panel panel-default">
+  <div markdown-text class="panel-body" id="torrent-description">Line one
+Line &amp; two</div>
+</div>
+<div class="panel panel-default">
+  <div class="panel-heading"><h3 class="panel-title">File list</h3></div>
+  <div class="torrent-file-list panel-body">
+    <ul>
+      <li><a href="" class="folder"><i class="fa fa-folder-open"></i>Show</a>
+        <ul>
+          <li><i class="fa fa-file"></i>Show - 01.mkv <span class="file-size">(1.4 GiB)</span></li>
+        </ul>
+      </li>
+      <li><i class="fa fa-file"></i>readme.txt <span class="file-size">(12 Bytes)</span></li>
+    </ul>
+  </div>
+</div>
+<div id="comments" class="panel panel-default">
+  <div class="panel-heading"><a data-toggle="collapse" href="#collapse-comments">
+    <h3 class="panel-title">Comments - 2</h3>
+  </a></div>
+  <div class="collapse in" id="collapse-comments">
+    <div class="panel panel-default comment-panel" id="com-1">
+      <div class="panel-body">
+        <div class="col-md-2">
+          <p><a class="text-success" href="/user/uploader" title="Trusted">uploader</a> (uploader)</p>
+        </div>
+        <div class="col-md-10 comment">
+          <div class="row comment-details">
+            <a href="#com-1"><small data-timestamp="1700000100">2023-11-14 22:15 UTC</small></a>
+          </div>
+          <div class="row comment-body">
+            <div markdown-text class="comment-content" id="torrent-comment1">Fixed version soon</div>
+          </div>
+        </div>
+      </div>
+    </div>
+    <div class="panel panel-default comment-panel" id="com-2">
+      <div class="panel-body">
+        <div class="col-md-2">
+          <p><a class="text-default" href="/user/someone" title="User">someone</a></p>
+        </div>
+        <div class="col-md-10 comment">
+          <div class="row comment-details">
+            <a href="#com-2"><small data-timestamp="1700000200">2023-11-14 22:16 UTC</small></a>
+          </div>
+          <div class="row comment-body">
+            <div markdown-text class="comment-content" id="torrent-comment2">bad encode</div>
+          </div>
+        </div>
+      </div>
+    </div>
+  </div>
+</div>
+</div></body></html>"##;
+
+#[test]
+fn test_parse_details() {
+    let details = parse_details(VIEW_PAGE).unwrap();
+    assert_eq!(details.title, "[Group] Show - 01 [1080p]");
+    assert_eq!(details.submitter, "uploader");
+    assert_eq!(
+        details.info_hash,
+        "0123456789abcdef0123456789abcdef01234567"
+    );
+    assert_eq!(details.description, "Line one\nLine & two");
+    assert_eq!(details.comment_count, 2);
+
+    let files = details
+        .files
+        .iter()
+        .map(|f| (f.name.as_str(), f.size.as_deref(), f.depth))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        files,
+        [
+            ("Show", None, 0),
+            ("Show - 01.mkv", Some("1.4 GiB"), 1),
+            ("readme.txt", Some("12 Bytes"), 0),
+        ]
+    );
+
+    let comments = details
+        .comments
+        .iter()
+        .map(|c| {
+            (
+                c.author.as_str(),
+                c.date.as_str(),
+                c.body.as_str(),
+                c.trusted,
+                c.uploader,
+            )
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        comments,
+        [
+            (
+                "uploader",
+                "2023-11-14 22:15 UTC",
+                "Fixed version soon",
+                true,
+                true
+            ),
+            (
+                "someone",
+                "2023-11-14 22:16 UTC",
+                "bad encode",
+                false,
+                false
+            ),
+        ]
+    );
+}
+
+#[test]
+fn test_merge_items() {
+    let item = |id: &str, hash: &str, seeders: u32| {
+        let magnet_link = format!("magnet:?xt=urn:btih:{}&dn=name", hash.repeat(10));
+        Item {
+            id: id.to_owned(),
+            hash: InfoHash::from_magnet(&magnet_link),
+            magnet_link,
+            seeders,
+            ..Default::default()
+        }
+    };
+    let mut items = merge_items(vec![
+        (
+            Sources::Nyaa,
+            vec![item("nyaa-1", "AAAA", 5), item("nyaa-2", "bbbb", 1)],
+        ),
+        (
+            Sources::TorrentGalaxy,
+            vec![item("tgx-1", "aaaa", 10), item("tgx-2", "cccc", 3)],
+        ),
+    ]);
+    sort_items(
+        &mut items,
+        SelectedSort {
+            sort: 2, // Seeders
+            dir: SortDir::Desc,
+        },
+    );
+    let merged = items
+        .iter()
+        .map(|i| (i.id.as_str(), Sources::All.of_item(i).to_string()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        merged,
+        [
+            ("tgx-1", "TorrentGalaxy".to_owned()),
+            ("tgx-2", "TorrentGalaxy".to_owned()),
+            ("nyaa-2", "Nyaa".to_owned()),
+        ]
+    );
+}
+
+#[test]
+fn test_info_hash() {
+    let hex = "0102030405060708090a0b0c0d0e0f1011121314";
+    let hash = InfoHash::from_magnet(&format!("magnet:?xt=urn:btih:{}&dn=x", hex.to_uppercase()));
+    assert_eq!(hash.map(|h| h.to_string()).as_deref(), Some(hex));
+    // Base32 hashes refer to the same torrent
+    let base32 = InfoHash::from_magnet("magnet:?dn=x&xt=urn:btih:AEBAGBAFAYDQQCIKBMGA2DQPCAIREEYU");
+    assert_eq!(base32, hash);
+    assert!(InfoHash::from_magnet("magnet:?xt=urn:btih:abcdef").is_none());
+    assert_eq!(
+        serde_json::to_string(&hash).unwrap(),
+        format!("\"{}\"", hex)
+    );
+}
+
+#[test]
+fn test_format_date() {
+    let mut extra = SourceExtraConfig {
+        date_format: Some("%Y".to_owned()),
+        relative_date: Some(true),
+        relative_date_short: Some(true),
+    };
+    let date = chrono::Utc::now() - chrono::Duration::seconds(90);
+    assert_eq!(extra.format_date(date), "1m 30s");
+    // The selected item shows the absolute date along with relative dates
+    assert_eq!(
+        extra.format_absolute_date(date),
+        date.with_timezone(&chrono::Local).format("%Y").to_string()
+    );
+    extra.relative_date = None;
+    assert_eq!(
+        extra.format_date(date),
+        date.with_timezone(&chrono::Local).format("%Y").to_string()
+    );
+    assert_eq!(extra.format_date(Default::default()), "");
+}
+
+static TORZNAB_RESPONSE: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<rss version="2.0" xmlns:atom="http://www.w3.org/2005/Atom" xmlns:torznab="http://torznab.com/schemas/2015/feed">
+<channel>
+  <title>AggregateSearch</title>
+  <item>
+    <title>[Group] Show - 01 [1080p]</title>
+    <guid>https://tracker.example/details/1</guid>
+    <comments>https://tracker.example/details/1</comments>
+    <pubDate>Tue, 14 Nov 2023 22:13:20 +0000</pubDate>
+    <link>http://localhost:9117/dl/1.torrent</link>
+    <enclosure url="http://localhost:9117/dl/1.torrent" length="1503238553" type="application/x-bittorrent" />
+    <torznab:attr name="category" value="5070" />
+    <torznab:attr name="category" value="100001" />
+    <torznab:attr name="seeders" value="12" />
+    <torznab:attr name="peers" value="15" />
+    <torznab:attr name="grabs" value="40" />
+    <torznab:attr name="infohash" value="0123456789ABCDEF0123456789ABCDEF01234567" />
+  </item>
+  <item>
+    <title>Some Movie 2160p</title>
+    <guid>https://tracker.example/details/2</guid>
+    <pubDate>Wed, 15 Nov 2023 10:00:00 +0000</pubDate>
+    <link>http://localhost:9117/dl/2.torrent</link>
+    <torznab:attr name="category" value="2099" />
+    <torznab:attr name="size" value="1024" />
+    <torznab:attr name="seeders" value="3" />
+    <torznab:attr name="peers" value="3" />
+    <torznab:attr name="magneturl" value="magnet:?xt=urn:btih:abcdef0123456789abcdef0123456789abcdef01&amp;dn=movie" />
+  </item>
+</channel>
+</rss>"#;
+
+#[test]
+fn test_parse_torznab() {
+    let search = SearchQuery {
+        page: 1,
+        ..Default::default()
+    };
+    let res = torznab::parse_results(TORZNAB_RESPONSE, &search).unwrap();
+    assert_eq!((res.last_page, res.total_results), (1, 2));
+
+    // Sorted by date, newest first
+    let movie = &res.items[0];
+    assert_eq!(movie.title, "Some Movie 2160p");
+    assert_eq!(movie.category, 2000);
+    assert_eq!(movie.size, 1024);
+    assert_eq!(movie.id, "torznab-abcdef0123456789abcdef0123456789abcdef01");
+    assert_eq!(
+        movie.magnet_link,
+        "magnet:?xt=urn:btih:abcdef0123456789abcdef0123456789abcdef01&dn=movie"
+    );
+
+    let show = &res.items[1];
+    assert_eq!(show.category, 5070);
+    assert_eq!((show.seeders, show.leechers, show.downloads), (12, 3, 40));
+    assert_eq!(show.size, 1503238553);
+    assert_eq!(show.date.timestamp(), 1700000000);
+    assert_eq!(
+        show.hash.map(|h| h.to_string()).as_deref(),
+        Some("0123456789abcdef0123456789abcdef01234567")
+    );
+    assert_eq!(show.torrent_link, "http://localhost:9117/dl/1.torrent");
+    assert_eq!(show.post_link, "https://tracker.example/details/1");
+    assert!(show
+        .magnet_link
+        .starts_with("magnet:?xt=urn:btih:0123456789abcdef0123456789abcdef01234567&dn="));
+
+    let error = r#"<?xml version="1.0" encoding="UTF-8"?><error code="100" description="Invalid API Key" />"#;
+    assert_eq!(
+        torznab::parse_results(error, &search)
+            .err()
+            .map(|e| e.to_string()),
+        Some("Torznab error: Invalid API Key".to_owned())
+    );
+}
+
+static CUSTOM_CONFIG: &str = r#"
+name = "Example"
+url = "https://tracker.example/search?q={query}&p={page}&c={category}"
+date_format = "%Y-%m-%d"
+
+[selectors]
+row = "table.results tr.torrent"
+title = "td.name a@title"
+magnet = "td.links a.magnet"
+torrent = "td.links a.dl"
+post = "td.name a"
+size = "td.size"
+date = "td.date"
+seeders = "td.seeds"
+leechers = "td.peers"
+last_page = "ul.pages a"
+
+[[categories]]
+name = "Anime"
+value = "anime"
+icon = "Ani"
+color = "info"
+"#;
+
+static CUSTOM_PAGE: &str = r##"<html><body>
+<table class="results">
+  <tr class="torrent">
+    <td class="name"><a href="/t/1" title="[Group] Show - 01 [1080p]">[Group] Show - 01...</a></td>
+    <td class="links"><a class="dl" href="/dl/1.torrent">DL</a><a class="magnet" href="magnet:?xt=urn:btih:ABCDEF0123456789ABCDEF0123456789ABCDEF01&amp;dn=show">M</a></td>
+    <td class="size">1.5 GiB</td>
+    <td class="date">2023-11-14</td>
+    <td class="seeds">1,204</td>
+    <td class="peers">7</td>
+  </tr>
+  <tr class="torrent">
+    <td class="name"><a href="/t/2" title="Newer Show">Newer Show</a></td>
+    <td class="links"><a class="dl" href="https://cdn.example/2.torrent">DL</a></td>
+    <td class="size">300 MiB</td>
+    <td class="date">2023-11-20</td>
+    <td class="seeds">3</td>
+    <td class="peers">0</td>
+  </tr>
+  <tr class="torrent"><td class="name">No title link</td></tr>
+</table>
+<ul class="pages"><a>1</a><a>2</a><a>14</a><a>Next</a></ul>
+</body></html>"##;
+
+#[test]
+fn test_parse_custom() {
+    let config: CustomSourceConfig = toml::from_str(CUSTOM_CONFIG).unwrap();
+    let search = SearchQuery {
+        page: 1,
+        category: 1,
+        ..Default::default()
+    };
+    let url = "https://tracker.example/search?q=show".parse().unwrap();
+    let res = custom::parse_results(CUSTOM_PAGE, &url, &config, &search).unwrap();
+    assert_eq!((res.last_page, res.total_results), (14, 2));
+    // The row without a title is dropped, but most rows were read
+    let report = res.report.as_ref().unwrap();
+    assert_eq!((report.matched, report.parsed), (3, 2));
+    assert!(!report.is_broken());
+
+    // Sorted by date, newest first
+    let newer = &res.items[0];
+    assert_eq!(newer.title, "Newer Show");
+    assert_eq!(newer.torrent_link, "https://cdn.example/2.torrent");
+    assert_eq!(newer.id, "Example-2");
+    let extra = SourceExtraConfig {
+        date_format: Some("%d/%m/%Y".to_owned()),
+        relative_date: None,
+        relative_date_short: None,
+    };
+    assert_eq!(extra.format_date(newer.date), "20/11/2023");
+
+    let show = &res.items[1];
+    assert_eq!(show.title, "[Group] Show - 01 [1080p]");
+    assert_eq!(
+        show.magnet_link,
+        "magnet:?xt=urn:btih:ABCDEF0123456789ABCDEF0123456789ABCDEF01&dn=show"
+    );
+    assert_eq!(show.torrent_link, "https://tracker.example/dl/1.torrent");
+    assert_eq!(show.post_link, "https://tracker.example/t/1");
+    assert_eq!(show.id, "Example-abcdef0123456789abcdef0123456789abcdef01");
+    assert_eq!((show.seeders, show.leechers), (1204, 7));
+    assert_eq!(show.size, 1610612736);
+    assert_eq!(show.category, 1);
+    assert_eq!(show.icon.label, "Ani");
+
+    // Custom sources are listed after the built-in sources once registered
+    custom::register(&SourceConfig {
+        custom: vec![config],
+        ..Default::default()
+    });
+    assert_eq!(
+        Sources::all().last().map(ToString::to_string),
+        Some("Example".to_owned())
+    );
+}
+
+#[test]
+fn test_custom_ids() {
+    // Without a hash or post selector, ids fall back to the torrent link, then the row
+    let mut config: CustomSourceConfig = toml::from_str(CUSTOM_CONFIG).unwrap();
+    config.selectors.post = None;
+    let page = r#"<table class="results">
+  <tr class="torrent"><td class="name"><a title="Linked"></a></td><td class="links"><a class="dl" href="/dl/a.torrent">DL</a></td><td class="date">Yesterday</td></tr>
+  <tr class="torrent"><td class="name"><a title="Unlinked"></a></td></tr>
+  <tr class="torrent"><td class="name"><a title="Also unlinked"></a></td></tr>
+</table>"#;
+    let search = SearchQuery {
+        page: 2,
+        ..Default::default()
+    };
+    let url = "https://tracker.example/search?q=show".parse().unwrap();
+    let res = custom::parse_results(page, &url, &config, &search).unwrap();
+    let mut ids: Vec<&str> = res.items.iter().map(|i| i.id.as_str()).collect();
+    ids.sort();
+    assert_eq!(
+        ids,
+        [
+            "Example-2.2",
+            "Example-2.3",
+            "Example-https://tracker.example/dl/a.torrent"
+        ]
+    );
+    let linked = res.items.iter().find(|i| i.title == "Linked").unwrap();
+    assert_eq!(
+        linked.file_name,
+        "Example-https___tracker.example_dl_a.torrent.torrent"
+    );
+
+    // Dates which can't be read are shown as scraped
+    assert_eq!(
+        SourceExtraConfig::default().format_item_date(linked),
+        "Yesterday"
+    );
+}
+
+#[test]
+fn test_command_source() {
+    let info = command::parse_description(
+        r#"{
+            "categories": [{"name": "Anime", "entries": [
+                {"id": 0, "name": "All Anime", "icon": "Ani", "color": "info"},
+                {"id": 5, "name": "Raw", "icon": "Raw"}
+            ]}],
+            "sorts": ["Date", "Seeders"]
+        }"#,
+    )
+    .unwrap();
+    assert_eq!(info.filters, ["No Filter"]);
+    assert_eq!(info.sorts, ["Date", "Seeders"]);
+    assert_eq!(info.clone().entry_from_cfg("AllAnime").id, 0);
+
+    let res = command::parse_results(
+        r#"{"items": [{"id": "a1", "title": "Show", "category": 5, "extra": {"timestamp": "1700000000"}},
+                      {"id": "a2", "date": "2023-11-14T22:13:20Z", "size": "1.5 GiB"}],
+            "last_page": 3, "total_results": 0}"#,
+        &info,
+    )
+    .unwrap();
+    assert_eq!((res.last_page, res.total_results), (3, 2));
+    let item = &res.items[0];
+    assert_eq!(item.icon.label, "Raw");
+    assert_eq!(item.file_name, "a1.torrent");
+    assert_eq!(item.date.timestamp(), 1700000000);
+    assert_eq!(res.items[1].date, res.items[0].date);
+    assert_eq!(res.items[1].size, 1610612736);
+    assert!(command::parse_results("not json", &info).is_err());
+}
+
+#[cfg(unix)]
+#[test]
+fn test_command_output() {
+    use std::time::Duration;
+
+    let search = SearchQuery {
+        query: "show".to_owned(),
+        page: 2,
+        ..Default::default()
+    };
+    let input = serde_json::to_string(&search).unwrap();
+    let output = CommandBuilder::new("cat".to_owned())
+        .output(
+            None,
+            Some(input.to_owned()),
+            Some(Duration::from_secs(5)),
+            None,
+        )
+        .unwrap();
+    assert_eq!(output, input);
+    assert!(output.contains(r#""query":"show","page":2"#));
+
+    let timeout = CommandBuilder::new("sleep 5".to_owned())
+        .output(None, None, Some(Duration::from_millis(100)), None)
+        .err()
+        .map(|e| e.to_string())
+        .unwrap_or_default();
+    assert!(timeout.ends_with("Timed out after 0 seconds"));
+
+    assert!(CommandBuilder::new("exit 3".to_owned())
+        .output(None, None, None, None)
+        .is_err());
+
+    // Commands of aborted searches are killed
+    let cancel = std::sync::atomic::AtomicBool::new(true);
+    let cancelled = CommandBuilder::new("sleep 5".to_owned())
+        .output(None, None, None, Some(&cancel))
+        .err()
+        .map(|e| e.to_string())
+        .unwrap_or_default();
+    assert!(cancelled.ends_with("Cancelled"));
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_command_describe() {
+    let src = command::CommandSource::new(command::CommandSourceConfig {
+        name: "Described".to_owned(),
+        cmd: r#"f() { sleep 0.3; if [ "$1" = --describe ]; then echo '{"sorts": ["Date", "Seeders"]}'; else echo '{"items": [{"id": "c1", "title": "Show"}], "last_page": 1, "total_results": 1}'; fi; }; f"#.to_owned(),
+        timeout: 5,
+        ..Default::default()
+    });
+    // Described in the background, so the source has no sorts yet
+    assert_eq!(src.info().sorts, ["Default"]);
+
+    let res = src
+        .search(
+            &reqwest::Client::new(),
+            &SearchQuery::default(),
+            &SourceConfig::default(),
+            &SourceExtraConfig::default(),
+        )
+        .await;
+    let Ok(SourceResponse::Results(res)) = res else {
+        panic!("expected results");
+    };
+    assert_eq!(res.items[0].title, "Show");
+    assert_eq!(src.info().sorts, ["Date", "Seeders"]);
+    assert!(command::take_described().contains(&"Described".to_owned()));
+}
+
+struct EchoSource;
+
+impl Source for EchoSource {
+    fn name(&self) -> String {
+        "Echo".to_owned()
+    }
+
+    fn search<'a>(
+        &'a self,
+        _client: &'a reqwest::Client,
+        search: &'a SearchQuery,
+        config: &'a SourceConfig,
+        _extra: &'a SourceExtraConfig,
+    ) -> SourceFuture<'a, SourceResponse> {
+        boxed(async move {
+            let prefix: String = config.get("echo").unwrap_or_default();
+            Ok(SourceResponse::Results(ResultResponse {
+                items: vec![Item {
+                    title: format!("{}{}", prefix, search.query),
+                    ..Default::default()
+                }],
+                last_page: 1,
+                total_results: 1,
+                ..Default::default()
+            }))
+        })
+    }
+
+    fn info(&self) -> SourceInfo {
+        Sources::All.info()
+    }
+
+    fn load_config(&self, config: &mut SourceConfig) {
+        if config.get::<String>("echo").is_none() {
+            config.set("echo", &"echo: ").unwrap();
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_register_source() {
+    let echo = registry().write().unwrap().register("Echo", EchoSource);
+    assert!(Sources::all().contains(&echo));
+    assert_eq!(echo.to_string(), "Echo");
+
+    let mut config = SourceConfig::default();
+    echo.load_config(&mut config);
+    let search = SearchQuery {
+        query: "hello".to_owned(),
+        ..Default::default()
+    };
+    let extra = SourceExtraConfig {
+        date_format: None,
+        relative_date: None,
+        relative_date_short: None,
+    };
+    let client = reqwest::Client::new();
+    let res = echo
+        .load(LoadType::Sorting, &client, &search, &config, &extra)
+        .await;
+    match res {
+        Ok(SourceResponse::Results(res)) => assert_eq!(res.items[0].title, "echo: hello"),
+        _ => panic!("expected results"),
+    }
+
+    // Tables of other sources are kept when the config is saved
+    let saved = toml::to_string(&config).unwrap();
+    assert!(saved.contains("echo = \"echo: \""));
+    assert!(Sources::new("Missing").source().name() == "Missing");
+    assert!(Sources::new("Missing")
+        .load(LoadType::Searching, &client, &search, &config, &extra)
+        .await
+        .is_err());
+}
+
+/// Five pages of three items, where page 3 fails and page 2 repeats the last item of page 1
+struct PagedSource;
+
+impl Source for PagedSource {
+    fn name(&self) -> String {
+        "Paged".to_owned()
+    }
+
+    fn search<'a>(
+        &'a self,
+        _client: &'a reqwest::Client,
+        search: &'a SearchQuery,
+        _config: &'a SourceConfig,
+        _extra: &'a SourceExtraConfig,
+    ) -> SourceFuture<'a, SourceResponse> {
+        boxed(async move {
+            if search.page == 3 {
+                return Err(SourceError::Other("Page not found".to_owned()));
+            }
+            let first = search.page * 3 - usize::from(search.page == 2);
+            let items = (first..search.page * 3 + 3)
+                .map(|i| Item {
+                    id: i.to_string(),
+                    seeders: (i * 7 % 10) as u32,
+                    ..Default::default()
+                })
+                .collect();
+            Ok(SourceResponse::Results(ResultResponse {
+                items,
+                last_page: 5,
+                total_results: 15,
+                ..Default::default()
+            }))
+        })
+    }
+
+    fn info(&self) -> SourceInfo {
+        Sources::All.info()
+    }
+}
+
+#[tokio::test]
+async fn test_load_all_pages() {
+    let paged = registry().write().unwrap().register("Paged", PagedSource);
+    let search = SearchQuery {
+        // Seeders, most first
+        sort: SelectedSort {
+            sort: 2,
+            dir: SortDir::Desc,
+        },
+        ..Default::default()
+    };
+    let config = AllPagesConfig {
+        max_pages: 4,
+        concurrency: 2,
+        page_size: 4,
+    };
+    let progress = std::sync::Mutex::new(vec![]);
+    let res = pages::load_all(
+        paged,
+        &reqwest::Client::new(),
+        &search,
+        &SourceConfig::default(),
+        &SourceExtraConfig::default(),
+        config,
+        |done, total| progress.lock().unwrap().push((done, total)),
+    )
+    .await;
+    let Ok(SourceResponse::Results(res)) = res else {
+        panic!("expected results");
+    };
+    assert_eq!(*progress.lock().unwrap(), [(1, 4), (2, 4), (3, 4), (4, 4)]);
+    assert_eq!(
+        res.errors,
+        [
+            "Only the first 4 of 5 pages were loaded",
+            "Page 3:\nPage not found"
+        ]
+    );
+    // Pages 1, 2 and 4 without the repeated item, sorted by seeders
+    let ids: Vec<&str> = res.items.iter().map(|i| i.id.as_str()).collect();
+    assert_eq!(ids, ["7", "4", "14", "8", "5", "12", "6", "3", "13"]);
+
+    let mut results = Results::all_pages(search, res);
+    let filter = "seeders>=2".parse::<ResultFilter>().unwrap();
+    results.paginate(2, config.page_size, Some(&filter));
+    let ids: Vec<&str> = results
+        .response
+        .items
+        .iter()
+        .map(|i| i.id.as_str())
+        .collect();
+    assert_eq!(ids, ["5", "12", "6"]);
+    assert_eq!(results.response.last_page, 2);
+    assert_eq!(results.response.total_results, 7);
+
+    let mut items = results.all.unwrap();
+    pages::sort_items(&mut items, "Seeders", SortDir::Asc);
+    assert_eq!(items[0].id, "3");
+    // Sorts which can't be done locally keep the order
+    pages::sort_items(&mut items, "Completed", SortDir::Desc);
+    assert_eq!(items[0].id, "3");
+}
+
+#[test]
+fn test_append_results() {
+    let item = |id: &str| Item {
+        id: id.to_owned(),
+        ..Default::default()
+    };
+    let mut results = Results::new(
+        SearchQuery {
+            page: 1,
+            ..Default::default()
+        },
+        ResultResponse {
+            items: vec![item("1"), item("2")],
+            last_page: 3,
+            total_results: 6,
+            ..Default::default()
+        },
+        ResultTable::default(),
+    );
+    results.append(ResultResponse {
+        items: vec![item("2"), item("3")],
+        last_page: 4,
+        total_results: 8,
+        ..Default::default()
+    });
+    let ids: Vec<&str> = results
+        .response
+        .items
+        .iter()
+        .map(|i| i.id.as_str())
+        .collect();
+    assert_eq!(ids, ["1", "2", "3"]);
+    assert_eq!(results.response.last_page, 4);
+    assert_eq!(results.response.total_results, 8);
+
+    // Items hidden by a local filter are appended to all loaded items
+    results.unfiltered = Some(results.response.items.clone());
+    results.response.items.truncate(1);
+    results.append(ResultResponse {
+        items: vec![item("4")],
+        ..Default::default()
+    });
+    assert_eq!(results.response.items.len(), 1);
+    assert_eq!(results.unfiltered.map(|u| u.len()), Some(4));
+}
+
+#[test]
+fn test_parse_release() {
+    let release = Release::parse("[SubsPlease] Some Show - 12v2 (1080p) [ABcd1234].mkv");
+    assert_eq!(
+        release,
+        Release {
+            group: Some("SubsPlease".to_owned()),
+            series: Some("Some Show".to_owned()),
+            episode: Some("12v2".to_owned()),
+            resolution: Some("1080p".to_owned()),
+            codec: None,
+            source: None,
+            crc: Some("ABCD1234".to_owned()),
+        }
+    );
+    let release = Release::parse("Some.Show.S01E05.2160p.WEB-DL.x265-GROUP");
+    assert_eq!(release.group.as_deref(), Some("GROUP"));
+    assert_eq!(release.series.as_deref(), Some("Some Show"));
+    assert_eq!(release.episode.as_deref(), Some("S01E05"));
+    assert_eq!(release.resolution.as_deref(), Some("2160p"));
+    assert_eq!(release.codec.as_deref(), Some("HEVC"));
+    assert_eq!(release.source.as_deref(), Some("WEB"));
+    let release = Release::parse("[Group] Show - 01-12 (BD 720p AVC)");
+    assert_eq!(release.episode.as_deref(), Some("01-12"));
+    assert_eq!(release.source.as_deref(), Some("BD"));
+
+    let item = |title: &str| Item {
+        title: title.to_owned(),
+        ..Default::default()
+    };
+    let mut items = vec![
+        item("[B] Show - 10 [720p]"),
+        item("[A] Show - 2 [1080p]"),
+        item("Show Batch"),
+    ];
+    // Values set by the source are kept
+    items[0]
+        .extra
+        .insert("group".to_owned(), "Source Group".to_owned());
+    release::annotate(&mut items);
+    assert_eq!(items[0].extra["group"], "Source Group");
+    assert_eq!(items[1].extra["resolution"], "1080p");
+    assert_eq!(ReleaseField::Episode.get(&items[2]), None);
+
+    // Episodes are compared as numbers, items without one come first
+    pages::sort_items(&mut items, "Episode", SortDir::Asc);
+    let titles: Vec<&str> = items.iter().map(|i| i.title.as_str()).collect();
+    assert_eq!(
+        titles,
+        ["Show Batch", "[A] Show - 2 [1080p]", "[B] Show - 10 [720p]"]
+    );
+}
+
+/// Serve every connection with the same status line, returning the base url
+fn serve(status: &'static str) -> String {
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/", listener.local_addr().unwrap());
+    std::thread::spawn(move || {
+        for mut stream in listener.incoming().flatten() {
+            use std::io::{Read as _, Write as _};
+            let mut buf = [0; 1024];
+            let _ = stream.read(&mut buf);
+            let _ = write!(
+                stream,
+                "HTTP/1.1 {}\r\nContent-Length: 2\r\nConnection: close\r\n\r\nok",
+                status
+            );
+        }
+    });
+    url
+}
+
+#[tokio::test]
+async fn test_mirror_failover() {
+    // Fail over right away instead of retrying each mirror
+    request::configure(RetryConfig {
+        retries: 0,
+        ..Default::default()
+    });
+    let dead = {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        format!("http://{}/", listener.local_addr().unwrap())
+    };
+    let down = serve("503 Service Unavailable");
+    let up = serve("200 OK");
+    let client = reqwest::Client::new();
+
+    let mirrors = Mirrors::new("test", &dead, &[down.clone(), up.clone()]);
+    let (base_url, response) = mirrors
+        .send(|base_url| Ok(client.get(base_url.clone())))
+        .await
+        .unwrap();
+    assert_eq!(base_url.as_str(), up);
+    assert_eq!(response.status(), 200);
+    assert_eq!(mirror::healthy("test"), Some(up.clone()));
+
+    // The healthy mirror is tried first for the rest of the session
+    let mirrors = Mirrors::new("test", &dead, &[down.clone(), up.clone()]);
+    assert_eq!(mirrors.first().unwrap().as_str(), up);
+
+    // The last mirror's response is kept so the source can report it
+    let mirrors = Mirrors::new("test-down", &dead, &[down]);
+    let (_, response) = mirrors
+        .send(|base_url| Ok(client.get(base_url.clone())))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 503);
+    assert_eq!(mirror::healthy("test-down"), None);
+
+    // Errors stay typed when every mirror is unreachable, so they can be retried
+    let also_dead = {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        format!("http://{}/", listener.local_addr().unwrap())
+    };
+    let mirrors = Mirrors::new("test-dead", &dead, &[also_dead]);
+    let Err(e) = mirrors
+        .send(|base_url| Ok(client.get(base_url.clone())))
+        .await
+    else {
+        panic!("dead mirrors responded");
+    };
+    let e = SourceError::from(e);
+    assert!(matches!(e, SourceError::Connect { .. }));
+    assert!(e.is_transient());
+}
+
+#[tokio::test]
+async fn test_source_errors() {
+    request::configure(RetryConfig {
+        retries: 0,
+        ..Default::default()
+    });
+    let client = reqwest::Client::new();
+    let search = SearchQuery {
+        page: 1,
+        ..Default::default()
+    };
+    let extra = SourceExtraConfig {
+        date_format: None,
+        relative_date: None,
+        relative_date_short: None,
+    };
+    let load = |config: SourceConfig, src: Sources| {
+        let (client, search, extra) = (&client, &search, &extra);
+        async move {
+            src.load(LoadType::Searching, client, search, &config, extra)
+                .await
+        }
+    };
+
+    let config = SourceConfig {
+        nyaa: Some(NyaaConfig {
+            base_url: serve("404 Not Found"),
+            ..Default::default()
+        }),
+        ..Default::default()
+    };
+    match load(config, Sources::Nyaa).await {
+        Err(e @ SourceError::Http { status: 404, .. }) => assert!(!e.is_transient()),
+        _ => panic!("expected an http error"),
+    }
+
+    let config = SourceConfig {
+        torznab: Some(TorznabConfig {
+            base_url: serve("401 Unauthorized"),
+            ..Default::default()
+        }),
+        ..Default::default()
+    };
+    match load(config, Sources::Torznab).await {
+        Err(SourceError::Auth { key, .. }) => assert_eq!(key, "source.torznab.apikey"),
+        _ => panic!("expected an auth error"),
+    }
+
+    match load(SourceConfig::default(), Sources::new("Missing")).await {
+        Err(SourceError::Config { key, .. }) => assert_eq!(key, "default_source"),
+        _ => panic!("expected a config error"),
+    }
+}
+
+#[test]
+fn test_parse_report() {
+    let rows: Vec<Result<usize, &'static str>> = vec![Ok(1), Err("id"), Err("id"), Err("title")];
+    let (items, report) = ParseReport::collect(rows);
+    assert_eq!(items, vec![1]);
+    assert_eq!((report.matched, report.parsed), (4, 1));
+    assert_eq!(report.failed, vec![("id", 2), ("title", 1)]);
+    assert!(report.is_broken());
+    assert_eq!(
+        report.to_string(),
+        "Read 1 of 4 rows, \"id\" failed in 2 of them"
+    );
+
+    let report = report.page("<html></html>");
+    let dir = std::env::temp_dir().join(format!("nyaa-test-{}", std::process::id()));
+    let path = report.dump(&dir, "Sukebei Nyaa").unwrap();
+    assert!(path
+        .file_name()
+        .unwrap()
+        .to_string_lossy()
+        .starts_with("sukebei_nyaa-"));
+    let dump = std::fs::read_to_string(&path).unwrap();
+    assert!(dump.ends_with("<html></html>"));
+    let _ = std::fs::remove_dir_all(dir);
+
+    // An empty page is broken if it claims to have results, or looks like it lists some
+    let (_, report) = ParseReport::collect(Vec::<Result<usize, &'static str>>::new());
+    assert!(!report.clone().expect(None).is_broken());
+    assert!(report.clone().expect(Some(20)).is_broken());
+    let listed = report.listing(true);
+    assert!(listed.is_broken());
+    assert!(!listed.clone().expect(Some(0)).is_broken());
+    assert_eq!(
+        listed.to_string(),
+        "No rows matched, although the page does not say nothing was found"
+    );
+
+    // Fields left empty are counted without dropping their rows
+    let mut defaults = Defaults::default();
+    let rows: Vec<Result<u64, &'static str>> = [Some(1), None, None]
+        .into_iter()
+        .map(|date| Ok(defaults.or_default(date, "date")))
+        .collect();
+    let (items, report) = ParseReport::collect(rows);
+    let report = report.defaults(defaults);
+    assert_eq!(items, vec![1, 0, 0]);
+    assert!(report.is_broken());
+    assert_eq!(
+        report.to_string(),
+        "Read 3 of 3 rows, \"date\" was left empty in 2 of them"
+    );
+}
+
+#[test]
+fn test_cookie_jar() {
+    let txt = "# Netscape HTTP Cookie File\n\
+        .nyaa.si\tTRUE\t/\tTRUE\t4102444800\tcf_clearance\tabc\n\
+        #HttpOnly_nyaa.si\tFALSE\t/\tFALSE\t0\tsession\txyz\n\
+        nyaa.si\tFALSE\t/\tFALSE\t1000\texpired\told\n\
+        example.com\tFALSE\t/\tFALSE\t4102444800\tother\tnope\n";
+    let jar = CookieJar::default();
+    assert_eq!(jar.import(txt, &["nyaa.si".to_owned()]), 2);
+
+    let url = "https://nyaa.si/?q=test".parse().unwrap();
+    let cookies = reqwest::cookie::CookieStore::cookies(&jar, &url).unwrap();
+    let cookies = cookies.to_str().unwrap();
+    assert!(cookies.contains("cf_clearance=abc"));
+    assert!(cookies.contains("session=xyz"));
+    assert!(!cookies.contains("expired"));
+    let url = "https://example.com/".parse().unwrap();
+    assert!(reqwest::cookie::CookieStore::cookies(&jar, &url).is_none());
+
+    // Session cookies are not kept between sessions
+    let dir = std::env::temp_dir().join(format!("nyaa-test-cookies-{}", std::process::id()));
+    let path = dir.join("cookies.json");
+    jar.save(&path).unwrap();
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt as _;
+        let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+    let jar = CookieJar::load(&path).unwrap();
+    let url = "https://nyaa.si/".parse().unwrap();
+    let cookies = reqwest::cookie::CookieStore::cookies(&jar, &url).unwrap();
+    assert_eq!(cookies.to_str().unwrap(), "cf_clearance=abc");
+    let _ = std::fs::remove_dir_all(dir);
+}